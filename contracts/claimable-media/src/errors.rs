@@ -5,6 +5,10 @@ use err_derive::Error;
 pub enum UpdateClaimableMediaError {
     #[error(display = "only the creator may update the claimable media")]
     Unauthorized,
+    #[error(display = "the claimable media is frozen")]
+    Frozen,
+    #[error(display = "the claimable media cannot move to the requested state")]
+    InvalidTransition,
 }
 
 #[derive(Debug, Error, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -12,9 +16,28 @@ pub enum UpdateClaimableMediaError {
 pub enum ProposeDistributionError {
     #[error(display = "only artists part of the media may propose a distribution")]
     Unauthorized,
+    #[error(display = "{}", _0)]
+    Update(#[error(source)] UpdateClaimableMediaError),
 }
 
 
+#[derive(Debug, Error, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum BuyoutError {
+    #[error(display = "only artists part of the media may buy out another artist")]
+    Unauthorized,
+    #[error(display = "the account is not an artist of the media")]
+    NotAnArtist,
+    #[error(display = "the artist has not consented to being bought out")]
+    NoConsent,
+    #[error(display = "the price is below the minimum the artist consented to")]
+    PriceTooLow,
+    #[error(display = "an erc20 error occurred: {}", _0)]
+    Erc20(#[error(source)] erc20::Error),
+    #[error(display = "{}", _0)]
+    Update(#[error(source)] UpdateClaimableMediaError),
+}
+
 #[derive(Debug, Error, PartialEq, Eq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum ValidateDistributionError {
@@ -24,4 +47,6 @@ pub enum ValidateDistributionError {
     NotPending,
     #[error(display = "distribution not found")]
     NotFound,
+    #[error(display = "{}", _0)]
+    Update(#[error(source)] UpdateClaimableMediaError),
 }
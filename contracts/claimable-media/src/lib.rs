@@ -10,22 +10,23 @@ mod models;
 use ink_lang as ink;
 
 pub use crate::claimable_media::ClaimableMedia;
-pub use errors::{ProposeDistributionError, UpdateClaimableMediaError};
+pub use errors::{BuyoutError, ProposeDistributionError, UpdateClaimableMediaError};
 
 #[ink::contract]
 mod claimable_media {
     use crate::{
         constants::WEEK,
-        errors::{ProposeDistributionError, UpdateClaimableMediaError, ValidateDistributionError},
+        errors::{BuyoutError, ProposeDistributionError, UpdateClaimableMediaError, ValidateDistributionError},
         models::{
             ClaimableMediaInfo, ClaimableMediaState, CreateClaimableMediaRequest, Distribution,
             DistributionProposalState,
         },
     };
+    use erc1620::StreamId;
     use ink_prelude::{collections::BTreeMap, string::String, vec::Vec};
     use ink_storage::collections::HashMap as StorageHashmap;
     use media::{
-        models::{CreateMediaRequest, MediaType},
+        models::{CollabShare, CollabShareExt, CreateMediaRequest},
         MediaStorage as Media,
     };
 
@@ -41,6 +42,13 @@ mod claimable_media {
         media_id: u64,
         erc1620: erc1620::Erc1620,
         erc20: erc20::Erc20,
+        is_frozen: bool,
+        /// How long a distribution proposal can be validated after it is proposed
+        voting_window: Timestamp,
+        /// Artists that agreed to be bought out by the other artists, with the minimum price they accept
+        buyout_consents: StorageHashmap<AccountId, Balance>,
+        /// The payment streams the media contract opened for this claimable media
+        stream_ids: StorageHashmap<StreamId, ()>,
     }
 
     #[ink(event)]
@@ -48,25 +56,58 @@ mod claimable_media {
         account: AccountId,
     }
 
+    #[ink(event)]
+    pub struct ArtistBoughtOut {
+        artist: AccountId,
+        buyer: AccountId,
+        price: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Frozen {
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unfrozen {
+        account: AccountId,
+    }
+
     impl ClaimableMedia {
         /// Creates a new claimable media, and an associated media object using the passed media contract.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the voting window is zero.
         #[ink(constructor)]
         pub fn new(request: CreateClaimableMediaRequest) -> Self {
-            let CreateClaimableMediaRequest { erc20, nft_info, name, view_info, artists, media, erc1620 } = request;
+            let CreateClaimableMediaRequest {
+                erc20,
+                nft_info,
+                name,
+                view_info,
+                artists,
+                media,
+                media_type,
+                erc1620,
+                voting_window,
+            } = request;
+            let voting_window = voting_window.unwrap_or(WEEK);
+            assert!(voting_window > 0, "the voting window must not be zero");
             let created_at = Self::env().block_timestamp();
             let creator = Self::env().caller();
             let contract_account_id = Self::env().account_id();
             let mut media = media;
 
             let mut collabs = BTreeMap::new();
-            collabs.insert(contract_account_id, ::media::constants::COLLAB_SHARE_COUNT);
+            collabs.insert(contract_account_id, CollabShare::from_percent(100));
 
             let media_id = media
                 .create_media(CreateMediaRequest {
                     creator_address: Self::env().caller(),
                     media_name: name.clone(),
                     pod_address: Self::env().account_id(),
-                    r#type: MediaType::Audio,
+                    r#type: media_type,
                     view_conditions: view_info,
                     nft_conditions: nft_info,
                     royalty: 1,
@@ -74,6 +115,12 @@ mod claimable_media {
                 })
                 .expect("unable to create media");
 
+            // the claimable media is the pod of its media, so it registers and uploads it to make it viewable
+            let mut media_info = media.get_media(media_id).expect("media was just created");
+            media_info.is_registered = true;
+            media_info.is_uploaded = true;
+            media.update_media(media_info.into()).expect("unable to register media");
+
             let artists = artists.into_iter().map(|id| (id, ())).collect();
 
             Self {
@@ -87,6 +134,10 @@ mod claimable_media {
                 media,
                 media_id,
                 erc1620,
+                is_frozen: false,
+                voting_window,
+                buyout_consents: Default::default(),
+                stream_ids: Default::default(),
             }
         }
 
@@ -107,7 +158,64 @@ mod claimable_media {
             Ok(())
         }
 
-        /// Change the state of the media.
+        /// Records whether the calling artist agrees to be bought out through `buyout_artist`, and for which
+        /// minimum price. `None` withdraws the consent.
+        ///
+        /// # Restrictions
+        ///
+        /// May only be called by one of the artists.
+        #[ink(message)]
+        pub fn set_buyout_consent(&mut self, min_price: Option<Balance>) -> Result<(), BuyoutError> {
+            let caller = self.env().caller();
+            self.is_artist(caller).then_some(()).ok_or(BuyoutError::Unauthorized)?;
+
+            match min_price {
+                Some(min_price) => self.buyout_consents.insert(caller, min_price),
+                None => self.buyout_consents.take(&caller),
+            };
+            Ok(())
+        }
+
+        /// The minimum price `artist` agreed to be bought out for, if they agreed.
+        #[ink(message)]
+        pub fn buyout_consent(&self, artist: AccountId) -> Option<Balance> {
+            self.buyout_consents.get(&artist).copied()
+        }
+
+        /// Buys out `artist`, who then stops being an artist of the media. The caller pays `price` in the media's
+        /// erc20 token to the departing artist, and must have approved the transfer beforehand.
+        ///
+        /// # Restrictions
+        ///
+        /// * May only be called by one of the other artists.
+        /// * `artist` must have consented through `set_buyout_consent`, for at most `price`.
+        /// * The claimable media may not be frozen.
+        #[ink(message)]
+        pub fn buyout_artist(&mut self, artist: AccountId, price: Balance) -> Result<(), BuyoutError> {
+            let caller = self.env().caller();
+
+            self.ensure_not_frozen()?;
+
+            if !self.is_artist(caller) || caller == artist {
+                return Err(BuyoutError::Unauthorized);
+            }
+            if !self.is_artist(artist) {
+                return Err(BuyoutError::NotAnArtist);
+            }
+            let min_price = self.buyout_consent(artist).ok_or(BuyoutError::NoConsent)?;
+            if price < min_price {
+                return Err(BuyoutError::PriceTooLow);
+            }
+
+            erc20_transfer_from(&mut self.erc20, caller, artist, price)?;
+            self.remove_artist(artist);
+            self.env().emit_event(ArtistBoughtOut { artist, buyer: caller, price });
+
+            Ok(())
+        }
+
+        /// Change the state of the media. See `ClaimableMediaState::can_transition` for the allowed
+        /// transitions.
         ///
         /// # Restrictions
         ///
@@ -117,12 +225,56 @@ mod claimable_media {
             if self.creator != self.env().caller() {
                 return Err(UpdateClaimableMediaError::Unauthorized);
             }
+            if !ClaimableMediaState::can_transition(&self.state, &media_state) {
+                return Err(UpdateClaimableMediaError::InvalidTransition);
+            }
 
             self.state = media_state;
 
             Ok(())
         }
 
+        /// Freezes the claimable media, which blocks proposing and validating distributions until it is
+        /// unfrozen.
+        ///
+        /// # Restrictions
+        ///
+        /// May only be called by the creator of the claimable media.
+        #[ink(message)]
+        pub fn freeze(&mut self) -> Result<(), UpdateClaimableMediaError> {
+            let caller = self.env().caller();
+            if self.creator != caller {
+                return Err(UpdateClaimableMediaError::Unauthorized);
+            }
+
+            self.is_frozen = true;
+            self.env().emit_event(Frozen { account: caller });
+
+            Ok(())
+        }
+
+        /// Unfreezes the claimable media.
+        ///
+        /// # Restrictions
+        ///
+        /// May only be called by the creator of the claimable media.
+        #[ink(message)]
+        pub fn unfreeze(&mut self) -> Result<(), UpdateClaimableMediaError> {
+            let caller = self.env().caller();
+            if self.creator != caller {
+                return Err(UpdateClaimableMediaError::Unauthorized);
+            }
+
+            self.is_frozen = false;
+            self.env().emit_event(Unfrozen { account: caller });
+
+            Ok(())
+        }
+
+        /// Whether the claimable media is currently frozen.
+        #[ink(message)]
+        pub fn is_frozen(&self) -> bool { self.is_frozen }
+
         #[ink(message)]
         pub fn info(&self) -> ClaimableMediaInfo {
             let artists = self.artists.keys().cloned().collect();
@@ -138,6 +290,7 @@ mod claimable_media {
                 media_id: self.media_id,
                 erc1620: self.erc1620.clone(),
                 erc20: self.erc20.clone(),
+                voting_window: self.voting_window,
             }
         }
 
@@ -160,7 +313,8 @@ mod claimable_media {
         ///
         /// # Restrictions
         ///
-        /// May only be called by one of the artists.
+        /// * May only be called by one of the artists.
+        /// * The claimable media may not be frozen.
         #[ink(message)]
         pub fn propose_distribution(
             &mut self,
@@ -169,6 +323,8 @@ mod claimable_media {
             let caller = self.env().caller();
             let now = self.env().block_timestamp();
 
+            self.ensure_not_frozen()?;
+
             self.is_artist(caller).then_some(()).ok_or(ProposeDistributionError::Unauthorized)?;
 
             self.distributions.insert(caller, Distribution {
@@ -184,18 +340,21 @@ mod claimable_media {
         }
 
         /// Votes and validates the claimable media. If all artists agreed to a distribution, that
-        /// distribution is finalized. If one artists votes against the proposal, it is permanently
-        /// denied
+        /// distribution is finalized. If one artists votes against the proposal, or the voting window
+        /// has passed, it is permanently denied
         ///
         /// # Restrictions
         ///
-        /// May only be called by one of the artists.
+        /// * May only be called by one of the artists.
+        /// * The claimable media may not be frozen.
         #[ink(message)]
         pub fn validate(&mut self, proposer: AccountId, accept: bool) -> Result<(), ValidateDistributionError> {
             let caller = self.env().caller();
             let now = self.env().block_timestamp();
             let contract_account_id = self.env().account_id();
 
+            self.ensure_not_frozen()?;
+
             self.is_artist(caller).then_some(()).ok_or(ValidateDistributionError::Unauthorized)?;
             let mut distribution = self.distributions.get_mut(&proposer).ok_or(ValidateDistributionError::NotFound)?;
 
@@ -207,7 +366,7 @@ mod claimable_media {
 
             let time_since_proposed = now - distribution.created_at;
 
-            if !accept || time_since_proposed > WEEK {
+            if !accept || time_since_proposed > self.voting_window {
                 distribution.state = DistributionProposalState::Denied;
                 return Ok(());
             }
@@ -218,7 +377,8 @@ mod claimable_media {
             if accepted {
                 // by failing on transfers, we roll back the state, that's better than arriving at an
                 // inconsistent state.
-                self.erc1620.withdraw_from_all_streams().expect("withdrawing from own streams");
+                self.sync_streams();
+                self.withdraw_from_own_streams().expect("withdrawing from own streams");
                 let total = self.erc20.balance_of(contract_account_id);
 
                 let creator_share = total / 1000;
@@ -236,7 +396,56 @@ mod claimable_media {
             Ok(())
         }
 
+        /// Tracks the payment streams the media contract opened for this claimable media. Only tracked streams are
+        /// withdrawn from when a distribution is accepted, as the erc1620 contract may hold other streams to this
+        /// contract.
+        #[ink(message)]
+        pub fn sync_streams(&mut self) {
+            for stream_id in self.media.get_media_streams(self.media_id) {
+                self.stream_ids.insert(stream_id, ());
+            }
+        }
+
+        /// Returns the tracked payment streams of this claimable media.
+        #[ink(message)]
+        pub fn stream_ids(&self) -> Vec<StreamId> { self.stream_ids.keys().copied().collect() }
+
+        /// Withdraws the available balance of every tracked stream and returns the total. Streams the erc1620
+        /// contract no longer knows, because they were drained or cancelled, are no longer tracked.
+        fn withdraw_from_own_streams(&mut self) -> Result<Balance, erc1620::Error> {
+            let contract_account_id = self.env().account_id();
+            let mut total: Balance = 0;
+            for stream_id in self.stream_ids.keys().copied().collect::<Vec<_>>() {
+                let amount = match erc1620_balance_of(&self.erc1620, stream_id, contract_account_id) {
+                    Ok(amount) => amount,
+                    Err(_) => {
+                        self.stream_ids.take(&stream_id);
+                        continue;
+                    }
+                };
+                if amount > 0 {
+                    erc1620_withdraw_from_stream(&mut self.erc1620, stream_id, amount)?;
+                    total += amount;
+                }
+            }
+            Ok(total)
+        }
+
         fn is_artist(&self, account_id: AccountId) -> bool { self.artists.contains_key(&account_id) }
+
+        /// Removes `artist` together with their consent and distribution proposal.
+        fn remove_artist(&mut self, artist: AccountId) {
+            self.artists.take(&artist);
+            self.buyout_consents.take(&artist);
+            self.distributions.take(&artist);
+        }
+
+        fn ensure_not_frozen(&self) -> Result<(), UpdateClaimableMediaError> {
+            if self.is_frozen {
+                return Err(UpdateClaimableMediaError::Frozen);
+            }
+            Ok(())
+        }
     }
 
     /// Uses euclidean division to distribute the royalties over the shares. Note that there will be
@@ -253,6 +462,281 @@ mod claimable_media {
             (account, royalty)
         })
     }
+
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn erc20_transfer_from(
+        erc20: &mut erc20::Erc20,
+        from: AccountId,
+        to: AccountId,
+        value: Balance,
+    ) -> Result<(), erc20::Error> {
+        erc20.transfer_from(from, to, value)
+    }
+
+    #[cfg(test)]
+    use tests::mock_erc20::transfer_from as erc20_transfer_from;
+
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn erc1620_balance_of(
+        erc1620: &erc1620::Erc1620,
+        stream_id: StreamId,
+        who: AccountId,
+    ) -> Result<Balance, erc1620::Error> {
+        erc1620.balance_of(stream_id, who)
+    }
+
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn erc1620_withdraw_from_stream(
+        erc1620: &mut erc1620::Erc1620,
+        stream_id: StreamId,
+        amount: Balance,
+    ) -> Result<bool, erc1620::Error> {
+        erc1620.withdraw_from_stream(stream_id, amount)
+    }
+
+    #[cfg(test)]
+    use tests::mock_erc1620::{balance_of as erc1620_balance_of, withdraw_from_stream as erc1620_withdraw_from_stream};
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use contract_utils::test_utils;
+        use ink_env::call::FromAccountId;
+
+        /// Records erc20 transfers, as the token contract can't be called off-chain
+        pub(super) mod mock_erc20 {
+            use super::*;
+            use std::cell::RefCell;
+
+            thread_local! {
+                static TRANSFERS: RefCell<Vec<(AccountId, AccountId, Balance)>> = RefCell::new(Vec::new());
+            }
+
+            pub fn transfer_from(
+                _erc20: &mut erc20::Erc20,
+                from: AccountId,
+                to: AccountId,
+                value: Balance,
+            ) -> Result<(), erc20::Error> {
+                TRANSFERS.with(|transfers| transfers.borrow_mut().push((from, to, value)));
+                Ok(())
+            }
+
+            pub fn transfers() -> Vec<(AccountId, AccountId, Balance)> {
+                TRANSFERS.with(|transfers| transfers.borrow().clone())
+            }
+        }
+
+        /// Serves stream balances and records withdrawals, as the erc1620 contract can't be called off-chain
+        pub(super) mod mock_erc1620 {
+            use super::*;
+            use std::{cell::RefCell, collections::HashMap};
+
+            thread_local! {
+                static BALANCES: RefCell<HashMap<StreamId, Balance>> = RefCell::new(HashMap::new());
+                static WITHDRAWALS: RefCell<Vec<(StreamId, Balance)>> = RefCell::new(Vec::new());
+            }
+
+            pub fn balance_of(
+                _erc1620: &erc1620::Erc1620,
+                stream_id: StreamId,
+                _who: AccountId,
+            ) -> Result<Balance, erc1620::Error> {
+                BALANCES
+                    .with(|balances| balances.borrow().get(&stream_id).copied())
+                    .ok_or(erc1620::Error::StreamNotFound)
+            }
+
+            pub fn withdraw_from_stream(
+                _erc1620: &mut erc1620::Erc1620,
+                stream_id: StreamId,
+                amount: Balance,
+            ) -> Result<bool, erc1620::Error> {
+                WITHDRAWALS.with(|withdrawals| withdrawals.borrow_mut().push((stream_id, amount)));
+                Ok(true)
+            }
+
+            pub fn set_balance(stream_id: StreamId, balance: Balance) {
+                BALANCES.with(|balances| balances.borrow_mut().insert(stream_id, balance));
+            }
+
+            pub fn withdrawals() -> Vec<(StreamId, Balance)> {
+                WITHDRAWALS.with(|withdrawals| withdrawals.borrow().clone())
+            }
+        }
+
+        /// Builds the contract directly, since the constructor creates a media through a cross-contract call.
+        fn new_claimable_media(artists: &[AccountId], voting_window: Timestamp) -> ClaimableMedia {
+            let accounts = test_utils::default_accounts();
+            ClaimableMedia {
+                name: "test".into(),
+                artists: artists.iter().map(|id| (*id, ())).collect(),
+                creator: accounts.alice,
+                created_at: 0,
+                state: ClaimableMediaState::default(),
+                distributions: Default::default(),
+                media: FromAccountId::from_account_id(accounts.frank),
+                media_id: 1,
+                erc1620: FromAccountId::from_account_id(accounts.frank),
+                erc20: FromAccountId::from_account_id(accounts.frank),
+                is_frozen: false,
+                voting_window,
+                buyout_consents: Default::default(),
+                stream_ids: Default::default(),
+            }
+        }
+
+        #[ink::test]
+        fn test_freeze() {
+            let accounts = test_utils::default_accounts();
+            let mut claimable = new_claimable_media(&[accounts.bob, accounts.charlie], WEEK);
+            let mut collabs = BTreeMap::new();
+            collabs.insert(accounts.bob, 1);
+
+            // only the creator may freeze
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(claimable.freeze(), Err(UpdateClaimableMediaError::Unauthorized));
+            ink_env::test::pop_execution_context();
+
+            claimable.freeze().unwrap();
+            assert!(claimable.is_frozen());
+
+            // proposing and validating fail while frozen
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(
+                claimable.propose_distribution(collabs.clone()),
+                Err(ProposeDistributionError::Update(UpdateClaimableMediaError::Frozen))
+            );
+            assert_eq!(
+                claimable.validate(accounts.bob, true),
+                Err(ValidateDistributionError::Update(UpdateClaimableMediaError::Frozen))
+            );
+            ink_env::test::pop_execution_context();
+
+            // both resume after unfreezing
+            claimable.unfreeze().unwrap();
+            test_utils::set_caller(accounts.bob);
+            claimable.propose_distribution(collabs).unwrap();
+            claimable.validate(accounts.bob, true).unwrap();
+            assert!(claimable.distribution(accounts.bob).unwrap().state.is_pending());
+        }
+
+        #[ink::test]
+        fn test_voting_window() {
+            let accounts = test_utils::default_accounts();
+            let artists = [accounts.bob, accounts.charlie];
+            let mut collabs = BTreeMap::new();
+            collabs.insert(accounts.bob, 1);
+
+            // proposals can be validated within the default window
+            let mut claimable = new_claimable_media(&artists, WEEK);
+            test_utils::set_caller(accounts.bob);
+            claimable.propose_distribution(collabs.clone()).unwrap();
+            test_utils::advance_time(60_000);
+            claimable.validate(accounts.bob, true).unwrap();
+            assert!(claimable.distribution(accounts.bob).unwrap().state.is_pending());
+            ink_env::test::pop_execution_context();
+
+            // a short window expires quickly
+            let mut claimable = new_claimable_media(&artists, 30_000);
+            assert_eq!(claimable.info().voting_window, 30_000);
+            test_utils::set_caller(accounts.bob);
+            claimable.propose_distribution(collabs).unwrap();
+            test_utils::advance_time(60_000);
+            claimable.validate(accounts.bob, true).unwrap();
+            assert_eq!(claimable.distribution(accounts.bob).unwrap().state, DistributionProposalState::Denied);
+        }
+
+        #[ink::test]
+        fn test_set_state() {
+            let accounts = test_utils::default_accounts();
+            let mut claimable = new_claimable_media(&[accounts.bob], WEEK);
+
+            // verification can't skip the claim
+            assert_eq!(
+                claimable.set_state(ClaimableMediaState::Verified),
+                Err(UpdateClaimableMediaError::InvalidTransition)
+            );
+            assert_eq!(claimable.info().state, ClaimableMediaState::NotClaimed);
+
+            // a rejected claim can be claimed again, and then verified
+            claimable.set_state(ClaimableMediaState::Claimed).unwrap();
+            claimable.set_state(ClaimableMediaState::NotClaimed).unwrap();
+            claimable.set_state(ClaimableMediaState::Claimed).unwrap();
+            claimable.set_state(ClaimableMediaState::Verified).unwrap();
+            assert_eq!(claimable.info().state, ClaimableMediaState::Verified);
+
+            // verified is final
+            assert_eq!(
+                claimable.set_state(ClaimableMediaState::Claimed),
+                Err(UpdateClaimableMediaError::InvalidTransition)
+            );
+        }
+
+        #[ink::test]
+        fn test_buyout_artist() {
+            let accounts = test_utils::default_accounts();
+            let mut claimable = new_claimable_media(&[accounts.bob, accounts.charlie, accounts.django], WEEK);
+            let mut collabs = BTreeMap::new();
+            collabs.insert(accounts.charlie, 1);
+
+            // charlie has not consented yet
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(claimable.buyout_artist(accounts.charlie, 1_000), Err(BuyoutError::NoConsent));
+            ink_env::test::pop_execution_context();
+
+            test_utils::set_caller(accounts.charlie);
+            claimable.propose_distribution(collabs).unwrap();
+            claimable.set_buyout_consent(Some(1_000)).unwrap();
+            assert_eq!(claimable.buyout_consent(accounts.charlie), Some(1_000));
+            ink_env::test::pop_execution_context();
+
+            // only other artists may buy out charlie
+            assert_eq!(claimable.buyout_artist(accounts.charlie, 1_000), Err(BuyoutError::Unauthorized));
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(claimable.buyout_artist(accounts.charlie, 1_000), Err(BuyoutError::Unauthorized));
+            ink_env::test::pop_execution_context();
+
+            // charlie asks for at least 1_000
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(claimable.buyout_artist(accounts.charlie, 999), Err(BuyoutError::PriceTooLow));
+            assert_eq!(claimable.buyout_artist(accounts.charlie, 0), Err(BuyoutError::PriceTooLow));
+            assert!(mock_erc20::transfers().is_empty());
+            claimable.buyout_artist(accounts.charlie, 1_000).unwrap();
+            assert_eq!(mock_erc20::transfers(), vec![(accounts.bob, accounts.charlie, 1_000)]);
+            assert_eq!(test_utils::recorded_event_count(), 2);
+            assert!(!claimable.info().artists.contains(&accounts.charlie));
+            assert_eq!(claimable.buyout_consent(accounts.charlie), None);
+            assert_eq!(claimable.distribution(accounts.charlie), None);
+            assert_eq!(claimable.buyout_artist(accounts.charlie, 1_000), Err(BuyoutError::NotAnArtist));
+
+            // django did not consent
+            assert_eq!(claimable.buyout_artist(accounts.django, 1_000), Err(BuyoutError::NoConsent));
+            assert_eq!(mock_erc20::transfers().len(), 1);
+        }
+
+        #[ink::test]
+        fn test_withdraw_from_own_streams() {
+            let accounts = test_utils::default_accounts();
+            let mut claimable = new_claimable_media(&[accounts.bob], WEEK);
+
+            // streams 1 and 3 were opened for this media, stream 2 pays the contract for something else and
+            // stream 3 has been closed since
+            claimable.stream_ids.insert(1, ());
+            claimable.stream_ids.insert(3, ());
+            mock_erc1620::set_balance(1, 100);
+            mock_erc1620::set_balance(2, 50);
+
+            assert_eq!(claimable.withdraw_from_own_streams(), Ok(100));
+            assert_eq!(mock_erc1620::withdrawals(), vec![(1, 100)]);
+            assert_eq!(claimable.stream_ids(), vec![1]);
+
+            // nothing is withdrawn from an empty stream
+            mock_erc1620::set_balance(1, 0);
+            assert_eq!(claimable.withdraw_from_own_streams(), Ok(0));
+            assert_eq!(mock_erc1620::withdrawals().len(), 1);
+        }
+    }
 }
 
 
@@ -1,7 +1,7 @@
 use ink_prelude::{collections::BTreeMap, string::String, vec::Vec};
 use ink_storage::traits::{PackedLayout, SpreadLayout};
 use media::{
-    models::{NftInfo, ViewInfo},
+    models::{MediaType, NftInfo, ViewInfo},
     MediaStorage as Media,
 };
 pub type Balance = <ink_env::DefaultEnvironment as ink_env::Environment>::Balance;
@@ -15,10 +15,13 @@ pub struct CreateClaimableMediaRequest {
     pub name: String,
     pub artists: Vec<AccountId>,
     pub media: Media,
+    pub media_type: MediaType,
     pub view_info: ViewInfo,
     pub nft_info: NftInfo,
     pub erc1620: erc1620::Erc1620,
     pub erc20: erc20::Erc20,
+    /// How long a distribution proposal can be validated after it is proposed. Defaults to a week.
+    pub voting_window: Option<Timestamp>,
 }
 
 #[derive(Debug, Clone, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
@@ -33,6 +36,7 @@ pub struct ClaimableMediaInfo {
     pub media_id: u64,
     pub erc1620: erc1620::Erc1620,
     pub erc20: erc20::Erc20,
+    pub voting_window: Timestamp,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
@@ -43,6 +47,18 @@ pub enum ClaimableMediaState {
     NotClaimed,
 }
 
+impl ClaimableMediaState {
+    /// Whether the media may move from `from` to `to`. The allowed transitions are:
+    ///
+    /// * `NotClaimed` -> `Claimed`: an artist claimed the media.
+    /// * `Claimed` -> `Verified`: the claim was verified. `Verified` is final.
+    /// * `Claimed` -> `NotClaimed`: the claim was rejected.
+    pub fn can_transition(from: &Self, to: &Self) -> bool {
+        use ClaimableMediaState::*;
+        matches!((from, to), (NotClaimed, Claimed) | (Claimed, Verified) | (Claimed, NotClaimed))
+    }
+}
+
 impl Default for ClaimableMediaState {
     fn default() -> Self { Self::NotClaimed }
 }
@@ -11,6 +11,8 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[ink::contract]
 mod erc1155 {
     use super::*;
+    use contract_utils::TokenStandard;
+    use enumflags2::{bitflags, BitFlags};
     use ink_prelude::vec::Vec;
     use ink_storage::{
         collections::HashMap as StorageHashMap,
@@ -27,6 +29,10 @@ mod erc1155 {
 
     pub type TokenId = u64;
 
+    /// Selector a receiving contract must implement to accept tokens from `safe_batch_transfer_from`. The message
+    /// takes `(operator, from, ids, amounts, data)` and must return these selector bytes to accept the transfer.
+    pub const ON_ERC1155_BATCH_RECEIVED_SELECTOR: [u8; 4] = [0xBC, 0x19, 0x7C, 0x81];
+
     #[ink(storage)]
     pub struct Erc1155 {
         /// Next Token Id
@@ -41,6 +47,10 @@ mod erc1155 {
         approvals_by_token_id: StorageHashMap<TokenId, AccountId>,
         /// Mapping from owner to operator approvals.
         operator_approvals: StorageHashMap<(AccountId, AccountId), bool>,
+        /// Amount of a token a spender may transfer on behalf of an owner.
+        allowances_by_id: StorageHashMap<(AccountId, AccountId, TokenId), Balance>,
+        /// Roles for each account. Each value is stored as a raw BitFlags<Role>.
+        roles: StorageHashMap<AccountId, u8>,
     }
 
     #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone, err_derive::Error)]
@@ -76,6 +86,15 @@ mod erc1155 {
         /// Cannot insert the caller as approved user        
         #[error(display = "Cannot insert the caller as approved user")]
         CannotInsert,
+        /// The recipient contract did not accept the tokens
+        #[error(display = "The recipient contract did not accept the tokens")]
+        UnsafeRecipient,
+        /// The spender's allowance is lower than the amount
+        #[error(display = "The spender's allowance is lower than the amount")]
+        InsufficientAllowance,
+        /// The caller is missing the role required for this operation
+        #[error(display = "The caller is missing the role required for this operation")]
+        MissingRole,
     }
 
     /// Event emitted when a token transfer occurs.
@@ -101,6 +120,18 @@ mod erc1155 {
         id: TokenId,
     }
 
+    /// Event emitted when an owner sets the amount of a token a spender may transfer.
+    #[ink(event)]
+    pub struct AllowanceApproval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        #[ink(topic)]
+        id: TokenId,
+        amount: Balance,
+    }
+
     /// Event emitted when an operator is enabled or disabled for an owner.
     /// The operator can manage all NFTs of the owner.
     #[ink(event)]
@@ -112,10 +143,26 @@ mod erc1155 {
         approved: bool,
     }
 
+    /// Event emitted when a minter is added.
+    #[ink(event)]
+    pub struct AddedMinter {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Event emitted when a minter is removed.
+    #[ink(event)]
+    pub struct RemovedMinter {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
     impl Erc1155 {
         /// Creates a new ERC1155 token contract.
         #[ink(constructor)]
         pub fn new() -> Self {
+            let mut roles = StorageHashMap::new();
+            roles.insert(Self::env().caller(), RoleBitFlags::all().bits());
             Self {
                 next_token_id: 0,
                 tokens_by_id: Default::default(),
@@ -123,16 +170,21 @@ mod erc1155 {
                 balances_by_account_id: Default::default(),
                 approvals_by_token_id: Default::default(),
                 operator_approvals: Default::default(),
+                allowances_by_id: Default::default(),
+                roles,
             }
         }
 
-        /// Creates a new token.
+        /// Creates a new token. The caller must have the Minter role.
         #[ink(message)]
         pub fn mint(&mut self, recipient: AccountId, amount: Balance, metadata: Vec<u8>) -> Result<()> {
             let caller = self.env().caller();
             if caller == AccountId::from([0x0; 32]) {
                 return Err(Error::NotAllowed);
             };
+            if !self.get_roles(caller).contains(Role::Minter) {
+                return Err(Error::MissingRole);
+            }
             let Self {
                 next_token_id,
                 tokens_by_id: tokens,
@@ -234,13 +286,49 @@ mod erc1155 {
             Ok(())
         }
 
-        /// Transfer approved or owned token.
+        /// Transfer approved or owned token. Callers that `from` did not approve as an operator, or for the token if
+        /// `from` owns it, spend their allowance from `approve_amount` instead, unless they transfer their own tokens.
         #[ink(message)]
         pub fn transfer_from(&mut self, from: AccountId, to: AccountId, id: TokenId, amount: Balance) -> Result<()> {
-            self.transfer_token_from_to(from, to, id, amount)?;
+            let caller = self.env().caller();
+            if !self.exists(id) {
+                return Err(Error::TokenNotFound);
+            }
+            if self.approved_by(from, caller, id) {
+                return self.move_tokens(from, to, id, amount);
+            }
+
+            let allowance = self.allowance(from, caller, id);
+            if allowance == 0 {
+                return Err(Error::NotApproved);
+            }
+            if allowance < amount {
+                return Err(Error::InsufficientAllowance);
+            }
+            self.move_tokens(from, to, id, amount)?;
+            self.allowances_by_id.insert((from, caller, id), allowance - amount);
+            Ok(())
+        }
+
+        /// Allows `spender` to transfer up to `amount` of token `id` from the caller with `transfer_from`. Calling
+        /// it again overwrites the current allowance.
+        #[ink(message)]
+        pub fn approve_amount(&mut self, spender: AccountId, id: TokenId, amount: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            if spender == owner {
+                return Err(Error::NotAllowed);
+            }
+            self.allowances_by_id.insert((owner, spender, id), amount);
+            self.env().emit_event(AllowanceApproval { owner, spender, id, amount });
             Ok(())
         }
 
+        /// Returns the amount of token `id` which `spender` may still transfer from `owner`.
+        #[ink(message)]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId, id: TokenId) -> Balance {
+            *self.allowances_by_id.get(&(owner, spender, id)).unwrap_or(&0)
+        }
+
         /// Transfers tokens from the caller to the given destination. Batch Transfer
         #[ink(message)]
         pub fn batch_transfer(
@@ -259,6 +347,44 @@ mod erc1155 {
             Ok(())
         }
 
+        /// Transfers tokens `from` to `to` like `batch_transfer`. Once the tokens were moved, `on_erc1155_batch_received`
+        /// is called with `data` if `to` is a contract, which must accept the tokens. Otherwise the transfer is undone
+        /// and `Error::UnsafeRecipient` is returned.
+        #[ink(message)]
+        pub fn safe_batch_transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            ids: Vec<TokenId>,
+            amounts: Vec<Balance>,
+            data: Vec<u8>,
+        ) -> Result<()> {
+            if ids.len() != amounts.len() {
+                return Err(Error::ArraysLengthNotEqual);
+            }
+            let operator = self.env().caller();
+            let mut moved = 0;
+            let mut result = Ok(());
+            for (id, amount) in ids.iter().zip(&amounts) {
+                result = self.transfer_token_from_to(from, to, *id, *amount);
+                if result.is_err() {
+                    break;
+                }
+                moved += 1;
+            }
+            if result.is_ok() {
+                result = check_batch_receiver_response(call_batch_receiver(operator, from, to, &ids, &amounts, data));
+            }
+
+            // a failing message does not revert, so the tokens that were already moved are moved back
+            if result.is_err() {
+                for (id, amount) in ids[..moved].iter().zip(&amounts[..moved]) {
+                    self.move_tokens(to, from, *id, *amount)?;
+                }
+            }
+            result
+        }
+
         /// Transfers token `id` `from` the sender to the `to` AccountId.
         fn transfer_token_from_to(
             &mut self,
@@ -277,6 +403,11 @@ mod erc1155 {
                 return Err(Error::NotApproved);
             };
 
+            self.move_tokens(from, to, token_id, amount)
+        }
+
+        /// Moves `amount` of token `id` from `from` to `to` without checking approvals.
+        fn move_tokens(&mut self, from: AccountId, to: AccountId, token_id: TokenId, amount: Balance) -> Result<()> {
             let Self { balances_by_account_id: balances, .. } = self;
 
             reduce_balance_of(balances, from, token_id, amount)?;
@@ -301,6 +432,32 @@ mod erc1155 {
         #[ink(message)]
         pub fn balance_of(&self, owner: AccountId, id: TokenId) -> Balance { self.balance_of_or_zero(&owner, id) }
 
+        /// Returns every account holding some of the token, with its balance. Unlike `owner_of`, which only knows the
+        /// minter, this covers fungible ids held by many accounts. It iterates over the balances of all tokens.
+        #[ink(message)]
+        pub fn holders_of(&self, id: TokenId) -> Vec<(AccountId, Balance)> {
+            self.balances_by_account_id
+                .iter()
+                .filter(|(key, balance)| key.1 == id && **balance > 0)
+                .map(|(key, balance)| (key.0, *balance))
+                .collect()
+        }
+
+        /// Returns the standard this contract implements. Its selector is `contract_utils::TOKEN_STANDARD_SELECTOR` in
+        /// every token contract, so callers can check the standard of an unknown contract.
+        #[ink(message)]
+        #[ink(selector = "0x746f6b73")]
+        pub fn token_standard(&self) -> TokenStandard { TokenStandard::Erc1155 }
+
+        /// Returns whether the contract supports every feature in `feature`, a set of `contract_utils::token_feature`
+        /// bits. Its selector is `contract_utils::SUPPORTS_SELECTOR` in every token contract.
+        #[ink(message)]
+        #[ink(selector = "0x73757070")]
+        pub fn supports(&self, feature: u32) -> bool {
+            use contract_utils::token_feature::*;
+            feature & !(MINT | BURN | METADATA | BATCH) == 0
+        }
+
         /// Returns the total amount of a given Token from an account.
         fn balance_of_or_zero(&self, of: &AccountId, id: TokenId) -> Balance {
             let balance = *self.balances_by_account_id.get(&(*of, id)).unwrap_or(&0);
@@ -322,6 +479,14 @@ mod erc1155 {
                     || self.approved_for_all(owner.expect("Error with AccountId"), from.expect("Error with AccountId")))
         }
 
+        /// Returns true if `caller` may move the tokens `id` of `from` without an allowance: `caller` is `from`, an
+        /// operator of `from`, or approved for the token by `from` as its owner.
+        fn approved_by(&self, from: AccountId, caller: AccountId, id: TokenId) -> bool {
+            from == caller
+                || self.approved_for_all(from, caller)
+                || (self.owner_of(id) == Some(from) && self.approvals_by_token_id.get(&id) == Some(&caller))
+        }
+
         /// Gets an operator on other Account's behalf.
         fn approved_for_all(&self, owner: AccountId, operator: AccountId) -> bool {
             *self.operator_approvals.get(&(owner, operator)).unwrap_or(&false)
@@ -367,6 +532,114 @@ mod erc1155 {
         pub fn is_approved_for_all(&self, owner: AccountId, operator: AccountId) -> bool {
             self.approved_for_all(owner, operator)
         }
+
+        /// Returns the account approved to transfer token `id` on behalf of its owner, if any. As the owner keeps
+        /// owning the id after transferring part of its balance, the approval stays in place across transfers.
+        #[ink(message)]
+        pub fn get_approved(&self, id: TokenId) -> Option<AccountId> { self.approvals_by_token_id.get(&id).copied() }
+    }
+
+    /// Calls `on_erc1155_batch_received` on `to`
+    #[cfg(not(test))]
+    fn call_batch_receiver(
+        operator: AccountId,
+        from: AccountId,
+        to: AccountId,
+        ids: &[TokenId],
+        amounts: &[Balance],
+        data: Vec<u8>,
+    ) -> ink_env::Result<[u8; 4]> {
+        use ink_env::call::{build_call, utils::ReturnType, ExecutionInput, Selector};
+
+        build_call::<Environment>()
+            .callee(to)
+            .gas_limit(0)
+            .exec_input(
+                ExecutionInput::new(Selector::new(ON_ERC1155_BATCH_RECEIVED_SELECTOR))
+                    .push_arg(operator)
+                    .push_arg(from)
+                    .push_arg(ids)
+                    .push_arg(amounts)
+                    .push_arg(data),
+            )
+            .returns::<ReturnType<[u8; 4]>>()
+            .fire()
+    }
+
+    // ========== ACCESS CONTROL
+
+    /// Roles grant an account permissions for certain operations
+    #[bitflags]
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    #[repr(u8)]
+    pub enum Role {
+        /// Can change roles (1)
+        Admin = 0b_0000_0001,
+        /// Can mint tokens (2)
+        Minter = 0b_0000_0010,
+    }
+
+    pub type RoleBitFlags = BitFlags<Role>;
+
+    impl Erc1155 {
+        /// Add the Minter role to an account. The caller must have the Admin role.
+        #[ink(message)]
+        pub fn add_minter(&mut self, account: AccountId) -> Result<()> {
+            self.add_roles(self.env().caller(), account, Role::Minter.into())?;
+            self.env().emit_event(AddedMinter { account });
+            Ok(())
+        }
+
+        /// Remove the Minter role from an account. The caller must have the Admin role.
+        #[ink(message)]
+        pub fn remove_minter(&mut self, account: AccountId) -> Result<()> {
+            self.remove_roles(self.env().caller(), account, Role::Minter.into())?;
+            self.env().emit_event(RemovedMinter { account });
+            Ok(())
+        }
+
+        /// Add a role to an account. Caller must have Admin role.
+        fn add_roles(&mut self, caller: AccountId, account: AccountId, value: RoleBitFlags) -> Result<()> {
+            if !self.get_roles(caller).contains(Role::Admin) {
+                return Err(Error::MissingRole);
+            }
+            self.roles
+                .entry(account)
+                .and_modify(|x| *x = (unsafe { BitFlags::from_bits_unchecked(*x) } | value).bits())
+                .or_insert(value.bits());
+            Ok(())
+        }
+
+        /// Remove a role from an account. Caller must have Admin role.
+        fn remove_roles(&mut self, caller: AccountId, account: AccountId, value: RoleBitFlags) -> Result<()> {
+            if !self.get_roles(caller).contains(Role::Admin) {
+                return Err(Error::MissingRole);
+            }
+            self.roles
+                .entry(account)
+                .and_modify(|x| *x = (unsafe { BitFlags::from_bits_unchecked(*x) } & !value).bits())
+                .or_insert(0);
+            Ok(())
+        }
+
+        /// Get the roles for this account
+        fn get_roles(&self, account: AccountId) -> RoleBitFlags {
+            unsafe { RoleBitFlags::from_bits_unchecked(self.roles.get(&account).copied().unwrap_or_default()) }
+        }
+    }
+
+    #[cfg(test)]
+    use tests::mock_receiver::call as call_batch_receiver;
+
+    /// Checks the result of calling `on_erc1155_batch_received`. Accounts that aren't contracts always accept, the
+    /// call to them fails with `NotCallable` as they have no code. Contracts must return
+    /// `ON_ERC1155_BATCH_RECEIVED_SELECTOR`, a contract that traps or reverts rejects the tokens.
+    fn check_batch_receiver_response(response: ink_env::Result<[u8; 4]>) -> Result<()> {
+        match response {
+            Ok(selector) if selector == ON_ERC1155_BATCH_RECEIVED_SELECTOR => Ok(()),
+            Err(ink_env::Error::NotCallable) => Ok(()),
+            _ => Err(Error::UnsafeRecipient),
+        }
     }
 
     /// Get the next token Id
@@ -418,6 +691,40 @@ mod erc1155 {
         use ink_env::{call, test};
         use ink_lang as ink;
 
+        /// The off-chain environment cannot call other contracts, so recipients are treated as plain accounts unless
+        /// they are set up to reject the tokens
+        pub(super) mod mock_receiver {
+            use super::*;
+            use std::cell::{Cell, RefCell};
+
+            thread_local! {
+                static REJECTING: Cell<bool> = Cell::new(false);
+                static CALLS: RefCell<Vec<(AccountId, Vec<TokenId>)>> = RefCell::new(Vec::new());
+            }
+
+            pub fn call(
+                _operator: AccountId,
+                _from: AccountId,
+                to: AccountId,
+                ids: &[TokenId],
+                _amounts: &[Balance],
+                _data: Vec<u8>,
+            ) -> ink_env::Result<[u8; 4]> {
+                CALLS.with(|calls| calls.borrow_mut().push((to, ids.to_vec())));
+                if REJECTING.with(Cell::get) {
+                    Ok([0, 0, 0, 0])
+                } else {
+                    Err(ink_env::Error::NotCallable)
+                }
+            }
+
+            /// Makes every following recipient a contract that rejects the tokens
+            pub fn reject() { REJECTING.with(|rejecting| rejecting.set(true)) }
+
+            /// The recipient and ids of every call to a receiver so far
+            pub fn calls() -> Vec<(AccountId, Vec<TokenId>)> { CALLS.with(|calls| calls.borrow().clone()) }
+        }
+
         #[ink::test]
         fn mint_works() {
             let accounts =
@@ -432,6 +739,30 @@ mod erc1155 {
             assert_eq!(erc1155.balance_of(accounts.alice, 1), 10000);
         }
 
+        #[ink::test]
+        fn mint_requires_minter_role() {
+            let accounts = test_utils::default_accounts();
+            let mut erc1155 = Erc1155::new();
+
+            // bob is not a minter
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc1155.mint(accounts.bob, 10, vec![1]), Err(Error::MissingRole));
+            assert_eq!(erc1155.add_minter(accounts.bob), Err(Error::MissingRole));
+            ink_env::test::pop_execution_context();
+
+            // the deployer grants bob the role
+            erc1155.add_minter(accounts.bob).unwrap();
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc1155.mint(accounts.bob, 10, vec![1]), Ok(()));
+            assert_eq!(erc1155.balance_of(accounts.bob, 1), 10);
+            ink_env::test::pop_execution_context();
+
+            // and takes it away again
+            erc1155.remove_minter(accounts.bob).unwrap();
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc1155.mint(accounts.bob, 10, vec![2]), Err(Error::MissingRole));
+        }
+
         #[ink::test]
         fn burn_works() {
             let accounts = test_utils::default_accounts();
@@ -450,6 +781,23 @@ mod erc1155 {
             assert_eq!(erc1155.balance_of(accounts.alice, 1), 5000);
         }
 
+        #[ink::test]
+        fn holders_of_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc1155 = Erc1155::new();
+
+            // mint two ids and spread the first one across accounts
+            erc1155.mint(accounts.alice, 10_000, vec![1]).unwrap();
+            erc1155.mint(accounts.alice, 5, vec![2]).unwrap();
+            erc1155.transfer(accounts.bob, 1, 3_000).unwrap();
+            erc1155.transfer(accounts.charlie, 1, 7_000).unwrap();
+
+            // alice has nothing left of the first id
+            assert_eq!(erc1155.holders_of(1), vec![(accounts.bob, 3_000), (accounts.charlie, 7_000)]);
+            assert_eq!(erc1155.holders_of(2), vec![(accounts.alice, 5)]);
+            assert!(erc1155.holders_of(3).is_empty());
+        }
+
         #[ink::test]
         fn burn_from_works() {
             let accounts = test_utils::default_accounts();
@@ -603,6 +951,40 @@ mod erc1155 {
             assert_eq!(erc1155.balance_of(accounts.eve, 1), 5000);
         }
 
+        #[ink::test]
+        fn token_standard_works() {
+            assert_eq!(TokenStandard::Erc1155, Erc1155::new().token_standard());
+        }
+
+        #[ink::test]
+        fn supports_works() {
+            use contract_utils::token_feature::*;
+
+            let erc1155 = Erc1155::new();
+            assert!(erc1155.supports(MINT | BURN | METADATA | BATCH));
+            assert!(!erc1155.supports(PAUSE));
+            assert!(!erc1155.supports(ROYALTIES));
+        }
+
+        #[ink::test]
+        fn get_approved_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc1155 = Erc1155::new();
+            assert_eq!(erc1155.mint(accounts.alice, 10000, "Some JSON".as_bytes().to_vec()), Ok(()));
+            assert_eq!(erc1155.get_approved(1), None);
+
+            // Bob is approved for token Id 1
+            assert_eq!(erc1155.approve(accounts.bob, 1), Ok(()));
+            assert_eq!(erc1155.get_approved(1), Some(accounts.bob));
+
+            // Bob may keep transferring Alice's balance
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc1155.transfer_from(accounts.alice, accounts.eve, 1, 5000), Ok(()));
+            assert_eq!(erc1155.get_approved(1), Some(accounts.bob));
+            assert_eq!(erc1155.transfer_from(accounts.alice, accounts.eve, 1, 5000), Ok(()));
+            assert_eq!(erc1155.balance_of(accounts.eve, 1), 10000);
+        }
+
         #[ink::test]
         fn approved_for_all_works() {
             let accounts =
@@ -692,6 +1074,153 @@ mod erc1155 {
             assert_eq!(erc1155.balance_of(accounts.eve, 1), 0);
         }
 
+        #[ink::test]
+        fn safe_batch_transfer_from_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.mint(accounts.alice, 100, vec![1]).unwrap();
+            erc1155.mint(accounts.alice, 100, vec![2]).unwrap();
+
+            assert_eq!(
+                erc1155.safe_batch_transfer_from(accounts.alice, accounts.bob, vec![1, 2], vec![10], vec![]),
+                Err(Error::ArraysLengthNotEqual)
+            );
+            // bob is not a contract, so the transfer goes through
+            erc1155.safe_batch_transfer_from(accounts.alice, accounts.bob, vec![1, 2], vec![10, 20], vec![]).unwrap();
+            assert_eq!(erc1155.balance_of(accounts.bob, 1), 10);
+            assert_eq!(erc1155.balance_of(accounts.bob, 2), 20);
+            assert_eq!(erc1155.balance_of(accounts.alice, 2), 80);
+            assert_eq!(mock_receiver::calls(), vec![(accounts.bob, vec![1, 2])]);
+        }
+
+        #[ink::test]
+        fn safe_batch_transfer_from_checks_before_calling_receiver() {
+            let accounts = test_utils::default_accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.mint(accounts.alice, 100, vec![1]).unwrap();
+            erc1155.mint(accounts.alice, 100, vec![2]).unwrap();
+
+            // the second id exceeds the balance, so the first one is moved back and the receiver is never called
+            assert_eq!(
+                erc1155.safe_batch_transfer_from(accounts.alice, accounts.bob, vec![1, 2], vec![10, 101], vec![]),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(erc1155.balance_of(accounts.alice, 1), 100);
+            assert_eq!(erc1155.balance_of(accounts.bob, 1), 0);
+
+            // an account that may not move the tokens is rejected before the receiver is called as well
+            set_sender(accounts.eve);
+            assert_eq!(
+                erc1155.safe_batch_transfer_from(accounts.alice, accounts.bob, vec![1], vec![10], vec![]),
+                Err(Error::NotApproved)
+            );
+            assert!(mock_receiver::calls().is_empty());
+        }
+
+        #[ink::test]
+        fn safe_batch_transfer_from_to_rejecting_receiver() {
+            let accounts = test_utils::default_accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.mint(accounts.alice, 100, vec![1]).unwrap();
+            erc1155.mint(accounts.alice, 100, vec![2]).unwrap();
+
+            // the receiver is called with the moved tokens, and rejecting them moves them back
+            mock_receiver::reject();
+            assert_eq!(
+                erc1155.safe_batch_transfer_from(accounts.alice, accounts.django, vec![1, 2], vec![10, 20], vec![]),
+                Err(Error::UnsafeRecipient)
+            );
+            assert_eq!(mock_receiver::calls(), vec![(accounts.django, vec![1, 2])]);
+            assert_eq!(erc1155.balance_of(accounts.alice, 1), 100);
+            assert_eq!(erc1155.balance_of(accounts.alice, 2), 100);
+            assert_eq!(erc1155.balance_of(accounts.django, 1), 0);
+            assert_eq!(erc1155.balance_of(accounts.django, 2), 0);
+        }
+
+        #[ink::test]
+        fn batch_receiver_response_is_checked() {
+            // a receiver that returns the selector accepts the tokens
+            assert_eq!(check_batch_receiver_response(Ok(ON_ERC1155_BATCH_RECEIVED_SELECTOR)), Ok(()));
+            // a receiver that returns another selector or no selector at all, traps or reverts rejects them
+            assert_eq!(check_batch_receiver_response(Ok([0, 0, 0, 0])), Err(Error::UnsafeRecipient));
+            assert_eq!(check_batch_receiver_response(Err(ink_env::Error::CalleeTrapped)), Err(Error::UnsafeRecipient));
+            assert_eq!(check_batch_receiver_response(Err(ink_env::Error::CalleeReverted)), Err(Error::UnsafeRecipient));
+            assert_eq!(
+                check_batch_receiver_response(Err(ink_env::Error::Decode(scale::Error::from("")))),
+                Err(Error::UnsafeRecipient)
+            );
+            // plain accounts, which have no code, always accept
+            assert_eq!(check_batch_receiver_response(Err(ink_env::Error::NotCallable)), Ok(()));
+        }
+
+        #[ink::test]
+        fn allowance_transfer_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.mint(accounts.alice, 1000, vec![1]).unwrap();
+
+            // Alice allows Bob to spend 100 of token 1
+            erc1155.approve_amount(accounts.bob, 1, 100).unwrap();
+            assert_eq!(erc1155.allowance(accounts.alice, accounts.bob, 1), 100);
+
+            // Bob transfers within the allowance
+            test_utils::set_caller(accounts.bob);
+            erc1155.transfer_from(accounts.alice, accounts.eve, 1, 60).unwrap();
+            assert_eq!(erc1155.balance_of(accounts.eve, 1), 60);
+            assert_eq!(erc1155.allowance(accounts.alice, accounts.bob, 1), 40);
+
+            // Bob cannot transfer beyond what is left
+            assert_eq!(erc1155.transfer_from(accounts.alice, accounts.eve, 1, 50), Err(Error::InsufficientAllowance));
+            assert_eq!(erc1155.balance_of(accounts.alice, 1), 940);
+
+            // Charlie has no allowance at all
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(erc1155.transfer_from(accounts.alice, accounts.eve, 1, 1), Err(Error::NotApproved));
+        }
+
+        #[ink::test]
+        fn transfer_from_self_needs_no_allowance() {
+            let accounts = test_utils::default_accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.mint(accounts.alice, 1000, vec![1]).unwrap();
+            erc1155.transfer(accounts.bob, 1, 100).unwrap();
+
+            // Bob holds tokens he neither minted nor was approved for, and moves them without an allowance
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc1155.allowance(accounts.bob, accounts.bob, 1), 0);
+            erc1155.transfer_from(accounts.bob, accounts.eve, 1, 60).unwrap();
+            assert_eq!(erc1155.balance_of(accounts.bob, 1), 40);
+            assert_eq!(erc1155.balance_of(accounts.eve, 1), 60);
+
+            // but not more than he holds
+            assert_eq!(erc1155.transfer_from(accounts.bob, accounts.eve, 1, 41), Err(Error::InsufficientBalance));
+        }
+
+        #[ink::test]
+        fn transfer_from_checks_approvals_of_from() {
+            let accounts = test_utils::default_accounts();
+            let mut erc1155 = Erc1155::new();
+            erc1155.mint(accounts.alice, 1000, vec![1]).unwrap();
+            erc1155.transfer(accounts.bob, 1, 100).unwrap();
+            erc1155.approve(accounts.charlie, 1).unwrap();
+
+            // neither alice, who minted the token, nor charlie, approved for the token by alice, can move bob's tokens
+            assert_eq!(erc1155.transfer_from(accounts.bob, accounts.alice, 1, 10), Err(Error::NotApproved));
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(erc1155.transfer_from(accounts.bob, accounts.charlie, 1, 10), Err(Error::NotApproved));
+            ink_env::test::pop_execution_context();
+
+            // an operator bob approved moves bob's tokens without an allowance
+            test_utils::set_caller(accounts.bob);
+            erc1155.set_approval_for_all(accounts.django, true).unwrap();
+            ink_env::test::pop_execution_context();
+            test_utils::set_caller(accounts.django);
+            erc1155.transfer_from(accounts.bob, accounts.eve, 1, 60).unwrap();
+            ink_env::test::pop_execution_context();
+            assert_eq!(erc1155.balance_of(accounts.bob, 1), 40);
+            assert_eq!(erc1155.balance_of(accounts.eve, 1), 60);
+        }
+
         fn set_sender(sender: AccountId) {
             let callee = ink_env::account_id::<ink_env::DefaultEnvironment>().unwrap_or([0x0; 32].into());
             test::push_execution_context::<Environment>(
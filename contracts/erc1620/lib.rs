@@ -14,6 +14,9 @@ pub enum Error {
     /// Only callable by sender or recipient
     #[error(display = "only callable by sender or recipient")]
     OnlyCallableBySenderOrRecipient,
+    /// Only callable by recipient
+    #[error(display = "only callable by recipient")]
+    OnlyCallableByRecipient,
     /// The balance is insufficient
     #[error(display = "the balance is insufficient")]
     InsufficientBalance,
@@ -44,8 +47,35 @@ pub enum Error {
     /// Indicates that the account id is not the recipient of any active streams.
     #[error(display = "account has no active streams")]
     StreamsNotFound,
+    /// The contract was deployed without a stream NFT contract
+    #[error(display = "no stream NFT contract is configured")]
+    StreamNftNotConfigured,
+    /// The stream is not represented by an NFT
+    #[error(display = "the stream is not tokenized")]
+    StreamNotTokenized,
+    /// An ERC-721 error occurred
+    #[error(display = "An Erc721 error occurred: {}", _0)]
+    Erc721(#[source] erc721::Error),
+    /// The recipient already has the maximum number of streams
+    #[error(display = "the recipient has too many streams")]
+    TooManyStreams,
+    /// The deposit is below the minimum deposit
+    #[error(display = "the deposit is below the minimum deposit")]
+    DepositBelowMinimum,
+    /// The early cancel penalty exceeds 100%
+    #[error(display = "the early cancel penalty exceeds {} basis points", MAX_BPS)]
+    InvalidPenalty,
 }
 
+/// The default maximum number of active streams per recipient
+pub const DEFAULT_MAX_STREAMS_PER_RECIPIENT: u32 = 1_000;
+
+/// The default minimum deposit of a stream
+pub const DEFAULT_MIN_DEPOSIT: u128 = 1;
+
+/// The largest possible value in basis points, 100%
+pub const MAX_BPS: u16 = 10_000;
+
 /// The result type.
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -54,8 +84,9 @@ pub type Result<T> = core::result::Result<T, Error>;
 mod contract {
     use super::*;
     #[cfg(not(feature = "ink-as-dependency"))]
-    use contract_utils::ZERO_ACCOUNT;
+    use contract_utils::{time, ZERO_ACCOUNT};
     use erc20::Erc20;
+    use erc721::TokenId;
     use ink_env::call::FromAccountId;
     use ink_prelude::{vec, vec::Vec};
 
@@ -67,6 +98,16 @@ mod contract {
         /// The next [StreamId]
         next_stream_id: ink_storage::lazy::Lazy<StreamId>,
         stream_ids_by_account: ink_storage::collections::HashMap<AccountId, Vec<StreamId>>,
+        /// The ERC-721 contract used to mint tokens representing streams
+        stream_nft: ink_storage::lazy::Lazy<Option<AccountId>>,
+        /// Map of tokenized streams by the id of the token representing them
+        stream_ids_by_token: ink_storage::collections::HashMap<TokenId, StreamId>,
+        /// Map of the tokens representing streams by stream id
+        token_ids_by_stream: ink_storage::collections::HashMap<StreamId, TokenId>,
+        /// The maximum number of active streams a recipient may have, bounding `withdraw_from_all_streams`
+        max_streams_per_recipient: ink_storage::lazy::Lazy<u32>,
+        /// The smallest deposit a stream may be created with
+        min_deposit: ink_storage::lazy::Lazy<Balance>,
     }
 
     // Events
@@ -93,6 +134,8 @@ mod contract {
         stream_id: StreamId,
         #[ink(topic)]
         recipient: AccountId,
+        /// The account the tokens were paid to, which is the recipient unless redirected with `withdraw_to`
+        to: AccountId,
         amount: Balance,
     }
 
@@ -109,6 +152,27 @@ mod contract {
         recipient_balance: Balance,
     }
 
+    /// Event emitted when an NFT representing a [Stream] is minted
+    #[ink(event)]
+    pub struct StreamTokenized {
+        #[ink(topic)]
+        stream_id: StreamId,
+        #[ink(topic)]
+        token_id: TokenId,
+        recipient: AccountId,
+    }
+
+    /// Event emitted when the recipient of a [Stream] changes to the holder of its NFT
+    #[ink(event)]
+    pub struct StreamRecipientChanged {
+        #[ink(topic)]
+        stream_id: StreamId,
+        #[ink(topic)]
+        previous_recipient: AccountId,
+        #[ink(topic)]
+        new_recipient: AccountId,
+    }
+
     use ink_storage::traits::{PackedLayout, SpreadLayout};
 
     /// Unique identifier for a [Stream]
@@ -137,6 +201,9 @@ mod contract {
         pub token_address: AccountId,
         /// indicates whether the stream exists or not
         pub is_entity: bool,
+        /// the share of the sender's refund, in basis points, paid to the recipient if the sender cancels before
+        /// `stop_time`
+        pub early_cancel_penalty_bps: Option<u16>,
     }
 
     impl Stream {
@@ -146,10 +213,8 @@ mod contract {
             if now <= self.start_time {
                 return 0;
             }
-            if now < self.stop_time {
-                return core::time::Duration::from_millis(now - self.start_time).as_secs();
-            }
-            core::time::Duration::from_millis(self.stop_time - self.start_time).as_secs()
+            // a stream whose stop time precedes its start time has nothing to stream
+            contract_utils::time::duration_secs(self.start_time, now.min(self.stop_time)).unwrap_or(0)
         }
 
         /// The amount that has been withdrawn so far
@@ -159,10 +224,19 @@ mod contract {
         #[inline]
         pub fn token(&self) -> Erc20 { get_erc20(self.token_address) }
 
+        /// The amount streamed to the recipient by `now`, including what has been withdrawn. When the deposit is not
+        /// a multiple of the duration, the remainder stays in `remaining_balance` and is released at `stop_time`.
+        pub fn streamed_amount(&self, now: Timestamp) -> Balance {
+            if now >= self.stop_time {
+                return self.deposit;
+            }
+            let time_delta: Balance = self.delta_seconds(now).into();
+            time_delta * self.rate_per_second
+        }
+
         /// Get the balance for `who` at `now`
         pub fn get_balance(&self, who: AccountId, time: Timestamp) -> Balance {
-            let time_delta: Balance = self.delta_seconds(time).into();
-            let recipient_balance = (time_delta * self.rate_per_second) - self.amount_withdrawn();
+            let recipient_balance = self.streamed_amount(time) - self.amount_withdrawn();
 
             // return appropriate balance
             if who == self.recipient {
@@ -173,21 +247,55 @@ mod contract {
                 0
             }
         }
+
+        /// The part of `refund` the sender forfeits to the recipient when cancelling before `stop_time`
+        pub fn early_cancel_penalty(&self, refund: Balance) -> Balance {
+            let max_bps = Balance::from(MAX_BPS);
+            self.early_cancel_penalty_bps.map_or(0, |bps| {
+                let bps = Balance::from(bps);
+                // dividing first loses some precision, but only for refunds that would overflow otherwise
+                refund.checked_mul(bps).map_or(refund / max_bps * bps, |x| x / max_bps)
+            })
+        }
     }
 
     impl Erc1620 {
         /// Creates a new ERC-1620 contract instance
         #[allow(clippy::new_without_default)]
         #[ink(constructor)]
-        pub fn new() -> Self {
+        pub fn new() -> Self { Self::with_limits(None, DEFAULT_MAX_STREAMS_PER_RECIPIENT, DEFAULT_MIN_DEPOSIT) }
+
+        /// Creates a new ERC-1620 contract instance that mints tokens from `stream_nft` to represent streams created
+        /// with `create_tokenized_stream`. The contract must be a minter of `stream_nft`.
+        #[ink(constructor)]
+        pub fn with_stream_nft(stream_nft: AccountId) -> Self {
+            Self::with_limits(Some(stream_nft), DEFAULT_MAX_STREAMS_PER_RECIPIENT, DEFAULT_MIN_DEPOSIT)
+        }
+
+        /// Creates a new ERC-1620 contract instance with an optional `stream_nft`, allowing at most
+        /// `max_streams_per_recipient` active streams per recipient and deposits of at least `min_deposit`.
+        #[ink(constructor)]
+        pub fn with_limits(
+            stream_nft: Option<AccountId>,
+            max_streams_per_recipient: u32,
+            min_deposit: Balance,
+        ) -> Self {
             Self {
                 streams_by_id: Default::default(),
                 next_stream_id: 1.into(),
                 stream_ids_by_account: Default::default(),
+                stream_nft: stream_nft.into(),
+                stream_ids_by_token: Default::default(),
+                token_ids_by_stream: Default::default(),
+                max_streams_per_recipient: max_streams_per_recipient.into(),
+                min_deposit: min_deposit.into(),
             }
         }
 
-        /// Creates a new stream funded by the caller and paid towards `recipient`.
+        /// Creates a new stream funded by the caller and paid towards `recipient`. With `strict_divisibility` the
+        /// deposit must be a multiple of the duration in seconds, otherwise the remainder of `deposit / duration` is
+        /// paid out at `stop_time`. If the sender cancels the stream before `stop_time`, `early_cancel_penalty_bps`
+        /// basis points of their refund are paid to the recipient instead.
         #[ink(message)]
         pub fn create_stream(
             &mut self,
@@ -196,6 +304,8 @@ mod contract {
             token_address: AccountId,
             start_time: Timestamp,
             stop_time: Timestamp,
+            strict_divisibility: bool,
+            early_cancel_penalty_bps: Option<u16>,
         ) -> Result<StreamId> {
             let caller = self.env().caller();
 
@@ -205,22 +315,35 @@ mod contract {
             }
 
             // validate time
-            if stop_time < start_time {
-                return Err(Error::InvalidStopTime);
-            }
+            let duration: Balance = time::duration_secs(start_time, stop_time).ok_or(Error::InvalidStopTime)?.into();
             let now = self.env().block_timestamp();
             if start_time < now {
                 return Err(Error::InvalidStartTime);
             }
 
             // validate deposit
-            let duration = core::time::Duration::from_millis(stop_time - start_time).as_secs().into();
+            if duration == 0 {
+                return Err(Error::InvalidStopTime);
+            }
+            // a smaller deposit would truncate the rate to zero
             if deposit < duration {
                 return Err(Error::DepositSmallerThanTimeDelta);
             }
-            if deposit % duration != 0 {
+            if strict_divisibility && deposit % duration != 0 {
                 return Err(Error::DepositNotMultipleOfZero);
             }
+            if deposit < *self.min_deposit {
+                return Err(Error::DepositBelowMinimum);
+            }
+            if early_cancel_penalty_bps.map_or(false, |bps| bps > MAX_BPS) {
+                return Err(Error::InvalidPenalty);
+            }
+
+            // bound the streams `withdraw_from_all_streams` iterates over
+            let stream_count = self.stream_ids_by_account.get(&recipient).map_or(0, Vec::len);
+            if stream_count >= *self.max_streams_per_recipient as usize {
+                return Err(Error::TooManyStreams);
+            }
 
             // transfer tokens to contract
             #[cfg(not(test))]
@@ -239,6 +362,7 @@ mod contract {
                 sender: caller,
                 token_address,
                 is_entity: true,
+                early_cancel_penalty_bps,
             });
 
             self.stream_ids_by_account
@@ -261,9 +385,93 @@ mod contract {
             Ok(stream_id)
         }
 
+        /// Creates a new stream like `create_stream` and mints an NFT representing it to `recipient`. Whoever holds
+        /// the NFT is the recipient of the stream, so the stream can be traded by transferring the token.
+        #[ink(message)]
+        pub fn create_tokenized_stream(
+            &mut self,
+            recipient: AccountId,
+            deposit: Balance,
+            token_address: AccountId,
+            start_time: Timestamp,
+            stop_time: Timestamp,
+            strict_divisibility: bool,
+            early_cancel_penalty_bps: Option<u16>,
+        ) -> Result<(StreamId, TokenId)> {
+            let stream_nft = self.stream_nft.ok_or(Error::StreamNftNotConfigured)?;
+            let stream_id = self.create_stream(
+                recipient,
+                deposit,
+                token_address,
+                start_time,
+                stop_time,
+                strict_divisibility,
+                early_cancel_penalty_bps,
+            )?;
+
+            let token_id = mint_stream_nft(stream_nft, recipient)?;
+            self.stream_ids_by_token.insert(token_id, stream_id);
+            self.token_ids_by_stream.insert(stream_id, token_id);
+
+            self.env().emit_event(StreamTokenized { stream_id, token_id, recipient });
+            Ok((stream_id, token_id))
+        }
+
+        /// Makes the current holder of the NFT representing the stream its recipient and returns the recipient.
+        /// Withdrawing and cancelling do this implicitly, but `withdraw_from_all_streams` only includes streams that
+        /// have been synced to the caller.
+        #[ink(message)]
+        pub fn sync_stream_recipient(&mut self, stream_id: StreamId) -> Result<AccountId> {
+            let token_id = *self.token_ids_by_stream.get(&stream_id).ok_or(Error::StreamNotTokenized)?;
+            self.sync_recipient(stream_id, token_id)
+        }
+
+        /// Returns the id of the NFT representing the stream, if it is tokenized
+        #[ink(message)]
+        pub fn stream_token_id(&self, stream_id: StreamId) -> Option<TokenId> {
+            self.token_ids_by_stream.get(&stream_id).copied()
+        }
+
+        /// Returns the id of the stream represented by the NFT with id `token_id`
+        #[ink(message)]
+        pub fn token_stream_id(&self, token_id: TokenId) -> Option<StreamId> {
+            self.stream_ids_by_token.get(&token_id).copied()
+        }
+
+        /// Whether the contract holds at least the remaining balance of every stream paid in `token`
+        #[ink(message)]
+        pub fn is_solvent(&self, token: AccountId) -> bool {
+            let remaining: Balance = self
+                .streams_by_id
+                .values()
+                .filter(|stream| stream.token_address == token)
+                .map(|stream| stream.remaining_balance)
+                .sum();
+            erc20_balance_of(token, self.env().account_id()) >= remaining
+        }
+
+        /// Returns the ERC-721 contract used to mint tokens representing streams
+        #[ink(message)]
+        pub fn stream_nft(&self) -> Option<AccountId> { *self.stream_nft }
+
         /// Withdraws from the contract to the recipient's account.
         #[ink(message)]
         pub fn withdraw_from_stream(&mut self, stream_id: StreamId, amount: Balance) -> Result<bool> {
+            self.withdraw(stream_id, amount, None)
+        }
+
+        /// Withdraws from the contract to `to` instead of the recipient's account. Only the recipient may redirect
+        /// a withdrawal, e.g. when the recipient is a contract that can't receive the tokens.
+        #[ink(message)]
+        pub fn withdraw_to(&mut self, stream_id: StreamId, amount: Balance, to: AccountId) -> Result<bool> {
+            if to == ZERO_ACCOUNT {
+                return Err(Error::InvalidRecipient);
+            }
+            self.withdraw(stream_id, amount, Some(to))
+        }
+
+        /// Withdraws `amount` from the stream and pays it to `to`, or to the recipient if `to` is `None`
+        fn withdraw(&mut self, stream_id: StreamId, amount: Balance, to: Option<AccountId>) -> Result<bool> {
             let caller = self.env().caller();
 
             // validate amount
@@ -271,13 +479,22 @@ mod contract {
                 return Err(Error::AmountCannotBeZero);
             }
 
-            let (remaining_balance, recipient) = {
+            // pay the current holder of a tokenized stream
+            if let Some(token_id) = self.stream_token_id(stream_id) {
+                self.sync_recipient(stream_id, token_id)?;
+            }
+
+            let (remaining_balance, recipient, to) = {
                 let now = self.env().block_timestamp();
                 let stream = self.streams_by_id.get_mut(&stream_id).ok_or(Error::StreamNotFound)?;
 
+                if to.is_some() && caller != stream.recipient {
+                    return Err(Error::OnlyCallableByRecipient);
+                }
                 if caller != stream.recipient && caller != stream.sender {
                     return Err(Error::OnlyCallableBySenderOrRecipient);
                 }
+                let to = to.unwrap_or(stream.recipient);
 
                 // validate balance of recipient
                 if stream.get_balance(stream.recipient, now) < amount {
@@ -289,29 +506,53 @@ mod contract {
 
                 // transfer the tokens
                 #[cfg(not(test))]
-                stream.token().transfer(stream.recipient, amount)?;
+                stream.token().transfer(to, amount)?;
 
-                (stream.remaining_balance, stream.recipient)
+                (stream.remaining_balance, stream.recipient, to)
             };
 
-            // remove the stream if it's empty
+            // remove the stream if it's empty, so it no longer counts towards the recipient's streams
             if remaining_balance == 0 {
                 self.streams_by_id.take(&stream_id);
+                self.remove_stream_token(stream_id);
+                if let Some(ids) = self.stream_ids_by_account.get_mut(&recipient) {
+                    ids.retain(|id| *id != stream_id);
+                }
             }
 
             // emit event
-            self.env().emit_event(WithdrawFromStream { stream_id, recipient, amount });
+            self.env().emit_event(WithdrawFromStream { stream_id, recipient, to, amount });
 
             Ok(true)
         }
 
-        /// Cancels the stream and transfers the tokens back on a pro ratea basis.
+        /// Cancels the stream and transfers the tokens back on a pro ratea basis. May only be called by the sender or
+        /// the recipient. A sender cancelling before `stop_time` pays the stream's early cancel penalty to the
+        /// recipient.
         #[ink(message)]
         pub fn cancel_stream(&mut self, stream_id: StreamId) -> Result<bool> {
-            let stream = self.streams_by_id.take(&stream_id).ok_or(Error::StreamNotFound)?;
+            let caller = self.env().caller();
+
+            // pay the current holder of a tokenized stream
+            if let Some(token_id) = self.stream_token_id(stream_id) {
+                self.sync_recipient(stream_id, token_id)?;
+            }
+            let stream = self.streams_by_id.get(&stream_id).ok_or(Error::StreamNotFound)?;
+            if caller != stream.recipient && caller != stream.sender {
+                return Err(Error::OnlyCallableBySenderOrRecipient);
+            }
+
+            // the stream was found above, so this unwrap cannot fail
+            let stream = self.streams_by_id.take(&stream_id).unwrap();
+            self.remove_stream_token(stream_id);
             let now = self.env().block_timestamp();
-            let sender_balance = stream.get_balance(stream.sender, now);
-            let recipient_balance = stream.get_balance(stream.recipient, now);
+            let mut sender_balance = stream.get_balance(stream.sender, now);
+            let mut recipient_balance = stream.get_balance(stream.recipient, now);
+            if caller == stream.sender && now < stream.stop_time {
+                let penalty = stream.early_cancel_penalty(sender_balance);
+                sender_balance -= penalty;
+                recipient_balance += penalty;
+            }
 
             // transfer tokens
             if recipient_balance > 0 {
@@ -367,10 +608,28 @@ mod contract {
             self.stream_ids_by_account.get(&account).cloned()
         }
 
+        /// Returns the maximum number of active streams per recipient
+        #[ink(message)]
+        pub fn max_streams_per_recipient(&self) -> u32 { *self.max_streams_per_recipient }
+
+        /// Returns the minimum deposit of a stream
+        #[ink(message)]
+        pub fn min_deposit(&self) -> Balance { *self.min_deposit }
+
         /// Returns the stream with id `stream_id`
         #[ink(message)]
         pub fn get_stream(&self, stream_id: StreamId) -> Option<Stream> { self.streams_by_id.get(&stream_id).cloned() }
 
+        /// Returns the stream with id `stream_id` together with the real-time `(sender_balance, recipient_balance)`
+        #[ink(message)]
+        pub fn get_stream_with_balances(&self, stream_id: StreamId) -> Option<(Stream, Balance, Balance)> {
+            let stream = self.streams_by_id.get(&stream_id)?.clone();
+            let now = self.env().block_timestamp();
+            let sender_balance = stream.get_balance(stream.sender, now);
+            let recipient_balance = stream.get_balance(stream.recipient, now);
+            Some((stream, sender_balance, recipient_balance))
+        }
+
         /// Returns the real-time balance of the account with address `who`.
         #[ink(message)]
         pub fn balance_of(&self, stream_id: StreamId, who: AccountId) -> Result<Balance> {
@@ -391,6 +650,38 @@ mod contract {
             stream_id
         }
 
+        /// Makes the holder of `token_id` the recipient of `stream_id` and returns the recipient
+        fn sync_recipient(&mut self, stream_id: StreamId, token_id: TokenId) -> Result<AccountId> {
+            let stream_nft = self.stream_nft.ok_or(Error::StreamNftNotConfigured)?;
+            let stream = self.streams_by_id.get_mut(&stream_id).ok_or(Error::StreamNotFound)?;
+            let previous_recipient = stream.recipient;
+            let new_recipient = match stream_nft_owner(stream_nft, token_id) {
+                // a burned token leaves the stream with its last recipient
+                Some(owner) if owner != previous_recipient => owner,
+                _ => return Ok(previous_recipient),
+            };
+            stream.recipient = new_recipient;
+
+            // move the stream id, keeping the new recipient's ids sorted for `cancel_stream`
+            if let Some(ids) = self.stream_ids_by_account.get_mut(&previous_recipient) {
+                ids.retain(|id| *id != stream_id);
+            }
+            let ids = self.stream_ids_by_account.entry(new_recipient).or_insert_with(Vec::new);
+            if let Err(index) = ids.binary_search(&stream_id) {
+                ids.insert(index, stream_id);
+            }
+
+            self.env().emit_event(StreamRecipientChanged { stream_id, previous_recipient, new_recipient });
+            Ok(new_recipient)
+        }
+
+        /// Removes the token mapping of a stream that no longer exists
+        fn remove_stream_token(&mut self, stream_id: StreamId) {
+            if let Some(token_id) = self.token_ids_by_stream.take(&stream_id) {
+                self.stream_ids_by_token.take(&token_id);
+            }
+        }
+
         /// Get the current time
         #[cfg(test)]
         fn now() -> Timestamp { Self::env().block_timestamp() }
@@ -399,11 +690,138 @@ mod contract {
     /// Gets an ERC-20 token from an account id
     fn get_erc20(account_id: AccountId) -> Erc20 { FromAccountId::from_account_id(account_id) }
 
+    /// Returns the balance of `owner` in the ERC-20 `token`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn erc20_balance_of(token: AccountId, owner: AccountId) -> Balance { get_erc20(token).balance_of(owner) }
+
+    /// Mints a token representing a stream from the `stream_nft` contract
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn mint_stream_nft(stream_nft: AccountId, recipient: AccountId) -> Result<TokenId> {
+        let mut erc721: erc721::Erc721 = FromAccountId::from_account_id(stream_nft);
+        Ok(erc721.mint(recipient)?)
+    }
+
+    /// Returns the owner of a token of the `stream_nft` contract
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn stream_nft_owner(stream_nft: AccountId, token_id: TokenId) -> Option<AccountId> {
+        let erc721: erc721::Erc721 = FromAccountId::from_account_id(stream_nft);
+        erc721.owner_of(token_id)
+    }
+
+    #[cfg(test)]
+    use tests::{
+        mock_erc20::balance_of as erc20_balance_of,
+        mock_stream_nft::{mint as mint_stream_nft, owner_of as stream_nft_owner},
+    };
+
     #[cfg(test)]
     mod tests {
         use super::*;
         use contract_utils::test_utils;
 
+        /// In-memory ERC-20 balances, as token contracts can't be called off-chain
+        pub(super) mod mock_erc20 {
+            use super::*;
+            use std::{cell::RefCell, collections::BTreeMap};
+
+            thread_local! {
+                static BALANCES: RefCell<BTreeMap<(AccountId, AccountId), Balance>> = RefCell::new(BTreeMap::new());
+            }
+
+            pub fn balance_of(token: AccountId, owner: AccountId) -> Balance {
+                BALANCES.with(|balances| balances.borrow().get(&(token, owner)).copied().unwrap_or_default())
+            }
+
+            pub fn set_balance(token: AccountId, owner: AccountId, balance: Balance) {
+                BALANCES.with(|balances| balances.borrow_mut().insert((token, owner), balance));
+            }
+        }
+
+        /// An in-memory stand-in for the stream NFT contract, which can't be called off-chain
+        pub(super) mod mock_stream_nft {
+            use super::*;
+            use std::{cell::RefCell, collections::BTreeMap};
+
+            thread_local! {
+                static OWNERS: RefCell<BTreeMap<TokenId, AccountId>> = RefCell::new(BTreeMap::new());
+            }
+
+            pub fn mint(_stream_nft: AccountId, recipient: AccountId) -> Result<TokenId> {
+                OWNERS.with(|owners| {
+                    let mut owners = owners.borrow_mut();
+                    let token_id = owners.len() as TokenId + 1;
+                    owners.insert(token_id, recipient);
+                    Ok(token_id)
+                })
+            }
+
+            pub fn owner_of(_stream_nft: AccountId, token_id: TokenId) -> Option<AccountId> {
+                OWNERS.with(|owners| owners.borrow().get(&token_id).copied())
+            }
+
+            pub fn transfer(token_id: TokenId, to: AccountId) {
+                OWNERS.with(|owners| owners.borrow_mut().insert(token_id, to));
+            }
+        }
+
+        #[ink::test]
+        fn test_get_stream_with_balances() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let start_time = Erc1620::now();
+            let stream_id = instance
+                .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000, true, None)
+                .unwrap();
+
+            let assert_bundled_balances = |instance: &Erc1620| {
+                let (stream, sender_balance, recipient_balance) = instance.get_stream_with_balances(stream_id).unwrap();
+                assert_eq!(Some(stream), instance.get_stream(stream_id));
+                assert_eq!(sender_balance, instance.balance_of(stream_id, accounts.alice).unwrap());
+                assert_eq!(recipient_balance, instance.balance_of(stream_id, accounts.bob).unwrap());
+                (sender_balance, recipient_balance)
+            };
+
+            assert_eq!(assert_bundled_balances(&instance), (10_000, 0));
+            test_utils::advance_time(4_000);
+            assert_eq!(assert_bundled_balances(&instance), (6_000, 4_000));
+            instance.withdraw_from_stream(stream_id, 1_000).unwrap();
+            assert_eq!(assert_bundled_balances(&instance), (6_000, 3_000));
+
+            assert_eq!(instance.get_stream_with_balances(stream_id + 1), None);
+        }
+
+        #[ink::test]
+        fn test_stream_limits() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = Erc1620::with_limits(None, 2, 5_000);
+            let start_time = Erc1620::now();
+            let stop_time = start_time + 1_000;
+
+            // deposits below the minimum are rejected
+            assert_eq!(
+                instance.create_stream(accounts.bob, 4_999, ZERO_ACCOUNT, start_time, stop_time, false, None),
+                Err(Error::DepositBelowMinimum)
+            );
+
+            // recipients are capped at two streams
+            instance.create_stream(accounts.bob, 5_000, ZERO_ACCOUNT, start_time, stop_time, true, None).unwrap();
+            instance.create_stream(accounts.bob, 5_000, ZERO_ACCOUNT, start_time, stop_time, true, None).unwrap();
+            assert_eq!(
+                instance.create_stream(accounts.bob, 5_000, ZERO_ACCOUNT, start_time, stop_time, true, None),
+                Err(Error::TooManyStreams)
+            );
+            instance.create_stream(accounts.charlie, 5_000, ZERO_ACCOUNT, start_time, stop_time, true, None).unwrap();
+
+            // finished streams free up room
+            test_utils::advance_time(1_000);
+            test_utils::set_caller(accounts.bob);
+            instance.withdraw_from_all_streams().unwrap();
+            ink_env::test::pop_execution_context();
+            instance
+                .create_stream(accounts.bob, 5_000, ZERO_ACCOUNT, stop_time, stop_time + 1_000, true, None)
+                .unwrap();
+        }
+
         /// Validate creating and withdrawing from a stream
         #[ink::test]
         fn test_create_and_withdraw() {
@@ -412,8 +830,9 @@ mod contract {
             let start_time = Erc1620::now();
 
             // create a stream and validate it
-            let stream_id =
-                instance.create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000).unwrap();
+            let stream_id = instance
+                .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000, true, None)
+                .unwrap();
             let stream = instance.get_stream(stream_id).unwrap();
             assert_eq!(stream, Stream {
                 deposit: 10_000,
@@ -424,7 +843,8 @@ mod contract {
                 recipient: accounts.bob,
                 sender: accounts.alice,
                 token_address: ZERO_ACCOUNT,
-                is_entity: true
+                is_entity: true,
+                early_cancel_penalty_bps: None
             });
 
             // check balnaces at start
@@ -477,8 +897,9 @@ mod contract {
             let start_time = Erc1620::now();
 
             // create a stream and validate it
-            let stream_id =
-                instance.create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000).unwrap();
+            let stream_id = instance
+                .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000, true, None)
+                .unwrap();
             instance.get_stream(stream_id).unwrap();
 
             // cancel the stream and make sure it doesn't exist
@@ -486,6 +907,157 @@ mod contract {
             assert!(instance.get_stream(stream_id).is_none());
         }
 
+        #[ink::test]
+        fn test_cancel_after_withdrawal() {
+            type Event = <Erc1620 as ::ink_lang::BaseEvent>::Type;
+            let accounts = test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let start_time = Erc1620::now();
+            let stream_id = instance
+                .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000, true, None)
+                .unwrap();
+
+            // part of the streamed amount is withdrawn before more is streamed
+            test_utils::advance_time(4_000);
+            instance.withdraw_from_stream(stream_id, 1_500).unwrap();
+            test_utils::advance_time(2_000);
+            let remaining_balance = instance.get_stream(stream_id).unwrap().remaining_balance;
+            assert_eq!(remaining_balance, 8_500);
+
+            // the recipient gets the streamed amount that was not withdrawn, the sender the rest
+            instance.cancel_stream(stream_id).unwrap();
+            let event = ink_env::test::recorded_events().last().unwrap();
+            match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                Event::CancelStream(CancelStream { sender_balance, recipient_balance, .. }) => {
+                    assert_eq!(recipient_balance, 4_500);
+                    assert_eq!(sender_balance, 4_000);
+                    assert_eq!(sender_balance + recipient_balance, remaining_balance);
+                }
+                _ => panic!("expected a CancelStream event"),
+            }
+        }
+
+        /// Cancels `stream_id` and returns the `(sender_balance, recipient_balance)` it paid out
+        fn cancel_and_get_balances(instance: &mut Erc1620, stream_id: StreamId) -> (Balance, Balance) {
+            type Event = <Erc1620 as ::ink_lang::BaseEvent>::Type;
+            instance.cancel_stream(stream_id).unwrap();
+            let event = ink_env::test::recorded_events().last().unwrap();
+            match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                Event::CancelStream(CancelStream { sender_balance, recipient_balance, .. }) => {
+                    (sender_balance, recipient_balance)
+                }
+                _ => panic!("expected a CancelStream event"),
+            }
+        }
+
+        #[ink::test]
+        fn test_early_cancel_penalty() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let start_time = Erc1620::now();
+            let stop_time = start_time + 10_000;
+            assert_eq!(
+                instance.create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, stop_time, true, Some(10_001)),
+                Err(Error::InvalidPenalty)
+            );
+            let stream_id = instance
+                .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, stop_time, true, Some(2_500))
+                .unwrap();
+            let other_stream_id = instance
+                .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, stop_time, true, Some(2_500))
+                .unwrap();
+            test_utils::advance_time(4_000);
+
+            // the sender cancelling early pays a quarter of the 6_000 refund to the recipient
+            assert_eq!(cancel_and_get_balances(&mut instance, stream_id), (4_500, 5_500));
+
+            // the recipient cancelling early is not penalized
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(cancel_and_get_balances(&mut instance, other_stream_id), (6_000, 4_000));
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn test_early_cancel_by_third_party() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let start_time = Erc1620::now();
+            let stream_id = instance
+                .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000, true, Some(2_500))
+                .unwrap();
+            test_utils::advance_time(4_000);
+
+            // the sender can't dodge the penalty by cancelling from another account
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(instance.cancel_stream(stream_id), Err(Error::OnlyCallableBySenderOrRecipient));
+            ink_env::test::pop_execution_context();
+            assert!(instance.get_stream(stream_id).is_some());
+            assert_eq!(cancel_and_get_balances(&mut instance, stream_id), (4_500, 5_500));
+        }
+
+        #[ink::test]
+        fn test_early_cancel_penalty_does_not_overflow() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let start_time = Erc1620::now();
+            let stream_id = instance
+                .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000, true, Some(5_000))
+                .unwrap();
+            let stream = instance.get_stream(stream_id).unwrap();
+            assert_eq!(stream.early_cancel_penalty(10_000), 5_000);
+            assert_eq!(stream.early_cancel_penalty(Balance::MAX), Balance::MAX / 10_000 * 5_000);
+        }
+
+        #[ink::test]
+        fn test_early_cancel_penalty_after_stop_time() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let start_time = Erc1620::now();
+            let stream_id = instance
+                .create_stream(accounts.bob, 10_005, ZERO_ACCOUNT, start_time, start_time + 10_000, false, Some(5_000))
+                .unwrap();
+            test_utils::advance_time(10_000);
+
+            // the whole deposit has been streamed, so the sender is refunded nothing and forfeits nothing
+            assert_eq!(cancel_and_get_balances(&mut instance, stream_id), (0, 10_005));
+        }
+
+        #[ink::test]
+        fn test_withdraw_to() {
+            type Event = <Erc1620 as ::ink_lang::BaseEvent>::Type;
+            let accounts = test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let start_time = Erc1620::now();
+            let stream_id = instance
+                .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000, true, None)
+                .unwrap();
+            test_utils::advance_time(5_000);
+
+            // only the recipient may redirect a withdrawal
+            assert_eq!(instance.withdraw_to(stream_id, 1_000, accounts.charlie), Err(Error::OnlyCallableByRecipient));
+
+            // bob redirects part of the streamed amount to charlie
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(instance.withdraw_to(stream_id, 1_000, ZERO_ACCOUNT), Err(Error::InvalidRecipient));
+            assert_eq!(instance.withdraw_to(stream_id, 5_001, accounts.charlie), Err(Error::InsufficientBalance));
+            instance.withdraw_to(stream_id, 2_000, accounts.charlie).unwrap();
+            let event = ink_env::test::recorded_events().last().unwrap();
+            match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                Event::WithdrawFromStream(WithdrawFromStream { recipient, to, amount, .. }) => {
+                    assert_eq!(recipient, accounts.bob);
+                    assert_eq!(to, accounts.charlie);
+                    assert_eq!(amount, 2_000);
+                }
+                _ => panic!("expected a WithdrawFromStream event"),
+            }
+
+            // the redirected amount is accounted to the stream like a regular withdrawal
+            let stream = instance.get_stream(stream_id).unwrap();
+            assert_eq!(stream.remaining_balance, 8_000);
+            assert_eq!(stream.get_balance(accounts.bob, Erc1620::now()), 3_000);
+            ink_env::test::pop_execution_context();
+        }
+
         #[ink::test]
         fn test_multiple_streams_are_updated() {
             let accounts = contract_utils::test_utils::default_accounts();
@@ -494,8 +1066,9 @@ mod contract {
             let total = 100;
 
             for i in 1..total {
-                let stream_id =
-                    instance.create_stream(accounts.bob, 100, ZERO_ACCOUNT, start_time, start_time + 10_000).unwrap();
+                let stream_id = instance
+                    .create_stream(accounts.bob, 100, ZERO_ACCOUNT, start_time, start_time + 10_000, true, None)
+                    .unwrap();
                 // we expect the streams to increment one by one.
                 assert_eq!(i, stream_id)
             }
@@ -515,7 +1088,7 @@ mod contract {
 
             for i in 1..=total {
                 let stream_id = instance
-                    .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000)
+                    .create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time + 10_000, true, None)
                     .unwrap();
                 // we expect the streams to increment one by one.
                 assert_eq!(i, stream_id)
@@ -526,5 +1099,143 @@ mod contract {
             let balance = instance.withdraw_from_all_streams().unwrap();
             assert_eq!(balance, total * 5_000)
         }
+
+        #[ink::test]
+        fn test_non_divisible_deposit() {
+            let accounts = contract_utils::test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let start_time = Erc1620::now();
+            let stop_time = start_time + 10_000;
+
+            // strict mode rejects a deposit that isn't a multiple of the duration
+            assert_eq!(
+                instance.create_stream(accounts.bob, 10_005, ZERO_ACCOUNT, start_time, stop_time, true, None),
+                Err(Error::DepositNotMultipleOfZero)
+            );
+            // a deposit smaller than the duration is rejected in both modes
+            assert_eq!(
+                instance.create_stream(accounts.bob, 9, ZERO_ACCOUNT, start_time, stop_time, false, None),
+                Err(Error::DepositSmallerThanTimeDelta)
+            );
+
+            // lenient mode accepts it and pays the remainder at the end
+            let stream_id =
+                instance.create_stream(accounts.bob, 10_005, ZERO_ACCOUNT, start_time, stop_time, false, None).unwrap();
+            let stream = instance.get_stream(stream_id).unwrap();
+            assert_eq!(stream.rate_per_second, 1_000);
+            assert_eq!(stream.get_balance(accounts.bob, start_time + 5_000), 5_000);
+            assert_eq!(stream.get_balance(accounts.alice, start_time + 5_000), 5_005);
+            assert_eq!(stream.get_balance(accounts.bob, stop_time), 10_005);
+            assert_eq!(stream.get_balance(accounts.alice, stop_time), 0);
+
+            // withdrawing everything removes the stream
+            test_utils::advance_time(10_000);
+            test_utils::set_caller(accounts.bob);
+            instance.withdraw_from_stream(stream_id, 10_005).unwrap();
+            assert!(instance.get_stream(stream_id).is_none());
+        }
+
+        #[ink::test]
+        fn test_invalid_time_range() {
+            let accounts = contract_utils::test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let start_time = Erc1620::now() + 10_000;
+
+            // inverted range
+            assert_eq!(
+                instance.create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time - 1, false, None),
+                Err(Error::InvalidStopTime)
+            );
+            // zero-length range
+            assert_eq!(
+                instance.create_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, start_time, false, None),
+                Err(Error::InvalidStopTime)
+            );
+        }
+
+        #[ink::test]
+        fn test_tokenized_stream() {
+            let accounts = test_utils::default_accounts();
+            let start_time = Erc1620::now();
+            let stop_time = start_time + 10_000;
+
+            // tokenizing requires a stream NFT contract
+            assert_eq!(
+                Erc1620::new().create_tokenized_stream(
+                    accounts.bob,
+                    10_000,
+                    ZERO_ACCOUNT,
+                    start_time,
+                    stop_time,
+                    true,
+                    None
+                ),
+                Err(Error::StreamNftNotConfigured)
+            );
+
+            // mint the stream NFT to bob
+            let mut instance = Erc1620::with_stream_nft(accounts.django);
+            let (stream_id, token_id) = instance
+                .create_tokenized_stream(accounts.bob, 10_000, ZERO_ACCOUNT, start_time, stop_time, true, None)
+                .unwrap();
+            assert_eq!(instance.stream_token_id(stream_id), Some(token_id));
+            assert_eq!(instance.token_stream_id(token_id), Some(stream_id));
+            assert_eq!(mock_stream_nft::owner_of(accounts.django, token_id), Some(accounts.bob));
+
+            // bob withdraws, then sells the NFT to charlie
+            test_utils::advance_time(5_000);
+            test_utils::set_caller(accounts.bob);
+            instance.withdraw_from_stream(stream_id, 1_000).unwrap();
+            mock_stream_nft::transfer(token_id, accounts.charlie);
+
+            // bob no longer receives the stream, charlie withdraws the rest of the streamed amount
+            assert_eq!(instance.withdraw_from_stream(stream_id, 1_000), Err(Error::OnlyCallableBySenderOrRecipient));
+            test_utils::set_caller(accounts.charlie);
+            instance.withdraw_from_stream(stream_id, 4_000).unwrap();
+            let stream = instance.get_stream(stream_id).unwrap();
+            assert_eq!(stream.recipient, accounts.charlie);
+            assert_eq!(stream.remaining_balance, 5_000);
+            assert_eq!(instance.stream_ids(accounts.bob), Some(vec![]));
+            assert_eq!(instance.stream_ids(accounts.charlie), Some(vec![stream_id]));
+
+            // withdrawing the rest removes the stream and its token mapping
+            test_utils::advance_time(5_000);
+            instance.withdraw_from_stream(stream_id, 5_000).unwrap();
+            assert_eq!(instance.token_stream_id(token_id), None);
+        }
+
+        #[ink::test]
+        fn test_is_solvent() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = Erc1620::new();
+            let contract = ink_env::account_id::<ink_env::DefaultEnvironment>().unwrap();
+            let start_time = Erc1620::now();
+            let (token, other_token) = (accounts.django, accounts.eve);
+
+            // two streams in `token` and one in `other_token`
+            for (stream_token, deposit) in [(token, 10_000), (token, 20_000), (other_token, 50_000)].iter().copied() {
+                instance
+                    .create_stream(accounts.bob, deposit, stream_token, start_time, start_time + 10_000, true, None)
+                    .unwrap();
+            }
+
+            // the contract holds every deposit
+            mock_erc20::set_balance(token, contract, 30_000);
+            mock_erc20::set_balance(other_token, contract, 50_000);
+            assert!(instance.is_solvent(token));
+            assert!(instance.is_solvent(other_token));
+
+            // balances moved out of the contract leave it undercollateralized
+            mock_erc20::set_balance(token, contract, 29_999);
+            assert!(!instance.is_solvent(token));
+            assert!(instance.is_solvent(other_token));
+
+            // withdrawals reduce what the contract must hold
+            test_utils::advance_time(5_000);
+            test_utils::set_caller(accounts.bob);
+            instance.withdraw_from_stream(1, 5_000).unwrap();
+            mock_erc20::set_balance(token, contract, 25_000);
+            assert!(instance.is_solvent(token));
+        }
     }
 }
@@ -3,6 +3,7 @@
 
 use contract_utils::env_exports::*;
 use ink_lang as ink;
+use ink_storage::traits::{PackedLayout, SpreadLayout};
 use scale::{Decode, Encode};
 
 pub use contract::Erc20;
@@ -26,6 +27,58 @@ pub enum Error {
     /// Transfers cannot be completed because they are paused
     #[error(display = "Transfers cannot be completed because they are paused")]
     TransfersPaused,
+    /// The token has no decimal count, so whole units cannot be converted
+    #[error(display = "The token has no decimal count")]
+    DecimalCountNotSet,
+    /// The amount does not fit into a balance
+    #[error(display = "The amount does not fit into a balance")]
+    Overflow,
+    /// The contract was created without a timelock
+    #[error(display = "The contract was created without a timelock")]
+    TimelockDisabled,
+    /// The operation is timelocked and has to be scheduled with `schedule_op`
+    #[error(display = "The operation is timelocked and has to be scheduled")]
+    Timelocked,
+    /// No operation is scheduled under the id
+    #[error(display = "No operation is scheduled under the id")]
+    OperationNotFound,
+    /// The delay of the scheduled operation has not passed yet
+    #[error(display = "The delay of the scheduled operation has not passed yet")]
+    OperationNotReady,
+}
+
+/// The timelock of sensitive admin operations
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+pub struct Timelock {
+    /// How long a scheduled operation has to wait before it can be executed
+    pub delay: Timestamp,
+    /// Mints of more than this amount have to be scheduled
+    pub mint_threshold: Balance,
+}
+
+/// An admin operation that has to be scheduled while the timelock is enabled
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+pub enum TimelockedOp {
+    /// Pause the contract
+    Pause,
+    /// Give the Minter role to the account
+    AddMinter(AccountId),
+    /// Mint `amount` coins to `recipient`
+    Mint { recipient: AccountId, amount: Balance },
+}
+
+/// A scheduled operation
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+pub struct PendingOp {
+    /// The operation to execute
+    pub op: TimelockedOp,
+    /// The account that scheduled the operation
+    pub scheduler: AccountId,
+    /// The time from which the operation can be executed
+    pub eta: Timestamp,
 }
 
 /// The ERC-20 result type.
@@ -64,6 +117,7 @@ pub trait Erc20Base {
 #[ink::contract]
 pub mod contract {
     use super::*;
+    use contract_utils::TokenStandard;
     use enumflags2::{bitflags, BitFlags};
     use ink_prelude::string::String;
 
@@ -94,6 +148,14 @@ pub mod contract {
         symbol: Lazy<Option<String>>,
         /// Optional decimals of the token
         decimal_count: Lazy<Option<u8>>,
+
+        // timelock
+        /// The timelock of admin operations, if enabled
+        timelock: Lazy<Option<Timelock>>,
+        /// Scheduled operations by id
+        pending_ops: HashMap<u64, PendingOp>,
+        /// The id of the next scheduled operation
+        next_op_id: Lazy<u64>,
     }
 
     // ========= ERC20 ========
@@ -109,6 +171,18 @@ pub mod contract {
         value: Balance,
     }
 
+    /// Event emitted alongside `Transfer` when a transfer is tagged with a memo.
+    #[ink(event)]
+    pub struct TransferWithMemo {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        #[ink(topic)]
+        memo: [u8; 32],
+    }
+
     /// Event emitted when an approval occurs that `spender` is allowed to withdraw
     /// up to the amount of `value` tokens from `owner`.
     #[ink(event)]
@@ -154,6 +228,22 @@ pub mod contract {
         #[ink(constructor)]
         pub fn new(initial_supply: Balance) -> Self { Self::new_optional(initial_supply, None, None, None) }
 
+        /// Create a new instance whose `pause`, `add_minter` and mints above `mint_threshold` have to be scheduled
+        /// with `schedule_op` and can only be executed `delay` after scheduling.
+        #[ink(constructor)]
+        pub fn new_timelocked(
+            initial_supply: Balance,
+            name: Option<String>,
+            symbol: Option<String>,
+            decimal_count: Option<u8>,
+            delay: Timestamp,
+            mint_threshold: Balance,
+        ) -> Self {
+            let mut instance = Self::new_optional(initial_supply, name, symbol, decimal_count);
+            Lazy::set(&mut instance.timelock, Some(Timelock { delay, mint_threshold }));
+            instance
+        }
+
         /// Create a new instance with additional optional arguments
         #[ink(constructor)]
         pub fn new_optional(
@@ -178,6 +268,9 @@ pub mod contract {
                 name: Lazy::new(name),
                 symbol: Lazy::new(symbol),
                 decimal_count: Lazy::new(decimal_count),
+                timelock: Lazy::new(None),
+                pending_ops: HashMap::new(),
+                next_op_id: Lazy::new(0),
             };
             Self::env().emit_event(Transfer { from: None, to: Some(caller), value: initial_supply });
             instance
@@ -187,6 +280,21 @@ pub mod contract {
         #[ink(message)]
         pub fn total_supply(&self) -> Balance { *self.total_supply }
 
+        /// Returns the standard this contract implements. Its selector is `contract_utils::TOKEN_STANDARD_SELECTOR` in
+        /// every token contract, so callers can check the standard of an unknown contract.
+        #[ink(message)]
+        #[ink(selector = "0x746f6b73")]
+        pub fn token_standard(&self) -> TokenStandard { TokenStandard::Erc20 }
+
+        /// Returns whether the contract supports every feature in `feature`, a set of `contract_utils::token_feature`
+        /// bits. Its selector is `contract_utils::SUPPORTS_SELECTOR` in every token contract.
+        #[ink(message)]
+        #[ink(selector = "0x73757070")]
+        pub fn supports(&self, feature: u32) -> bool {
+            use contract_utils::token_feature::*;
+            feature & !(MINT | BURN | PAUSE | METADATA) == 0
+        }
+
         /// Returns the account balance for the specified `owner`.
         ///
         /// Returns `0` if the account is non-existent.
@@ -254,6 +362,23 @@ pub mod contract {
             self.allowances.insert((from, caller), allowance - value);
             Ok(())
         }
+
+        /// Transfers `value` amount of tokens from the caller's account to account `to`, tagging the transfer
+        /// with `memo` so it can be reconciled off-chain.
+        ///
+        /// On success a `Transfer` event is emitted, followed by a `TransferWithMemo` event.
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the caller's account balance.
+        #[ink(message)]
+        pub fn transfer_with_memo(&mut self, to: AccountId, value: Balance, memo: [u8; 32]) -> Result<()> {
+            let from = self.caller();
+            self._transfer_from_to(from, to, value)?;
+            self.env().emit_event(TransferWithMemo { from, to, value, memo });
+            Ok(())
+        }
     }
 
     // ========== ACCESS CONTROL
@@ -337,11 +462,43 @@ pub mod contract {
         contract.add_roles(accounts.bob, accounts.alice, Role::Admin.into()).unwrap();
     }
 
+    impl Erc20 {
+        /// Returns the roles of `account` as raw `Role` bits: Admin (1), Minter (2), Burner (4), Pauser (8)
+        #[ink(message)]
+        pub fn roles_of(&self, account: AccountId) -> u8 { self.get_roles(account).bits() }
+    }
+
+    /// Test reading the raw role bits
+    #[ink::test]
+    fn test_roles_of() {
+        let mut contract = test_utils::new_erc20(1);
+        let accounts = test_utils::default_accounts();
+
+        assert_eq!(contract.roles_of(accounts.alice), RoleBitFlags::all().bits());
+        assert_eq!(contract.roles_of(accounts.bob), 0);
+
+        contract.add_roles(accounts.alice, accounts.bob, Role::Minter | Role::Burner).unwrap();
+        assert_eq!(contract.roles_of(accounts.bob), 0b_0000_0110);
+    }
+
     impl Erc20 {
         /// Sends `amount` coins to mint account
         #[ink(message)]
         #[ink(selector = "0xcfdd9aa2")]
         pub fn mint(&mut self, recipient: AccountId, amount: Balance) -> Result<()> {
+            self.ensure_mint_not_timelocked(amount)?;
+            self._mint(self.caller(), recipient, amount)
+        }
+
+        /// Sends `whole_units` coins scaled by `10^decimal_count` to the mint account, so that minting matches the
+        /// precision of the token. Fails with `DecimalCountNotSet` if the token has no decimal count.
+        #[ink(message)]
+        pub fn mint_whole(&mut self, recipient: AccountId, whole_units: Balance) -> Result<()> {
+            let decimal_count = self.decimal_count().ok_or(Error::DecimalCountNotSet)?;
+            let amount = Balance::checked_pow(10, decimal_count.into())
+                .and_then(|unit| whole_units.checked_mul(unit))
+                .ok_or(Error::Overflow)?;
+            self.ensure_mint_not_timelocked(amount)?;
             self._mint(self.caller(), recipient, amount)
         }
 
@@ -374,7 +531,15 @@ pub mod contract {
         /// Add the Minter role to an account
         #[ink(message)]
         pub fn add_minter(&mut self, account: AccountId) -> Result<()> {
-            self.add_roles(self.caller(), account, Role::Minter.into())?;
+            if self.timelock.is_some() {
+                return Err(Error::Timelocked);
+            }
+            self._add_minter(self.caller(), account)
+        }
+
+        /// Internal implementation of add_minter
+        fn _add_minter(&mut self, caller: AccountId, account: AccountId) -> Result<()> {
+            self.add_roles(caller, account, Role::Minter.into())?;
             self.env().emit_event(AddedMinter { account });
             Ok(())
         }
@@ -418,6 +583,27 @@ pub mod contract {
         contract._mint(accounts.bob, accounts.alice, 2).unwrap_err();
     }
 
+    /// Test minting whole units
+    #[ink::test]
+    fn test_mint_whole() {
+        let accounts = test_utils::default_accounts();
+
+        // a token without decimals has no whole units
+        let mut contract = test_utils::new_erc20(100);
+        assert_eq!(contract.mint_whole(accounts.bob, 1), Err(Error::DecimalCountNotSet));
+
+        let mut contract = Erc20::new_optional(0, None, None, Some(12));
+        contract.mint_whole(accounts.bob, 3).unwrap();
+        assert_eq!(contract.balance_of(accounts.bob), 3_000_000_000_000);
+        assert_eq!(contract.total_supply(), 3_000_000_000_000);
+
+        // raw amounts are still minted as they are
+        contract.mint(accounts.bob, 5).unwrap();
+        assert_eq!(contract.balance_of(accounts.bob), 3_000_000_000_005);
+
+        assert_eq!(contract.mint_whole(accounts.bob, Balance::MAX), Err(Error::Overflow));
+    }
+
     // impl Burnable for Contract {
     impl Erc20 {
         /// Destroys `amount` tokens
@@ -543,7 +729,12 @@ pub mod contract {
 
         /// Pauses the contract
         #[ink(message)]
-        pub fn pause(&mut self) -> Result<()> { self._set_is_paused(self.caller(), true) }
+        pub fn pause(&mut self) -> Result<()> {
+            if self.timelock.is_some() {
+                return Err(Error::Timelocked);
+            }
+            self._set_is_paused(self.caller(), true)
+        }
 
         /// Unpauses the contract
         #[ink(message)]
@@ -609,6 +800,144 @@ pub mod contract {
         contract._transfer_from_to(accounts.alice, accounts.bob, 10).unwrap();
     }
 
+    // ========== TIMELOCK
+
+    /// Event emitted when an operation is scheduled
+    #[ink(event)]
+    pub struct OperationScheduled {
+        #[ink(topic)]
+        id: u64,
+        op: TimelockedOp,
+        eta: Timestamp,
+    }
+
+    /// Event emitted when a scheduled operation is executed
+    #[ink(event)]
+    pub struct OperationExecuted {
+        #[ink(topic)]
+        id: u64,
+    }
+
+    /// Event emitted when a scheduled operation is cancelled
+    #[ink(event)]
+    pub struct OperationCancelled {
+        #[ink(topic)]
+        id: u64,
+    }
+
+    impl Erc20 {
+        /// The timelock of admin operations, if enabled
+        #[ink(message)]
+        pub fn timelock(&self) -> Option<Timelock> { *self.timelock }
+
+        /// The scheduled operation with the id
+        #[ink(message)]
+        pub fn pending_op(&self, id: u64) -> Option<PendingOp> { self.pending_ops.get(&id).copied() }
+
+        /// Schedules `op` to be executed after the timelock delay and returns its id. The caller needs the role
+        /// required by the operation.
+        #[ink(message)]
+        pub fn schedule_op(&mut self, op: TimelockedOp) -> Result<u64> {
+            let timelock = self.timelock.ok_or(Error::TimelockDisabled)?;
+            let caller = self.caller();
+            let role = match op {
+                TimelockedOp::Pause => Role::Pauser,
+                TimelockedOp::AddMinter(_) => Role::Admin,
+                TimelockedOp::Mint { .. } => Role::Minter,
+            };
+            if !self.get_roles(caller).contains(role) {
+                return Err(Error::MissingRole);
+            }
+
+            let id = *self.next_op_id;
+            *self.next_op_id += 1;
+            let eta = self.env().block_timestamp() + timelock.delay;
+            self.pending_ops.insert(id, PendingOp { op, scheduler: caller, eta });
+            self.env().emit_event(OperationScheduled { id, op, eta });
+            Ok(id)
+        }
+
+        /// Executes the scheduled operation once its delay has passed
+        #[ink(message)]
+        pub fn execute_op(&mut self, id: u64) -> Result<()> {
+            let pending = self.pending_op(id).ok_or(Error::OperationNotFound)?;
+            if self.env().block_timestamp() < pending.eta {
+                return Err(Error::OperationNotReady);
+            }
+
+            let caller = self.caller();
+            match pending.op {
+                TimelockedOp::Pause => self._set_is_paused(caller, true)?,
+                TimelockedOp::AddMinter(account) => self._add_minter(caller, account)?,
+                TimelockedOp::Mint { recipient, amount } => self._mint(caller, recipient, amount)?,
+            }
+            self.pending_ops.take(&id);
+            self.env().emit_event(OperationExecuted { id });
+            Ok(())
+        }
+
+        /// Cancels the scheduled operation. Only callable by whoever scheduled it or an Admin.
+        #[ink(message)]
+        pub fn cancel_op(&mut self, id: u64) -> Result<()> {
+            let pending = self.pending_op(id).ok_or(Error::OperationNotFound)?;
+            let caller = self.caller();
+            if caller != pending.scheduler && !self.get_roles(caller).contains(Role::Admin) {
+                return Err(Error::MissingRole);
+            }
+            self.pending_ops.take(&id);
+            self.env().emit_event(OperationCancelled { id });
+            Ok(())
+        }
+
+        /// Fails with `Timelocked` if mints of `amount` have to be scheduled
+        fn ensure_mint_not_timelocked(&self, amount: Balance) -> Result<()> {
+            match *self.timelock {
+                Some(timelock) if amount > timelock.mint_threshold => Err(Error::Timelocked),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    /// Test scheduling and executing timelocked operations
+    #[ink::test]
+    #[cfg(test)]
+    fn test_timelock() {
+        let accounts = test_utils::default_accounts();
+
+        // without the timelock nothing can be scheduled
+        let mut contract = test_utils::new_erc20(100);
+        assert_eq!(contract.schedule_op(TimelockedOp::Pause), Err(Error::TimelockDisabled));
+
+        let mut contract = Erc20::new_timelocked(100, None, None, None, 1_000, 50);
+
+        // sensitive operations have to be scheduled, small mints don't
+        assert_eq!(contract.pause(), Err(Error::Timelocked));
+        assert_eq!(contract.add_minter(accounts.bob), Err(Error::Timelocked));
+        assert_eq!(contract.mint(accounts.bob, 51), Err(Error::Timelocked));
+        contract.mint(accounts.bob, 50).unwrap();
+
+        let mint = contract.schedule_op(TimelockedOp::Mint { recipient: accounts.bob, amount: 1_000 }).unwrap();
+        let pause = contract.schedule_op(TimelockedOp::Pause).unwrap();
+
+        // only the scheduler or an admin may cancel
+        test_utils::set_caller(accounts.bob);
+        assert_eq!(contract.schedule_op(TimelockedOp::AddMinter(accounts.bob)), Err(Error::MissingRole));
+        assert_eq!(contract.cancel_op(pause), Err(Error::MissingRole));
+        ink_env::test::pop_execution_context();
+        contract.cancel_op(pause).unwrap();
+        assert_eq!(contract.execute_op(pause), Err(Error::OperationNotFound));
+
+        // the mint can't be executed before the delay has passed
+        test_utils::advance_time(999);
+        assert_eq!(contract.execute_op(mint), Err(Error::OperationNotReady));
+        assert_eq!(contract.balance_of(accounts.bob), 50);
+
+        test_utils::advance_time(1);
+        contract.execute_op(mint).unwrap();
+        assert_eq!(contract.balance_of(accounts.bob), 1_050);
+        assert_eq!(contract.pending_op(mint), None);
+    }
+
     // ========== Optional Data
     impl Erc20 {
         /// The number of decimals
@@ -756,6 +1085,23 @@ pub mod contract {
             assert_transfer_event(&emitted_events[0], None, Some(AccountId::from([0x01; 32])), 100);
         }
 
+        /// The advertised features match the implemented messages.
+        #[ink::test]
+        fn test_token_standard() {
+            let erc20 = test_utils::new_erc20(100);
+            assert_eq!(erc20.token_standard(), TokenStandard::Erc20);
+        }
+
+        #[ink::test]
+        fn test_supports() {
+            use contract_utils::token_feature::*;
+
+            let erc20 = test_utils::new_erc20(100);
+            assert!(erc20.supports(MINT | BURN | PAUSE | METADATA));
+            assert!(!erc20.supports(ROYALTIES));
+            assert!(!erc20.supports(MINT | BATCH));
+        }
+
         /// The total supply was applied.
         #[ink::test]
         fn test_total_supply() {
@@ -879,6 +1225,33 @@ pub mod contract {
             );
         }
 
+        #[ink::test]
+        fn test_transfer_with_memo() {
+            let mut erc20 = test_utils::new_erc20(100);
+            let accounts = test_utils::default_accounts();
+            let memo = [0x2a; 32];
+
+            // Alice transfers 10 tokens to Bob with a memo.
+            assert_eq!(erc20.transfer_with_memo(accounts.bob, 10, memo), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.alice), 90);
+            assert_eq!(erc20.balance_of(accounts.bob), 10);
+
+            // The standard transfer event is followed by the memo event.
+            let emitted_events = ink_env::test::recorded_events().collect::<Vec<_>>();
+            assert_eq!(emitted_events.len(), 3);
+            assert_transfer_event(&emitted_events[1], Some(accounts.alice), Some(accounts.bob), 10);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &emitted_events[2].data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::TransferWithMemo(event) = decoded_event {
+                assert_eq!(event.from, accounts.alice);
+                assert_eq!(event.to, accounts.bob);
+                assert_eq!(event.value, 10);
+                assert_eq!(event.memo, memo);
+            } else {
+                panic!("encountered unexpected event kind: expected a TransferWithMemo event")
+            }
+        }
+
         #[ink::test]
         fn test_allowance_must_not_change_on_failed_transfer() {
             let mut contract = test_utils::new_erc20(100);
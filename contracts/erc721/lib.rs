@@ -8,10 +8,13 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[ink::contract]
 mod erc721 {
     use super::*;
-    use ink_prelude::vec::Vec;
+    use contract_utils::TokenStandard;
+    use enumflags2::{bitflags, BitFlags};
+    use ink_prelude::{string::String, vec::Vec};
     use ink_storage::{
         collections::{hashmap::Entry, HashMap as StorageHashMap},
         traits::{PackedLayout, SpreadLayout},
+        Lazy,
     };
     use scale::{Decode, Encode};
 
@@ -24,9 +27,14 @@ mod erc721 {
     /// A token ID.
     pub type TokenId = u64;
 
+    /// The maximum number of owners `balances_of` answers in one call
+    pub const MAX_BALANCES_QUERY: usize = 100;
+
     #[ink(storage)]
     #[derive(Default)]
     pub struct Erc721 {
+        /// The account that created the contract
+        owner: AccountId,
         /// Next Token Id
         next_token_id: u64,
         /// Mapping from TokenId to TokenInfo
@@ -39,6 +47,14 @@ mod erc721 {
         approvals_by_token_id: StorageHashMap<TokenId, AccountId>,
         /// Mapping from owner to operator approvals.
         operator_approvals: StorageHashMap<(AccountId, AccountId), bool>,
+        /// Roles for each account. Each value is stored as a raw BitFlags<Role>.
+        roles: StorageHashMap<AccountId, u8>,
+        /// If set, the URI of a token is this base followed by the token id
+        base_uri: Lazy<Option<String>>,
+        /// Once true, the metadata and base URI can no longer be changed
+        metadata_frozen: bool,
+        /// The royalty receiver and royalty of tokens minted with `mint_with_royalty`
+        royalties_by_token_id: StorageHashMap<TokenId, (AccountId, Balance)>,
     }
 
     #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone, err_derive::Error)]
@@ -71,6 +87,15 @@ mod erc721 {
         /// Cannot remove the caller as approved user
         #[error(display = "Cannot remove the caller as approved user")]
         CannotRemove,
+        /// Only the contract owner may perform this operation
+        #[error(display = "Only the contract owner may perform this operation")]
+        OnlyOwnerAllowed,
+        /// The caller is missing the role required for this operation
+        #[error(display = "The caller is missing the role required for this operation")]
+        MissingRole,
+        /// The metadata has been frozen and can no longer be changed
+        #[error(display = "The metadata has been frozen and can no longer be changed")]
+        MetadataFrozen,
     }
 
     /// Event emitted when a token transfer occurs.
@@ -106,17 +131,39 @@ mod erc721 {
         approved: bool,
     }
 
+    /// Event emitted when a minter is added.
+    #[ink(event)]
+    pub struct AddedMinter {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    /// Event emitted when a minter is removed.
+    #[ink(event)]
+    pub struct RemovedMinter {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
     impl Erc721 {
         /// Creates a new ERC721 token contract.
         #[ink(constructor)]
         pub fn new() -> Self {
+            let caller = Self::env().caller();
+            let mut roles = StorageHashMap::new();
+            roles.insert(caller, RoleBitFlags::all().bits());
             Self {
+                owner: caller,
                 next_token_id: 0,
                 token_infos_by_id: Default::default(),
                 owners_by_token_id: Default::default(),
                 token_counts_by_account_id: Default::default(),
                 approvals_by_token_id: Default::default(),
                 operator_approvals: Default::default(),
+                roles,
+                base_uri: Default::default(),
+                metadata_frozen: false,
+                royalties_by_token_id: Default::default(),
             }
         }
 
@@ -126,14 +173,92 @@ mod erc721 {
         #[ink(message)]
         pub fn balance_of(&self, owner: AccountId) -> u64 { self.balance_of_or_zero(&owner) }
 
+        /// Returns the balance of each of `owners`, in the same order. Only the first `MAX_BALANCES_QUERY` owners are
+        /// answered, so the result may be shorter than the input.
+        #[ink(message)]
+        pub fn balances_of(&self, owners: Vec<AccountId>) -> Vec<u64> {
+            owners.iter().take(MAX_BALANCES_QUERY).map(|owner| self.balance_of_or_zero(owner)).collect()
+        }
+
         /// Returns the owner of the token.
         #[ink(message)]
         pub fn owner_of(&self, id: TokenId) -> Option<AccountId> { self.owners_by_token_id.get(&id).cloned() }
 
+        /// Returns the standard this contract implements. Its selector is `contract_utils::TOKEN_STANDARD_SELECTOR` in
+        /// every token contract, so callers can check the standard of an unknown contract.
+        #[ink(message)]
+        #[ink(selector = "0x746f6b73")]
+        pub fn token_standard(&self) -> TokenStandard { TokenStandard::Erc721 }
+
+        /// Returns whether the contract supports every feature in `feature`, a set of `contract_utils::token_feature`
+        /// bits. Its selector is `contract_utils::SUPPORTS_SELECTOR` in every token contract.
+        #[ink(message)]
+        #[ink(selector = "0x73757070")]
+        pub fn supports(&self, feature: u32) -> bool {
+            use contract_utils::token_feature::*;
+            feature & !(MINT | BURN | METADATA | ROYALTIES) == 0
+        }
+
         /// Returns the metadata of the token.
         #[ink(message)]
         pub fn token_info_of(&self, id: TokenId) -> Option<TokenInfo> { self.token_infos_by_id.get(&id).cloned() }
 
+        /// Returns the URI of the token's metadata. If a base URI is set, this is the base URI followed by the token
+        /// id, otherwise it is the metadata stored with the token. Returns `None` if the token does not exist or it
+        /// has no URI.
+        #[ink(message)]
+        pub fn token_uri(&self, id: TokenId) -> Option<String> {
+            let info = self.token_infos_by_id.get(&id)?;
+            match &*self.base_uri {
+                Some(base_uri) => Some(ink_prelude::format!("{}{}", base_uri, id)),
+                None => String::from_utf8(info.metadata.clone()).ok().filter(|uri| !uri.is_empty()),
+            }
+        }
+
+        /// Returns the base URI of the tokens
+        #[ink(message)]
+        pub fn base_uri(&self) -> Option<String> { (*self.base_uri).clone() }
+
+        /// Sets or removes the base URI of the tokens. Only callable by the contract owner.
+        #[ink(message)]
+        pub fn set_base_uri(&mut self, base_uri: Option<String>) -> Result<()> {
+            self.ensure_metadata_editable()?;
+            Lazy::set(&mut self.base_uri, base_uri);
+            Ok(())
+        }
+
+        /// Replaces the metadata of token `id`. Only callable by the contract owner.
+        #[ink(message)]
+        pub fn set_token_metadata(&mut self, id: TokenId, metadata: Vec<u8>) -> Result<()> {
+            self.ensure_metadata_editable()?;
+            let info = self.token_infos_by_id.get_mut(&id).ok_or(Error::TokenNotFound)?;
+            info.metadata = metadata;
+            Ok(())
+        }
+
+        /// Permanently prevents changes to the token metadata and base URI. Only callable by the contract owner.
+        #[ink(message)]
+        pub fn freeze_metadata(&mut self) -> Result<()> {
+            self.ensure_metadata_editable()?;
+            self.metadata_frozen = true;
+            Ok(())
+        }
+
+        /// Returns true if the metadata has been frozen
+        #[ink(message)]
+        pub fn is_metadata_frozen(&self) -> bool { self.metadata_frozen }
+
+        /// Ensures the caller is the contract owner and the metadata is not frozen
+        fn ensure_metadata_editable(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OnlyOwnerAllowed);
+            }
+            if self.metadata_frozen {
+                return Err(Error::MetadataFrozen);
+            }
+            Ok(())
+        }
+
         /// Transfers the token from the caller to the given destination.
         #[ink(message)]
         pub fn transfer(&mut self, destination: AccountId, id: TokenId) -> Result<()> {
@@ -149,48 +274,88 @@ mod erc721 {
             Ok(())
         }
 
-        /// Creates a new token.
+        /// Creates a new token. The caller must have the Minter role.
         #[ink(message)]
         pub fn mint(&mut self, recipient: AccountId) -> Result<TokenId> {
             self.mint_with_metadata(recipient, Vec::new())
         }
 
-        /// Creates a new token with metadata.
+        /// Creates a new token with metadata. The caller must have the Minter role.
         #[ink(message)]
         pub fn mint_with_metadata(&mut self, recipient: AccountId, metadata: Vec<u8>) -> Result<TokenId> {
+            if !self.get_roles(self.env().caller()).contains(Role::Minter) {
+                return Err(Error::MissingRole);
+            }
+            let token_id = get_next_token_id(self.next_token_id)?;
+
+            // Save token_id
+            self.next_token_id = token_id;
+
+            self.insert_token(recipient, token_id, metadata);
+            Ok(token_id)
+        }
+
+        /// Creates a new token whose royalties are paid to `receiver`. `royalty` is stored as given, e.g. the royalty
+        /// of a media in the media contract. The caller must have the Minter role.
+        #[ink(message)]
+        pub fn mint_with_royalty(
+            &mut self,
+            recipient: AccountId,
+            receiver: AccountId,
+            royalty: Balance,
+        ) -> Result<TokenId> {
+            let token_id = self.mint_with_metadata(recipient, Vec::new())?;
+            self.royalties_by_token_id.insert(token_id, (receiver, royalty));
+            Ok(token_id)
+        }
+
+        /// Returns the royalty receiver and royalty of the token, or `None` if it was not minted with a royalty
+        #[ink(message)]
+        pub fn royalty_info(&self, id: TokenId) -> Option<(AccountId, Balance)> {
+            self.royalties_by_token_id.get(&id).copied()
+        }
+
+        /// Creates a new token with the given `id`, for example to preserve ids bridged from another chain. Only
+        /// the contract owner can choose token ids.
+        #[ink(message)]
+        pub fn mint_with_id(&mut self, recipient: AccountId, id: TokenId, metadata: Vec<u8>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OnlyOwnerAllowed);
+            }
+            if self.exists(id) {
+                return Err(Error::TokenExists);
+            }
+
+            // make sure `mint` never generates this id later
+            self.next_token_id = self.next_token_id.max(id);
+
+            self.insert_token(recipient, id, metadata);
+            Ok(())
+        }
+
+        /// Stores a new token with `id` for `recipient`
+        fn insert_token(&mut self, recipient: AccountId, id: TokenId, metadata: Vec<u8>) {
             let Self {
-                next_token_id,
                 token_infos_by_id: tokens,
                 owners_by_token_id: token_owner,
                 token_counts_by_account_id: owned_tokens_count,
                 ..
             } = self;
 
-            let token_id = get_next_token_id(*next_token_id)?;
-
-            // Save token_id
-            *next_token_id = token_id;
-
             // Create Token Info
             let token_info = TokenInfo { metadata };
 
             // Insert token Info
-            tokens.insert(token_id, token_info);
+            tokens.insert(id, token_info);
 
             // Increase token count of to / owner of the minted Token
             let entry = owned_tokens_count.entry(recipient);
             increase_counter_of(entry);
 
             // Insert the caller as the owner of the minted Token
-            token_owner.insert(token_id, recipient);
+            token_owner.insert(id, recipient);
 
-            self.env().emit_event(Transfer {
-                from: Some(AccountId::from([0x0; 32])),
-                to: Some(recipient),
-                id: token_id,
-            });
-
-            Ok(token_id)
+            self.env().emit_event(Transfer { from: Some(AccountId::from([0x0; 32])), to: Some(recipient), id });
         }
 
         /// Deletes an existing token. Only the owner can burn the token.
@@ -214,6 +379,7 @@ mod erc721 {
 
             decrease_counter_of(&mut self.token_counts_by_account_id, &account)?;
             self.owners_by_token_id.take(&id);
+            self.royalties_by_token_id.take(&id);
             self.env().emit_event(Transfer { from: Some(account), to: Some(AccountId::from([0x0; 32])), id });
 
             Ok(())
@@ -368,12 +534,79 @@ mod erc721 {
             self.approved_for_all(owner, operator)
         }
 
+        /// Returns the account approved to transfer token `id`, if any. The approval is cleared when the token is
+        /// transferred.
+        #[ink(message)]
+        pub fn get_approved(&self, id: TokenId) -> Option<AccountId> { self.approvals_by_token_id.get(&id).copied() }
+
         /// Returns true if token `id` exists or false if it does not.
         fn exists(&self, id: TokenId) -> bool {
             self.owners_by_token_id.get(&id).is_some() && self.owners_by_token_id.contains_key(&id)
         }
     }
 
+    // ========== ACCESS CONTROL
+
+    /// Roles grant an account permissions for certain operations
+    #[bitflags]
+    #[derive(Debug, Clone, Copy, Eq, PartialEq)]
+    #[repr(u8)]
+    pub enum Role {
+        /// Can change roles (1)
+        Admin = 0b_0000_0001,
+        /// Can mint tokens (2)
+        Minter = 0b_0000_0010,
+    }
+
+    pub type RoleBitFlags = BitFlags<Role>;
+
+    impl Erc721 {
+        /// Add the Minter role to an account. The caller must have the Admin role.
+        #[ink(message)]
+        pub fn add_minter(&mut self, account: AccountId) -> Result<()> {
+            self.add_roles(self.env().caller(), account, Role::Minter.into())?;
+            self.env().emit_event(AddedMinter { account });
+            Ok(())
+        }
+
+        /// Remove the Minter role from an account. The caller must have the Admin role.
+        #[ink(message)]
+        pub fn remove_minter(&mut self, account: AccountId) -> Result<()> {
+            self.remove_roles(self.env().caller(), account, Role::Minter.into())?;
+            self.env().emit_event(RemovedMinter { account });
+            Ok(())
+        }
+
+        /// Add a role to an account. Caller must have Admin role.
+        fn add_roles(&mut self, caller: AccountId, account: AccountId, value: RoleBitFlags) -> Result<()> {
+            if !self.get_roles(caller).contains(Role::Admin) {
+                return Err(Error::MissingRole);
+            }
+            self.roles
+                .entry(account)
+                .and_modify(|x| *x = (unsafe { BitFlags::from_bits_unchecked(*x) } | value).bits())
+                .or_insert(value.bits());
+            Ok(())
+        }
+
+        /// Remove a role from an account. Caller must have Admin role.
+        fn remove_roles(&mut self, caller: AccountId, account: AccountId, value: RoleBitFlags) -> Result<()> {
+            if !self.get_roles(caller).contains(Role::Admin) {
+                return Err(Error::MissingRole);
+            }
+            self.roles
+                .entry(account)
+                .and_modify(|x| *x = (unsafe { BitFlags::from_bits_unchecked(*x) } & !value).bits())
+                .or_insert(0);
+            Ok(())
+        }
+
+        /// Get the roles for this account
+        fn get_roles(&self, account: AccountId) -> RoleBitFlags {
+            unsafe { RoleBitFlags::from_bits_unchecked(self.roles.get(&account).copied().unwrap_or_default()) }
+        }
+    }
+
     // Get the next token Id
     #[allow(dead_code)]
     fn get_next_token_id(current_id: TokenId) -> Result<TokenId> {
@@ -416,6 +649,150 @@ mod erc721 {
             assert_eq!(erc721.balance_of(accounts.alice), 1);
         }
 
+        #[ink::test]
+        fn mint_with_royalty_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc721 = Erc721::new();
+
+            let id = erc721.mint_with_royalty(accounts.bob, accounts.charlie, 5).unwrap();
+            assert_eq!(erc721.owner_of(id), Some(accounts.bob));
+            assert_eq!(erc721.royalty_info(id), Some((accounts.charlie, 5)));
+
+            // tokens minted without a royalty have none
+            let other = erc721.mint(accounts.bob).unwrap();
+            assert_eq!(erc721.royalty_info(other), None);
+
+            // the royalty is removed with the token
+            test_utils::set_caller(accounts.bob);
+            erc721.burn(id).unwrap();
+            assert_eq!(erc721.royalty_info(id), None);
+            test::pop_execution_context();
+
+            // minting with a royalty requires the Minter role
+            test_utils::set_caller(accounts.eve);
+            assert_eq!(erc721.mint_with_royalty(accounts.eve, accounts.eve, 5), Err(Error::MissingRole));
+        }
+
+        #[ink::test]
+        fn mint_requires_minter_role() {
+            let accounts = test_utils::default_accounts();
+            let mut erc721 = Erc721::new();
+
+            // bob is not a minter
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc721.mint(accounts.bob), Err(Error::MissingRole));
+            assert_eq!(erc721.add_minter(accounts.bob), Err(Error::MissingRole));
+            ink_env::test::pop_execution_context();
+
+            // the deployer grants bob the role
+            erc721.add_minter(accounts.bob).unwrap();
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc721.mint(accounts.bob), Ok(1));
+            ink_env::test::pop_execution_context();
+
+            // and takes it away again
+            erc721.remove_minter(accounts.bob).unwrap();
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc721.mint_with_metadata(accounts.bob, vec![1]), Err(Error::MissingRole));
+        }
+
+        #[ink::test]
+        fn balances_of_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc721 = Erc721::new();
+            erc721.mint(accounts.alice).unwrap();
+            erc721.mint(accounts.alice).unwrap();
+            erc721.mint(accounts.bob).unwrap();
+
+            assert_eq!(erc721.balances_of(vec![accounts.bob, accounts.charlie, accounts.alice]), vec![1, 0, 2]);
+
+            // the input is capped
+            let owners = vec![accounts.alice; MAX_BALANCES_QUERY + 1];
+            assert_eq!(erc721.balances_of(owners), vec![2; MAX_BALANCES_QUERY]);
+        }
+
+        #[ink::test]
+        fn mint_with_id_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc721 = Erc721::new();
+
+            // Mint a chosen id
+            assert_eq!(erc721.mint_with_id(accounts.bob, 5, vec![1, 2, 3]), Ok(()));
+            assert_eq!(erc721.owner_of(5), Some(accounts.bob));
+            assert_eq!(erc721.balance_of(accounts.bob), 1);
+            assert_eq!(erc721.token_info_of(5), Some(TokenInfo { metadata: vec![1, 2, 3] }));
+
+            // The counter skips past the chosen id
+            assert_eq!(erc721.mint(accounts.alice), Ok(6));
+
+            // A lower id does not move the counter back
+            assert_eq!(erc721.mint_with_id(accounts.alice, 2, Vec::new()), Ok(()));
+            assert_eq!(erc721.mint(accounts.alice), Ok(7));
+        }
+
+        #[ink::test]
+        fn mint_with_id_rejects_duplicate() {
+            let accounts = test_utils::default_accounts();
+            let mut erc721 = Erc721::new();
+
+            assert_eq!(erc721.mint(accounts.alice), Ok(1));
+            assert_eq!(erc721.mint_with_id(accounts.bob, 1, Vec::new()), Err(Error::TokenExists));
+            assert_eq!(erc721.owner_of(1), Some(accounts.alice));
+
+            // Only the owner may choose ids
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc721.mint_with_id(accounts.bob, 2, Vec::new()), Err(Error::OnlyOwnerAllowed));
+        }
+
+        #[ink::test]
+        fn token_uri_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc721 = Erc721::new();
+            erc721.mint_with_metadata(accounts.alice, b"ipfs://token-one".to_vec()).unwrap();
+            erc721.mint(accounts.alice).unwrap();
+
+            // without a base URI, the stored metadata is used
+            assert_eq!(erc721.token_uri(1), Some("ipfs://token-one".into()));
+            assert_eq!(erc721.token_uri(2), None);
+            assert_eq!(erc721.token_uri(3), None);
+
+            // the base URI is followed by the token id
+            erc721.set_base_uri(Some("https://example.com/nft/".into())).unwrap();
+            assert_eq!(erc721.token_uri(1), Some("https://example.com/nft/1".into()));
+            assert_eq!(erc721.token_uri(2), Some("https://example.com/nft/2".into()));
+            assert_eq!(erc721.token_uri(3), None);
+
+            // only the owner may set it
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc721.set_base_uri(None), Err(Error::OnlyOwnerAllowed));
+        }
+
+        #[ink::test]
+        fn freeze_metadata_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc721 = Erc721::new();
+            erc721.mint(accounts.alice).unwrap();
+
+            // metadata can be updated freely before freezing
+            erc721.set_token_metadata(1, vec![1]).unwrap();
+            erc721.set_base_uri(Some("ipfs://".into())).unwrap();
+            assert_eq!(erc721.token_info_of(1), Some(TokenInfo { metadata: vec![1] }));
+            assert_eq!(erc721.set_token_metadata(2, vec![2]), Err(Error::TokenNotFound));
+
+            // only the owner can freeze
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(erc721.freeze_metadata(), Err(Error::OnlyOwnerAllowed));
+            ink_env::test::pop_execution_context();
+
+            erc721.freeze_metadata().unwrap();
+            assert!(erc721.is_metadata_frozen());
+            assert_eq!(erc721.set_token_metadata(1, vec![2]), Err(Error::MetadataFrozen));
+            assert_eq!(erc721.set_base_uri(None), Err(Error::MetadataFrozen));
+            assert_eq!(erc721.freeze_metadata(), Err(Error::MetadataFrozen));
+            assert_eq!(erc721.token_info_of(1), Some(TokenInfo { metadata: vec![1] }));
+            assert_eq!(erc721.base_uri(), Some("ipfs://".into()));
+        }
+
         #[ink::test]
         fn transfer_works() {
             let accounts =
@@ -508,6 +885,37 @@ mod erc721 {
             assert_eq!(erc721.balance_of(accounts.eve), 1);
         }
 
+        #[ink::test]
+        fn token_standard_works() {
+            assert_eq!(TokenStandard::Erc721, Erc721::new().token_standard());
+        }
+
+        #[ink::test]
+        fn supports_works() {
+            use contract_utils::token_feature::*;
+
+            let erc721 = Erc721::new();
+            assert!(erc721.supports(MINT | BURN | METADATA | ROYALTIES));
+            assert!(!erc721.supports(PAUSE));
+            assert!(!erc721.supports(BATCH));
+        }
+
+        #[ink::test]
+        fn get_approved_works() {
+            let accounts = test_utils::default_accounts();
+            let mut erc721 = Erc721::new();
+            assert_eq!(erc721.mint(accounts.alice), Ok(1));
+            assert_eq!(erc721.get_approved(1), None);
+
+            // Bob is approved for token Id 1
+            assert_eq!(erc721.approve(accounts.bob, 1), Ok(()));
+            assert_eq!(erc721.get_approved(1), Some(accounts.bob));
+
+            // The approval is cleared once the token is transferred
+            assert_eq!(erc721.transfer(accounts.eve, 1), Ok(()));
+            assert_eq!(erc721.get_approved(1), None);
+        }
+
         #[ink::test]
         fn approved_for_all_works() {
             let accounts =
@@ -5,6 +5,8 @@ mod constants {
     pub const EXCHANGE_ID_SALT: [u8; 4] = *b"exid";
     /// Salt for generating offer id
     pub const OFFER_ID_SALT: [u8; 4] = *b"ofid";
+    /// Basis points in 100%
+    pub const MAX_FEE_BPS: u16 = 10_000;
 }
 
 /// Contains fhr contract code
@@ -51,8 +53,16 @@ mod contract {
         offer_ids_by_exchange_id: ink_storage::collections::HashMap<ExchangeId, Vec<OfferId>>,
         /// The offers by id
         offers_by_id: ink_storage::collections::HashMap<OfferId, Offer>,
+        /// The offer ids by creator (allows cancelling all offers of an account)
+        offer_ids_by_creator: ink_storage::collections::HashMap<AccountId, Vec<OfferId>>,
         /// Nonce used for random seed
         nonce: ink_storage::lazy::Lazy<u128>,
+        /// The owner of the contract, who configures the fee tiers and receives the fees
+        owner: ink_storage::lazy::Lazy<AccountId>,
+        /// The taker fee tiers, ordered by `min_volume`
+        fee_tiers: ink_storage::lazy::Lazy<Vec<FeeTier>>,
+        /// The cumulative value each trader has taken from offers, in offer tokens
+        volume_by_trader: ink_storage::collections::HashMap<AccountId, Balance>,
     }
 
     impl Exchanges {
@@ -65,10 +75,48 @@ mod contract {
                 exchanges_by_id: Default::default(),
                 offer_ids_by_exchange_id: Default::default(),
                 offers_by_id: Default::default(),
+                offer_ids_by_creator: Default::default(),
                 nonce: Default::default(),
+                owner: Self::env().caller().into(),
+                fee_tiers: Default::default(),
+                volume_by_trader: Default::default(),
             }
         }
 
+        /// Replaces the taker fee tiers. Traders pay the fee of the highest tier their cumulative volume reaches,
+        /// and no fee below the first tier. Only callable by the owner.
+        ///
+        /// * `tiers` - The tiers, ordered by strictly increasing `min_volume`
+        #[ink(message)]
+        pub fn set_fee_tiers(&mut self, tiers: Vec<FeeTier>) -> Result<()> {
+            if self.env().caller() != *self.owner {
+                return Err(Error::Unauthorized);
+            }
+            let is_ordered = tiers.windows(2).all(|pair| pair[0].min_volume < pair[1].min_volume);
+            if !is_ordered || tiers.iter().any(|tier| tier.taker_fee_bps > constants::MAX_FEE_BPS) {
+                return Err(Error::InvalidFeeTiers);
+            }
+            *self.fee_tiers = tiers;
+            Ok(())
+        }
+
+        /// Get the taker fee tiers
+        #[ink(message)]
+        pub fn fee_tiers(&self) -> Vec<FeeTier> { (*self.fee_tiers).clone() }
+
+        /// Get the taker fee in basis points that `trader` pays on their next fill
+        #[ink(message)]
+        pub fn fee_tier_of(&self, trader: AccountId) -> u16 {
+            let volume = self.traded_volume(trader);
+            self.fee_tiers.iter().rev().find(|tier| tier.min_volume <= volume).map_or(0, |tier| tier.taker_fee_bps)
+        }
+
+        /// Get the cumulative value `trader` has taken from offers
+        #[ink(message)]
+        pub fn traded_volume(&self, trader: AccountId) -> Balance {
+            self.volume_by_trader.get(&trader).copied().unwrap_or_default()
+        }
+
         /// Creates a new exchange for selling a given asset. It could be an NFT, a social token...
         ///
         /// * `exchange_token_id` - ExchangeToken token that is going to be traded through this order book model
@@ -82,7 +130,7 @@ mod contract {
             let offer_token = input.offer_token.into();
 
             // create exchange
-            let exchange_id = self.random_hash(constants::EXCHANGE_ID_SALT);
+            let exchange_id = self.new_exchange_id()?;
             let caller = self.env().caller();
             // let exchange_address = self.random_hash(EXCHANGE_ADDRESS_SALT).to_account_id();
             let exchange = Exchange {
@@ -94,7 +142,7 @@ mod contract {
             };
 
             // create offer
-            let offer_id = self.random_hash(constants::OFFER_ID_SALT);
+            let offer_id = self.new_offer_id()?;
             let offer = Offer {
                 id: offer_id,
                 exchange_id: exchange.id,
@@ -110,6 +158,7 @@ mod contract {
 
             // store the data
             self.exchanges_by_id.insert(exchange_id, exchange);
+            self.offer_ids_by_creator.entry(caller).or_insert_with(Vec::new).push(offer_id);
             self.offers_by_id.insert(offer_id, offer);
             self.offer_ids_by_exchange_id.entry(exchange_id).and_modify(|x| x.push(offer_id)).or_insert(vec![offer_id]);
 
@@ -175,7 +224,7 @@ mod contract {
 
         fn place_offer(&mut self, input: PlaceOfferRequest, offer_type: OfferType) -> Result<()> {
             // create the offer
-            let offer_id = self.random_hash(constants::OFFER_ID_SALT);
+            let offer_id = self.new_offer_id()?;
             let mut offer_token = input.offer_token.into();
             let offer = Offer {
                 id: offer_id,
@@ -200,6 +249,7 @@ mod contract {
             }
 
             // store the data
+            self.offer_ids_by_creator.entry(offer.creator).or_insert_with(Vec::new).push(offer_id);
             self.offers_by_id.insert(offer.id, offer);
             self.offer_ids_by_exchange_id
                 .entry(input.exchange_id)
@@ -228,13 +278,30 @@ mod contract {
             self.cancel_offer(input.exchange_id, input.offer_id, OfferType::Sell)
         }
 
+        /// Cancels every offer placed by the caller and refunds their escrowed tokens. Returns the number of
+        /// cancelled offers.
+        #[ink(message)]
+        pub fn cancel_all_offers(&mut self) -> Result<u32> {
+            let offer_ids = self.offer_ids_by_creator.get(&self.env().caller()).cloned().unwrap_or_default();
+            for offer_id in &offer_ids {
+                let exchange_id = self.get_offer(offer_id)?.exchange_id;
+                self.remove_offer(exchange_id, *offer_id)?;
+            }
+            Ok(offer_ids.len() as u32)
+        }
+
         /// Cancels an offer
         fn cancel_offer(&mut self, exchange_id: ExchangeId, offer_id: OfferId, offer_type: OfferType) -> Result<()> {
-            let exchange = self.get_exchange(&exchange_id)?;
-            let offer = self.get_offer(&offer_id)?;
-            if offer.offer_type != offer_type {
+            if self.get_offer(&offer_id)?.offer_type != offer_type {
                 return Err(Error::OfferTypeMismatch);
             }
+            self.remove_offer(exchange_id, offer_id)
+        }
+
+        /// Refunds the escrowed tokens of an offer to its creator and removes it
+        fn remove_offer(&mut self, exchange_id: ExchangeId, offer_id: OfferId) -> Result<()> {
+            let exchange = self.get_exchange(&exchange_id)?;
+            let offer = self.get_offer(&offer_id)?;
 
             // transfer the tokens back to the creator
             match offer.offer_type {
@@ -243,7 +310,14 @@ mod contract {
             }
 
             // update storage
+            let creator = offer.creator;
             self.offers_by_id.take(&offer_id);
+            if let Some(offer_ids) = self.offer_ids_by_creator.get_mut(&creator) {
+                offer_ids.retain(|x| *x != offer_id);
+                if offer_ids.is_empty() {
+                    self.offer_ids_by_creator.take(&creator);
+                }
+            }
             if let Some(offer_ids) = self.offer_ids_by_exchange_id.get_mut(&exchange_id) {
                 if let Some((index, _)) = offer_ids.iter().enumerate().find(|(_, x)| **x == offer_id) {
                     offer_ids.remove(index);
@@ -267,6 +341,8 @@ mod contract {
         #[ink(message)]
         pub fn buy_from_offer(&mut self, input: OfferRequest) -> Result<()> {
             let mut exchange_token = self.get_exchange(&input.exchange_id).map(|x| x.exchange_token)?;
+            let taker_fee_bps = self.fee_tier_of(input.address);
+            let owner = *self.owner;
             let offer = self.get_offer_mut(&input.offer_id)?;
             if offer.offer_type != OfferType::Sell {
                 return Err(Error::OfferTypeMismatch);
@@ -276,13 +352,19 @@ mod contract {
                 return Err(Error::InsufficientBalance);
             }
 
-            // transfer offer tokens from buyer to the offer's creator
-            offer.token.transfer_from(input.address, offer.creator, Some(offer.price * input.amount))?;
+            // transfer offer tokens from buyer to the offer's creator, and the fee on top to the owner
+            let value = offer.price * input.amount;
+            offer.token.transfer_from(input.address, offer.creator, Some(value))?;
+            let fee = taker_fee(value, taker_fee_bps);
+            if fee > 0 {
+                offer.token.transfer_from(input.address, owner, Some(fee))?;
+            }
             // transfer exchange token from exchange to the buyer
             exchange_token.transfer(input.address, input.amount)?;
 
             // update offer state
             offer.amount -= input.amount;
+            self.add_traded_volume(input.address, value);
             Ok(())
         }
 
@@ -295,6 +377,8 @@ mod contract {
         #[ink(message)]
         pub fn sell_from_offer(&mut self, input: OfferRequest) -> Result<()> {
             let mut exchange_token = self.get_exchange(&input.exchange_id).map(|x| x.exchange_token)?;
+            let taker_fee_bps = self.fee_tier_of(input.address);
+            let owner = *self.owner;
             let offer = self.get_offer_mut(&input.offer_id)?;
             if offer.offer_type != OfferType::Buy {
                 return Err(Error::OfferTypeMismatch);
@@ -306,15 +390,40 @@ mod contract {
 
             // transfer exchange tokens from the exchange to the offer creator
             exchange_token.transfer(offer.creator, Some(input.amount))?;
-            // transfer offer tokens from the exchange to the seller
-            offer.token.transfer(input.address, Some(offer.price * input.amount))?;
+            // transfer offer tokens from the exchange to the seller, less the fee which goes to the owner
+            let value = offer.price * input.amount;
+            let fee = taker_fee(value, taker_fee_bps);
+            offer.token.transfer(input.address, Some(value - fee))?;
+            if fee > 0 {
+                offer.token.transfer(owner, Some(fee))?;
+            }
 
             // update offer state
             offer.amount -= input.amount;
+            self.add_traded_volume(input.address, value);
             Ok(())
         }
 
-        /// Generate a random `Hash` based on caller, nonce, and salt
+        /// Generate an id for a new exchange
+        fn new_exchange_id(&mut self) -> Result<ExchangeId> {
+            let id = self.random_hash(constants::EXCHANGE_ID_SALT);
+            if self.exchanges_by_id.contains_key(&id) {
+                return Err(Error::IdCollision);
+            }
+            Ok(id)
+        }
+
+        /// Generate an id for a new offer
+        fn new_offer_id(&mut self) -> Result<OfferId> {
+            let id = self.random_hash(constants::OFFER_ID_SALT);
+            if self.offers_by_id.contains_key(&id) {
+                return Err(Error::IdCollision);
+            }
+            Ok(id)
+        }
+
+        /// Generate a random `Hash` based on caller, nonce, and salt. The nonce is incremented for every hash, so no
+        /// two hashes share a subject, even when generated in the same block.
         fn random_hash(&mut self, salt: [u8; 4]) -> Hash {
             use contract_utils::AccountIdExt;
 
@@ -347,6 +456,12 @@ mod contract {
             self.offers_by_id.get_mut(offer_id).ok_or(Error::OfferNotFound)
         }
 
+        /// Adds `value` to the cumulative volume of `trader`
+        fn add_traded_volume(&mut self, trader: AccountId, value: Balance) {
+            let volume = self.volume_by_trader.entry(trader).or_insert(0);
+            *volume = volume.saturating_add(value);
+        }
+
         /// Increments nonce and returns current
         fn increment_nonce(&mut self) -> u128 {
             let current = *self.nonce;
@@ -354,4 +469,9 @@ mod contract {
             current
         }
     }
+
+    /// The fee of `taker_fee_bps` basis points on `value`, rounded down
+    fn taker_fee(value: Balance, taker_fee_bps: u16) -> Balance {
+        value * Balance::from(taker_fee_bps) / Balance::from(constants::MAX_FEE_BPS)
+    }
 }
@@ -24,6 +24,15 @@ pub enum Error {
     /// An ERC-20 error occurred
     #[error(display = "erc20 error {}", _0)]
     MultiToken(#[source] multi_token::Error),
+    /// Only the owner may perform this operation
+    #[error(display = "Only the owner may perform this operation")]
+    Unauthorized,
+    /// Fee tiers must be ordered by volume and charge at most 100%
+    #[error(display = "Fee tiers must be ordered by volume and charge at most 100%")]
+    InvalidFeeTiers,
+    /// A generated id is already in use
+    #[error(display = "A generated id is already in use")]
+    IdCollision,
 }
 
 /// The Result type for this crate
@@ -62,6 +62,16 @@ pub mod storage {
         Sell,
     }
 
+    /// A taker fee that applies to traders whose cumulative traded volume is at least `min_volume`
+    #[derive(Debug, Encode, Decode, SpreadLayout, PackedLayout, Copy, Clone, Eq, PartialEq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub struct FeeTier {
+        /// The cumulative volume, in offer tokens, from which the tier applies
+        pub min_volume: Balance,
+        /// The fee charged to the taker of an offer, in basis points of the filled value
+        pub taker_fee_bps: u16,
+    }
+
     impl OfferType {
         pub fn as_str(&self) -> &'static str {
             match self {
@@ -34,6 +34,15 @@ pub enum Error {
     /// Only the owner pay perform this operation
     #[error(display = "Only the owner pay perform this operation")]
     RequiresOwner,
+    /// The time lock expires too soon after the contract is created
+    #[error(display = "The time lock expires too soon after the contract is created")]
+    LockTooShort,
+    /// The time lock expires too late after the contract is created
+    #[error(display = "The time lock expires too late after the contract is created")]
+    LockTooLong,
+    /// The minimum lock duration is greater than the maximum
+    #[error(display = "The minimum lock duration is greater than the maximum")]
+    InvalidLockDurationBounds,
     /// An ERC-20 error occurred
     #[error(display = "An Erc20 error occurred: {}", _0)]
     Erc20(#[source] multi_token::Error),
@@ -49,6 +58,9 @@ pub mod contract {
 
     // #[cfg(not(feature = "ink-as-dependency"))]
     use crate::model::{event_output::*, input::*, storage::*};
+    use contract_utils::TokenStandard;
+    use ink_prelude::vec::Vec;
+    use multi_token::UniqueMultiToken;
 
 
     #[ink(storage)]
@@ -59,6 +71,14 @@ pub mod contract {
         owner: AccountId,
         /// A nonce that is used to generate the contract hash
         nonce: ink_storage::lazy::Lazy<u128>,
+        /// The minimum time between creating a contract and its time lock
+        min_lock_duration: ink_storage::lazy::Lazy<u64>,
+        /// The maximum time between creating a contract and its time lock
+        max_lock_duration: ink_storage::lazy::Lazy<u64>,
+        /// The parties that agreed to cancel a contract, by (contract hash, party)
+        cancel_consents: ink_storage::collections::HashMap<(Hash, AccountId), ()>,
+        /// The final status of contracts that were claimed or refunded, as those are deleted
+        terminal_by_hash: ink_storage::collections::HashMap<Hash, HtlcStatus>,
     }
 
     // ======== Events
@@ -95,12 +115,36 @@ pub mod contract {
         pub output: RefundFundsEventOutput,
     }
 
+    /// Sent when both parties agreed to cancel a contract and the funds were returned to the sender
+    #[ink(event)]
+    #[derive(derive_new::new)]
+    pub struct MutualCancelEvent {
+        /// Ouput of the event
+        pub output: MutualCancelEventOutput,
+    }
+
+    /// Sent when the owner of the contract changed
+    #[ink(event)]
+    #[derive(derive_new::new)]
+    pub struct OwnerChanged {
+        /// Ouput of the event
+        pub output: OwnerChangedOutput,
+    }
+
     impl HashTimeLockedContracts {
         /// Creates a new ERC-20 contract with the specified initial supply.
         #[allow(clippy::new_without_default)]
         #[ink(constructor)]
         pub fn new() -> Self {
-            Self { contracts_by_hash: Default::default(), owner: Self::env().caller(), nonce: Default::default() }
+            Self {
+                contracts_by_hash: Default::default(),
+                owner: Self::env().caller(),
+                nonce: Default::default(),
+                min_lock_duration: Default::default(),
+                max_lock_duration: ink_storage::lazy::Lazy::new(u64::MAX),
+                cancel_consents: Default::default(),
+                terminal_by_hash: Default::default(),
+            }
         }
 
         /// Generates a proposal for a new Hash-Time Locked Contract. Returns the unique id generated for the contract.
@@ -137,12 +181,9 @@ pub mod contract {
                 self.env().random(&subject)
             };
 
-            // Check if is expired
+            // Check if is expired or outside of the allowed lock duration
             let timestamp: u64 = self.env().block_timestamp();
-            if contract.time_lock <= timestamp {
-                // ink_env::debug_println(&ink_prelude::format!("timestamp: {}", timestamp));
-                return Err(Error::ContractExpired);
-            }
+            check_time_lock(timestamp, contract.time_lock, *self.min_lock_duration, *self.max_lock_duration)?;
 
             // Transfer funds to contract. Mint them if it's a swap-in.
             if self.caller_is_owner() {
@@ -206,17 +247,17 @@ pub mod contract {
             }
 
             // Mint funds to the claimer. If there is no claimer, burn the tokens.
-            let mut multi_token = contract.token;
             if self.caller_is_owner() {
-                multi_token.burn(contract.amount)?;
+                burn_token(contract.token, contract.amount)?;
             } else {
-                multi_token.transfer(contract.to, contract.amount)?;
+                transfer_token(contract.token, contract.to, contract.amount)?;
             }
 
             let from_or_to_is_owner = self.caller_is_owner() || self.account_is_owner(contract.to);
 
             // Delete HTLC contract on blockchain
             self.contracts_by_hash.take(&claim.contract_hash);
+            self.terminal_by_hash.insert(claim.contract_hash, HtlcStatus::Claimed);
 
             // Send event
             if from_or_to_is_owner {
@@ -254,28 +295,64 @@ pub mod contract {
                 return Err(Error::IncorrectSecret(caller));
             }
 
+            // Make sure the locked funds were not drained in the meantime
+            if !self.holds_locked_funds(contract) {
+                return Err(Error::ContractNotLocked(refund.contract_hash));
+            }
+
             // Refund to claimer if not swap-in. Otherwise, burn the funds
-            let mut multi_token = contract.token;
             if self.caller_is_owner() {
-                multi_token.burn(contract.amount)?;
+                burn_token(contract.token, contract.amount)?;
             } else {
-                multi_token.transfer(caller, contract.amount)?;
+                transfer_token(contract.token, caller, contract.amount)?;
             }
 
-            let to_or_from_is_owner = self.caller_is_owner() || self.account_is_owner(contract.to);
-
             // Delete HTL contract
             self.contracts_by_hash.take(&refund.contract_hash);
+            self.terminal_by_hash.insert(refund.contract_hash, HtlcStatus::Refunded);
 
-            // Send an event in case of swap-in or swap-out
-            if to_or_from_is_owner {
-                self.env().emit_event(RefundFundsEvent::new(RefundFundsEventOutput {
-                    address: caller,
-                    contract_hash: refund.contract_hash,
-                    secret: refund.secret_hash,
-                }));
+            self.env().emit_event(RefundFundsEvent::new(RefundFundsEventOutput {
+                address: caller,
+                contract_hash: refund.contract_hash,
+                secret: refund.secret_hash,
+            }));
+
+            Ok(())
+        }
+
+        /// Cancels the HTLC before its time lock expires. Both `from` and `to` must call this; the first call only
+        /// records the caller's consent. Once both agreed, the funds are returned to `from`, or burned if the
+        /// contract is a swap-in, and the contract is deleted.
+        #[ink(message)]
+        pub fn mutual_cancel(&mut self, contract_hash: Hash) -> Result<()> {
+            let contract =
+                self.contracts_by_hash.get(&contract_hash).ok_or(Error::ContractNotFound(contract_hash))?.clone();
+            let caller = self.env().caller();
+
+            // Validate HTLC has the funds locked
+            if !contract.locked {
+                return Err(Error::ContractNotLocked(contract_hash));
+            }
+
+            if !self.consent_to_cancel(contract_hash, &contract, caller)? {
+                return Ok(());
             }
 
+            // Refund to the sender if not swap-in. Otherwise, burn the funds
+            if self.account_is_owner(contract.from) {
+                burn_token(contract.token, contract.amount)?;
+            } else {
+                transfer_token(contract.token, contract.from, contract.amount)?;
+            }
+
+            // Delete HTL contract
+            self.contracts_by_hash.take(&contract_hash);
+            self.terminal_by_hash.insert(contract_hash, HtlcStatus::Refunded);
+            self.cancel_consents.take(&(contract_hash, contract.from));
+            self.cancel_consents.take(&(contract_hash, contract.to));
+
+            self.env().emit_event(MutualCancelEvent::new(MutualCancelEventOutput { contract_hash }));
+
             Ok(())
         }
 
@@ -291,21 +368,81 @@ pub mod contract {
                 amount: x.amount,
                 time_lock: x.time_lock,
                 locked: x.locked,
-                // unlocked: !x.locked,
-                // rolled_back: false,
             })
         }
 
+        /// Returns information about the HTLCs given the `contract_hashes`. The result has the same order as
+        /// `contract_hashes`, with `None` for contracts that do not exist or were claimed or refunded.
+        #[ink(message)]
+        pub fn get_htlc_infos(&self, contract_hashes: Vec<Hash>) -> Vec<Option<model::output::HTLContractOutput>> {
+            contract_hashes.into_iter().map(|contract_hash| self.get_htlc_info(contract_hash)).collect()
+        }
+
+        /// Returns the status of the HTLC given the `contract_hash`, or `None` if it never existed
+        #[ink(message)]
+        pub fn get_htlc_status(&self, contract_hash: Hash) -> Option<HtlcStatus> {
+            if self.contracts_by_hash.contains_key(&contract_hash) {
+                return Some(HtlcStatus::Locked);
+            }
+            self.terminal_by_hash.get(&contract_hash).copied()
+        }
+
+        /// Returns the sum of the amounts locked in `token` by all HTLCs. Operators can compare it with the balance
+        /// this contract holds of `token` to verify it is solvent.
+        #[ink(message)]
+        pub fn total_locked(&self, token: AccountId) -> Balance {
+            total_locked_in(self.contracts_by_hash.values(), token)
+        }
+
         /// Set the owner. May only be done by the current owner.
         #[ink(message)]
         pub fn set_owner(&mut self, owner: AccountId) -> Result<()> {
             if !self.caller_is_owner() {
                 return Err(Error::RequiresOwner);
             }
-            self.owner = owner;
+            let previous = core::mem::replace(&mut self.owner, owner);
+            self.env().emit_event(OwnerChanged::new(OwnerChangedOutput { previous, new: owner }));
             Ok(())
         }
 
+        /// Returns the owner, who may mint swapped-in tokens and configure the contract
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId { self.owner }
+
+        /// Set the minimum and maximum time between creating a contract and its time lock. May only be done by the
+        /// owner.
+        #[ink(message)]
+        pub fn set_lock_duration_bounds(&mut self, min: u64, max: u64) -> Result<()> {
+            if !self.caller_is_owner() {
+                return Err(Error::RequiresOwner);
+            }
+            if min > max {
+                return Err(Error::InvalidLockDurationBounds);
+            }
+            *self.min_lock_duration = min;
+            *self.max_lock_duration = max;
+            Ok(())
+        }
+
+        /// Returns the minimum and maximum time between creating a contract and its time lock
+        #[ink(message)]
+        pub fn lock_duration_bounds(&self) -> (u64, u64) { (*self.min_lock_duration, *self.max_lock_duration) }
+
+        /// Records that `caller` agrees to cancel `contract`. Returns true if both parties have agreed.
+        fn consent_to_cancel(
+            &mut self,
+            contract_hash: Hash,
+            contract: &HTLContract,
+            caller: AccountId,
+        ) -> Result<bool> {
+            if caller != contract.from && caller != contract.to {
+                return Err(Error::InvalidClaimer(caller));
+            }
+            self.cancel_consents.insert((contract_hash, caller), ());
+            Ok(self.cancel_consents.contains_key(&(contract_hash, contract.from))
+                && self.cancel_consents.contains_key(&(contract_hash, contract.to)))
+        }
+
         /// Returns a unique number
         fn increment_nonce(&mut self) -> u128 {
             let value = *self.nonce;
@@ -318,6 +455,11 @@ pub mod contract {
             Self::env().hash_bytes::<ink_env::hash::Keccak256>(secret.as_ref())
         }
 
+        /// True if this contract still holds the funds locked by `contract`
+        fn holds_locked_funds(&self, contract: &HTLContract) -> bool {
+            holds_funds(contract.token, self.env().account_id(), contract.amount)
+        }
+
         /// True if the caller is the owner
         fn caller_is_owner(&self) -> bool { self.owner == self.env().caller() }
 
@@ -325,11 +467,146 @@ pub mod contract {
         fn account_is_owner(&self, account: AccountId) -> bool { self.owner == account }
     }
 
+    /// Transfers `amount` of `token` from this contract to `to`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn transfer_token(mut token: UniqueMultiToken, to: AccountId, amount: Balance) -> multi_token::Result<()> {
+        token.transfer(to, amount)
+    }
+
+    /// Burns `amount` of `token` held by this contract
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn burn_token(mut token: UniqueMultiToken, amount: Balance) -> multi_token::Result<()> { token.burn(amount) }
+
+    /// True if `account` holds `amount` of `token`, or owns it for ERC-721
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn holds_funds(token: UniqueMultiToken, account: AccountId, amount: Balance) -> bool {
+        let UniqueMultiToken { multi_token, token_id } = token;
+        match multi_token.standard {
+            TokenStandard::Erc20 => multi_token.as_erc20_unchecked().balance_of(account) >= amount,
+            TokenStandard::Erc721 => {
+                token_id.and_then(|id| multi_token.as_erc721_unchecked().owner_of(id)) == Some(account)
+            }
+            TokenStandard::Erc1155 => {
+                token_id.map_or(false, |id| multi_token.as_erc1155_unchecked().balance_of(account, id) >= amount)
+            }
+        }
+    }
+
+    #[cfg(test)]
+    use tests::mock_token::{burn as burn_token, holds_funds, transfer as transfer_token};
+
+    /// Sums the amounts locked in `token` by `contracts`
+    fn total_locked_in<'a>(contracts: impl Iterator<Item = &'a HTLContract>, token: AccountId) -> Balance {
+        contracts
+            .filter(|contract| contract.locked && contract.token.multi_token.account_id == token)
+            .map(|contract| contract.amount)
+            .sum()
+    }
+
+    /// Ensures `time_lock` has not expired at `now` and is between `min` and `max` from `now`
+    fn check_time_lock(now: u64, time_lock: u64, min: u64, max: u64) -> Result<()> {
+        if time_lock <= now {
+            return Err(Error::ContractExpired);
+        }
+        let duration = time_lock - now;
+        if duration < min {
+            return Err(Error::LockTooShort);
+        }
+        if duration > max {
+            return Err(Error::LockTooLong);
+        }
+        Ok(())
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
+        use contract_utils::test_utils;
         use hex_literal::hex;
 
+        type Event = <HashTimeLockedContracts as ::ink_lang::BaseEvent>::Type;
+
+        /// In-memory ERC20 balances, as token contracts can't be called off-chain
+        pub(super) mod mock_token {
+            use super::*;
+            use std::{cell::RefCell, collections::BTreeMap};
+
+            thread_local! {
+                static BALANCES: RefCell<BTreeMap<(AccountId, AccountId), Balance>> = RefCell::new(BTreeMap::new());
+            }
+
+            pub fn balance_of(token: AccountId, account: AccountId) -> Balance {
+                BALANCES.with(|x| x.borrow().get(&(token, account)).copied().unwrap_or_default())
+            }
+
+            pub fn set_balance(token: AccountId, account: AccountId, balance: Balance) {
+                BALANCES.with(|x| x.borrow_mut().insert((token, account), balance));
+            }
+
+            pub fn holds_funds(token: UniqueMultiToken, account: AccountId, amount: Balance) -> bool {
+                assert_eq!(TokenStandard::Erc20, token.multi_token.standard);
+                balance_of(token.multi_token.account_id, account) >= amount
+            }
+
+            pub fn transfer(token: UniqueMultiToken, to: AccountId, amount: Balance) -> multi_token::Result<()> {
+                let (token, contract) = (token.multi_token.account_id, ink_env::account_id::<Environment>().unwrap());
+                let balance =
+                    balance_of(token, contract).checked_sub(amount).expect("the contract holds too few tokens");
+                set_balance(token, contract, balance);
+                set_balance(token, to, balance_of(token, to) + amount);
+                Ok(())
+            }
+
+            pub fn burn(token: UniqueMultiToken, amount: Balance) -> multi_token::Result<()> {
+                let (token, contract) = (token.multi_token.account_id, ink_env::account_id::<Environment>().unwrap());
+                let balance =
+                    balance_of(token, contract).checked_sub(amount).expect("the contract holds too few tokens");
+                set_balance(token, contract, balance);
+                Ok(())
+            }
+        }
+
+        fn token() -> AccountId { AccountId::from([1; 32]) }
+
+        fn contract() -> AccountId { ink_env::account_id::<Environment>().unwrap() }
+
+        /// Stores a locked HTLC of 100 tokens from `from` to `to` and returns its hash
+        fn store_htlc(htlc: &mut HashTimeLockedContracts, from: AccountId, to: AccountId) -> Hash {
+            let contract_hash = Hash::from([7; 32]);
+            htlc.contracts_by_hash.insert(contract_hash, HTLContract {
+                secret_hash: Hash::from([9; 32]),
+                from,
+                to,
+                token: UniqueMultiToken {
+                    multi_token: multi_token::MultiToken::new(token(), TokenStandard::Erc20),
+                    token_id: None,
+                },
+                amount: 100,
+                time_lock: 0,
+                locked: true,
+            });
+            contract_hash
+        }
+
+        fn refund(htlc: &mut HashTimeLockedContracts, caller: AccountId, contract_hash: Hash) -> Result<()> {
+            test_utils::set_caller(caller);
+            let result = htlc.refund_funds(RefundRequest { contract_hash, secret_hash: Hash::from([9; 32]) });
+            ink_env::test::pop_execution_context();
+            result
+        }
+
+        fn assert_refund_event(address: AccountId, contract_hash: Hash) {
+            let event = ink_env::test::recorded_events().last().unwrap();
+            match <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap() {
+                Event::RefundFundsEvent(RefundFundsEvent { output }) => {
+                    assert_eq!(address, output.address);
+                    assert_eq!(contract_hash, output.contract_hash);
+                    assert_eq!(Hash::from([9; 32]), output.secret);
+                }
+                _ => panic!("expected a RefundFundsEvent"),
+            }
+        }
+
         #[test]
         fn test_secret_hash() {
             assert_eq!(
@@ -339,5 +616,138 @@ pub mod contract {
                 hex!("4c9bf8fc46df3e252c8eaf0d450d7bf95c56f4d6284a3c89af37154dc2660a39")
             )
         }
+
+        #[test]
+        fn test_check_time_lock() {
+            // expired
+            assert_eq!(check_time_lock(100, 100, 0, u64::MAX), Err(Error::ContractExpired));
+
+            // boundaries are inclusive
+            assert_eq!(check_time_lock(100, 109, 10, 20), Err(Error::LockTooShort));
+            assert_eq!(check_time_lock(100, 110, 10, 20), Ok(()));
+            assert_eq!(check_time_lock(100, 120, 10, 20), Ok(()));
+            assert_eq!(check_time_lock(100, 121, 10, 20), Err(Error::LockTooLong));
+
+            // within range
+            assert_eq!(check_time_lock(100, 115, 10, 20), Ok(()));
+        }
+
+        #[ink_lang::test]
+        fn test_consent_to_cancel() {
+            let mut htlc = HashTimeLockedContracts::new();
+            let (from, to, eve) = (AccountId::from([3; 32]), AccountId::from([4; 32]), AccountId::from([5; 32]));
+            let contract = HTLContract {
+                secret_hash: Default::default(),
+                from,
+                to,
+                token: UniqueMultiToken {
+                    multi_token: multi_token::MultiToken::new(AccountId::from([1; 32]), TokenStandard::Erc20),
+                    token_id: None,
+                },
+                amount: 100,
+                time_lock: 0,
+                locked: true,
+            };
+            let contract_hash = Hash::from([7; 32]);
+
+            // only the parties may consent
+            assert_eq!(htlc.consent_to_cancel(contract_hash, &contract, eve), Err(Error::InvalidClaimer(eve)));
+
+            // a single consent leaves the cancellation pending, also when repeated
+            assert_eq!(htlc.consent_to_cancel(contract_hash, &contract, from), Ok(false));
+            assert_eq!(htlc.consent_to_cancel(contract_hash, &contract, from), Ok(false));
+
+            // both consents cancel the contract
+            assert_eq!(htlc.consent_to_cancel(contract_hash, &contract, to), Ok(true));
+        }
+
+        #[ink_lang::test]
+        fn test_owner() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>().unwrap();
+            let mut htlc = HashTimeLockedContracts::new();
+            assert_eq!(htlc.owner(), accounts.alice);
+
+            htlc.set_owner(accounts.bob).unwrap();
+            assert_eq!(htlc.owner(), accounts.bob);
+            assert_eq!(ink_env::test::recorded_events().count(), 1);
+
+            // alice is no longer the owner
+            assert_eq!(htlc.set_owner(accounts.alice), Err(Error::RequiresOwner));
+            assert_eq!(htlc.owner(), accounts.bob);
+        }
+
+        #[ink_lang::test]
+        fn test_refund_funds() {
+            let accounts = test_utils::default_accounts();
+            let mut htlc = HashTimeLockedContracts::new();
+            let contract_hash = store_htlc(&mut htlc, accounts.bob, accounts.charlie);
+            mock_token::set_balance(token(), contract(), 100);
+
+            // only the sender may refund
+            assert_eq!(
+                refund(&mut htlc, accounts.charlie, contract_hash),
+                Err(Error::InvalidClaimer(accounts.charlie))
+            );
+
+            assert_eq!(refund(&mut htlc, accounts.bob, contract_hash), Ok(()));
+            assert_eq!(mock_token::balance_of(token(), accounts.bob), 100);
+            assert_eq!(mock_token::balance_of(token(), contract()), 0);
+            assert_eq!(htlc.get_htlc_status(contract_hash), Some(HtlcStatus::Refunded));
+            // the event is emitted even though neither party is the owner
+            assert_refund_event(accounts.bob, contract_hash);
+        }
+
+        #[ink_lang::test]
+        fn test_refund_funds_burns_swap_in() {
+            let accounts = test_utils::default_accounts();
+            let mut htlc = HashTimeLockedContracts::new();
+            let contract_hash = store_htlc(&mut htlc, accounts.alice, accounts.bob);
+            mock_token::set_balance(token(), contract(), 100);
+
+            assert_eq!(refund(&mut htlc, accounts.alice, contract_hash), Ok(()));
+            assert_eq!(mock_token::balance_of(token(), accounts.alice), 0);
+            assert_eq!(mock_token::balance_of(token(), contract()), 0);
+            assert_refund_event(accounts.alice, contract_hash);
+        }
+
+        #[ink_lang::test]
+        fn test_refund_funds_requires_funds() {
+            let accounts = test_utils::default_accounts();
+            let mut htlc = HashTimeLockedContracts::new();
+            let contract_hash = store_htlc(&mut htlc, accounts.bob, accounts.charlie);
+            mock_token::set_balance(token(), contract(), 99);
+
+            assert_eq!(refund(&mut htlc, accounts.bob, contract_hash), Err(Error::ContractNotLocked(contract_hash)));
+            assert_eq!(mock_token::balance_of(token(), accounts.bob), 0);
+            assert_eq!(htlc.get_htlc_status(contract_hash), Some(HtlcStatus::Locked));
+            assert_eq!(ink_env::test::recorded_events().count(), 0);
+        }
+
+        #[test]
+        fn test_total_locked() {
+            let (token, other_token) = (AccountId::from([1; 32]), AccountId::from([2; 32]));
+            let contract = |token, amount, locked| HTLContract {
+                secret_hash: Default::default(),
+                from: AccountId::from([3; 32]),
+                to: AccountId::from([4; 32]),
+                token: UniqueMultiToken {
+                    multi_token: multi_token::MultiToken::new(token, TokenStandard::Erc20),
+                    token_id: None,
+                },
+                amount,
+                time_lock: 0,
+                locked,
+            };
+            let contracts = vec![
+                contract(token, 100, true),
+                contract(token, 250, true),
+                contract(other_token, 1_000, true),
+                contract(token, 50, false),
+            ];
+
+            assert_eq!(total_locked_in(contracts.iter(), token), 350);
+            assert_eq!(total_locked_in(contracts.iter(), other_token), 1_000);
+            assert_eq!(total_locked_in(contracts.iter(), AccountId::from([5; 32])), 0);
+        }
     }
 }
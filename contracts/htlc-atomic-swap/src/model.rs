@@ -25,6 +25,18 @@ pub mod storage {
         /// If the contract is locked
         pub locked: bool,
     }
+
+    /// Where an HTLC is in its lifecycle
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+    pub enum HtlcStatus {
+        /// The funds are locked until they are claimed or refunded
+        Locked,
+        /// The receiver claimed the funds with the secret
+        Claimed,
+        /// The funds were returned to the sender, after the time lock expired or by mutual cancellation
+        Refunded,
+    }
 }
 
 pub mod input {
@@ -138,4 +150,22 @@ pub mod event_output {
         /// Secret of the HTLC
         pub secret: Hash,
     }
+
+    /// Sent when both parties agreed to cancel a contract
+    #[derive(Debug, Encode, Decode, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct MutualCancelEventOutput {
+        /// The hash of the contract that was cancelled
+        pub contract_hash: Hash,
+    }
+
+    /// Sent when the owner of the contract changed
+    #[derive(Debug, Encode, Decode, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct OwnerChangedOutput {
+        /// The previous owner
+        pub previous: AccountId,
+        /// The new owner
+        pub new: AccountId,
+    }
 }
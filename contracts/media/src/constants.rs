@@ -1,6 +1,15 @@
-/// The number of parents to search in get_sharing_proportions function
-pub const GET_SHARING_PROPORTIONS_DEPTH: usize = 3;
+/// The maximum number of parents a media may search in get_sharing_proportions function
+pub const MAX_SHARING_PROPORTIONS_DEPTH: u32 = 8;
 /// The duration an `UpdateMediaProposal` will remain valid
 pub const UPDATE_MEDIA_PROPOSAL_DURATION: u64 = contract_utils::time::WEEK;
+/// The maximum amount of time an `UpdateMediaProposal` can be extended by
+pub const MAX_PROPOSAL_EXTENSION: u64 = contract_utils::time::WEEK;
 /// The total number of shares a collab can have for a media
 pub const COLLAB_SHARE_COUNT: u128 = 1_000_000_000;
+/// The maximum number of medias returned by a single `get_medias` call
+pub const MAX_GET_MEDIAS_COUNT: usize = 50;
+/// Selector of the `on_media_created(media_id: MediaId, creator: AccountId)` message the observer must implement,
+/// e.g. with `#[ink(message, selector = "0x6D656469")]`
+pub const ON_MEDIA_CREATED_SELECTOR: [u8; 4] = [0x6D, 0x65, 0x64, 0x69];
+/// Basis points in 100%, the maximum protocol fee
+pub const MAX_BPS: u16 = 10_000;
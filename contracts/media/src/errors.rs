@@ -28,6 +28,9 @@ pub enum Error {
     /// The media sharing parent does not exist
     #[error(display = "The media sharing parent does not exist")]
     MediaSharingParentNotFound,
+    /// The sharing depth exceeds the maximum
+    #[error(display = "The sharing depth exceeds the maximum")]
+    SharingDepthOutOfRange,
     /// The media sharing does not exist
     #[error(display = "The media sharing does not exist")]
     MediaSharingNotFound,
@@ -46,6 +49,27 @@ pub enum Error {
     /// The balance is insufficient
     #[error(display = "The balance is insufficient")]
     InsufficientBalance,
+    /// The media type can only be changed to an incompatible type through an update proposal
+    #[error(display = "The media type can only be changed to an incompatible type through an update proposal")]
+    IncompatibleMediaType,
+    /// The protocol fee may not exceed 100%
+    #[error(display = "The protocol fee may not exceed 100%")]
+    InvalidProtocolFee,
+    /// The media is not registered, not uploaded or not released yet
+    #[error(display = "The media is not registered, not uploaded or not released yet")]
+    MediaNotAvailable,
+    /// Only the requester of the proposal may extend it
+    #[error(display = "Only the requester of the proposal may extend it")]
+    RequesterRequired,
+    /// The proposal has already been extended
+    #[error(display = "The proposal has already been extended")]
+    ProposalAlreadyExtended,
+    /// The proposal has expired
+    #[error(display = "The proposal has expired")]
+    ProposalExpired,
+    /// The extension must be positive and at most `constants::MAX_PROPOSAL_EXTENSION`
+    #[error(display = "The extension must be positive and at most the maximum proposal extension")]
+    InvalidProposalExtension,
     /// An ERC-1620 error occurred
     #[error(display = "An Erc1620 error occurred: {}", _0)]
     Erc1620(#[source] erc1620::Error),
@@ -59,4 +83,28 @@ pub enum Error {
     /// Message is only callable by the media's pod address.
     #[error(display = "only callable by the pod address contract")]
     PodAddressRequired,
+    /// Message is only callable by the media's pod address or creator.
+    #[error(display = "only callable by the pod address contract or the creator")]
+    PodOrCreatorRequired,
+}
+
+/// The reasons the release of a media can't be rescheduled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, err_derive::Error)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum RescheduleReleaseError {
+    /// The new release date is in the past
+    #[error(display = "The new release date must be in the future")]
+    ReleaseDateMustBeInFuture,
+    /// Only the creator may reschedule the release
+    #[error(display = "Only the creator may reschedule the release")]
+    Unauthorized,
+    /// The media is not registered
+    #[error(display = "The media is not registered")]
+    MediaNotRegistered,
+    /// The media has already been released
+    #[error(display = "The media has already been released")]
+    MediaAlreadyReleased,
+    /// The new release date is not later than the current one
+    #[error(display = "The release can only be postponed")]
+    ReleaseCanOnlyBePostponed,
 }
@@ -19,11 +19,9 @@ mod media {
 
     cfg_if::cfg_if! {
         if #[cfg(not(feature = "ink-as-dependency"))] {
-            use ink_env::call::FromAccountId;
             use core::convert::{TryFrom, TryInto};
-            use erc20::Erc20;
             use ink_prelude::{vec::Vec};
-            use ink_storage::{Lazy};
+            use ink_storage::{collections::Vec as StorageVec, Lazy};
         }
     }
 
@@ -32,6 +30,14 @@ mod media {
     pub struct MediaStorage {
         /// Owner of the contract
         owner: AccountId,
+        /// Contract that is notified through `on_media_created` when a media is created
+        observer: Lazy<Option<AccountId>>,
+        /// The share of every tip, in basis points, that is taken as a protocol fee
+        protocol_fee_bps: Lazy<u16>,
+        /// The account that receives the protocol fee
+        fee_recipient: Lazy<AccountId>,
+        /// The protocol fees held by the contract that have not been withdrawn, by ERC-20 token
+        protocol_fees: HashMap<AccountId, Balance>,
         /// The next `SharingId` to use when a media is shared
         next_sharing_id: Lazy<SharingId>,
 
@@ -43,6 +49,8 @@ mod media {
         // media
         /// Medias by media id
         medias_by_id: HashMap<MediaId, Media>,
+        /// The ids of all medias, in creation order
+        media_ids: StorageVec<MediaId>,
         /// Collaborators by media id
         collaborators_by_media_id: HashMap<MediaId, BTreeMap<AccountId, CollabShare>>,
         /// The sharings by id
@@ -77,6 +85,30 @@ mod media {
         pub output: SharedMediaOutput,
     }
 
+    /// Emitted when a media is tipped
+    #[ink(event)]
+    #[derive(derive_new::new)]
+    pub struct MediaTipped {
+        /// Ouput of the event
+        pub output: TippedMediaOutput,
+    }
+
+    /// Emitted when a media is taken down
+    #[ink(event)]
+    #[derive(derive_new::new)]
+    pub struct MediaTakenDown {
+        /// Ouput of the event
+        pub output: TakenDownMediaOutput,
+    }
+
+    /// Emitted when the owner withdraws protocol fees
+    #[ink(event)]
+    #[derive(derive_new::new)]
+    pub struct ProtocolFeesWithdrawn {
+        /// Ouput of the event
+        pub output: WithdrawnProtocolFeesOutput,
+    }
+
     impl MediaStorage {
         /// Create a new contract.
         #[allow(clippy::new_without_default)]
@@ -85,10 +117,15 @@ mod media {
             let contract_owner = Self::env().caller();
             Self {
                 owner: contract_owner,
+                observer: Default::default(),
+                protocol_fee_bps: Default::default(),
+                fee_recipient: Lazy::new(contract_owner),
+                protocol_fees: Default::default(),
                 next_sharing_id: Default::default(),
                 erc1620: erc1620_account_id,
                 erc721: erc721_account_id,
                 medias_by_id: Default::default(),
+                media_ids: Default::default(),
                 collaborators_by_media_id: Default::default(),
                 proposals_by_key: Default::default(),
                 communities_by_proposal_key: Default::default(),
@@ -110,9 +147,14 @@ mod media {
         #[ink(message)]
         pub fn create_media(&mut self, input: CreateMediaRequest) -> Result<MediaId> {
             let caller = self.env().caller();
+            let creator = input.creator_address;
+
+            if input.view_conditions.sharing_depth > constants::MAX_SHARING_PROPORTIONS_DEPTH {
+                return Err(Error::SharingDepthOutOfRange);
+            }
 
-            // mint nft token
-            let media_id = self.erc721.mint(caller)?;
+            // mint nft token, with the media's royalty if the NFT contract supports royalties
+            let media_id = mint_media_nft(&mut self.erc721, caller, creator, input.royalty)?;
 
             // update storage
             self.medias_by_id.insert(media_id, Media {
@@ -128,6 +170,7 @@ mod media {
                 is_uploaded: false,
                 royalty: input.royalty,
             });
+            self.media_ids.push(media_id);
 
             if let Some(collabs) = input.collabs {
                 self.collaborators_by_media_id.insert(media_id, collabs);
@@ -137,9 +180,70 @@ mod media {
 
             self.env().emit_event(CreatedMedia::new(CreatedMediaOutput { media_id }));
 
+            // a failing observer must not prevent media from being created
+            if let Some(observer) = *self.observer {
+                let _ = notify_media_created(observer, media_id, creator);
+            }
+
             Ok(media_id)
         }
 
+        /// Sets the contract that is notified when a media is created, or removes it with `None`. The observer must
+        /// implement a message with `constants::ON_MEDIA_CREATED_SELECTOR`. Only callable by the owner.
+        #[ink(message)]
+        pub fn set_observer(&mut self, observer: Option<AccountId>) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OwnerRequired);
+            }
+            Lazy::set(&mut self.observer, observer);
+            Ok(())
+        }
+
+        /// Returns the contract that is notified when a media is created
+        #[ink(message)]
+        pub fn observer(&self) -> Option<AccountId> { *self.observer }
+
+        /// Sets the protocol fee that is deducted from every tip, in basis points, and the account that receives it.
+        /// If the recipient is the media contract itself, the fees accrue in the contract until the owner withdraws
+        /// them with `withdraw_protocol_fees`. Only callable by the owner.
+        #[ink(message)]
+        pub fn set_protocol_fee(&mut self, protocol_fee_bps: u16, fee_recipient: AccountId) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OwnerRequired);
+            }
+            if protocol_fee_bps > constants::MAX_BPS {
+                return Err(Error::InvalidProtocolFee);
+            }
+            Lazy::set(&mut self.protocol_fee_bps, protocol_fee_bps);
+            Lazy::set(&mut self.fee_recipient, fee_recipient);
+            Ok(())
+        }
+
+        /// Returns the protocol fee in basis points and the account that receives it
+        #[ink(message)]
+        pub fn protocol_fee(&self) -> (u16, AccountId) { (*self.protocol_fee_bps, *self.fee_recipient) }
+
+        /// Returns the protocol fees in `token` that can be withdrawn
+        #[ink(message)]
+        pub fn accrued_protocol_fees(&self, token: AccountId) -> Balance {
+            self.protocol_fees.get(&token).copied().unwrap_or_default()
+        }
+
+        /// Transfers the protocol fees accrued in `token` to `to` and returns the amount. Only the fees are
+        /// withdrawn, never other funds the contract holds in `token`. Only callable by the owner.
+        #[ink(message)]
+        pub fn withdraw_protocol_fees(&mut self, token: AccountId, to: AccountId) -> Result<Balance> {
+            if self.env().caller() != self.owner {
+                return Err(Error::OwnerRequired);
+            }
+            let amount = self.protocol_fees.take(&token).unwrap_or_default();
+            if amount > 0 {
+                erc20_transfer(token, to, amount)?;
+            }
+            self.env().emit_event(ProtocolFeesWithdrawn::new(WithdrawnProtocolFeesOutput { token, to, amount }));
+            Ok(amount)
+        }
+
         /// Gets the media from the `media_id`
         #[ink(message)]
         pub fn get_media(&self, id: MediaId) -> Option<MediaInfo> {
@@ -161,6 +265,32 @@ mod media {
             })
         }
 
+        /// Gets the collaborators of the media with their shares, or `None` if the media does not exist
+        #[ink(message)]
+        pub fn get_collaborators(&self, media_id: MediaId) -> Option<BTreeMap<AccountId, CollabShare>> {
+            self.collaborators_by_media_id.get(&media_id).cloned()
+        }
+
+        /// Gets the medias for `ids`. The result has the same order as `ids`, with `None` for medias that do not
+        /// exist. Only the first `constants::MAX_GET_MEDIAS_COUNT` ids are looked up.
+        #[ink(message)]
+        pub fn get_medias(&self, ids: Vec<MediaId>) -> Vec<Option<MediaInfo>> {
+            ids.into_iter().take(constants::MAX_GET_MEDIAS_COUNT).map(|id| self.get_media(id)).collect()
+        }
+
+        /// Returns the number of medias that have been created
+        #[ink(message)]
+        pub fn media_count(&self) -> u64 { u64::from(self.media_ids.len()) }
+
+        /// Returns up to `limit` media ids in creation order, starting at the `start`th media. At most
+        /// `constants::MAX_GET_MEDIAS_COUNT` ids are returned.
+        #[ink(message)]
+        pub fn media_ids_paged(&self, start: u64, limit: u64) -> Vec<MediaId> {
+            let limit = limit.min(constants::MAX_GET_MEDIAS_COUNT as u64);
+            let end = start.saturating_add(limit).min(self.media_count());
+            (start..end).filter_map(|index| self.media_ids.get(index as u32).copied()).collect()
+        }
+
         /// Creates a proposal to update a `Media`
         /// ### Arguments
         /// See arguments for `create_media`
@@ -176,6 +306,10 @@ mod media {
                 return Err(Error::RequiresCollaborator);
             }
 
+            if request.view_conditions.sharing_depth > constants::MAX_SHARING_PROPORTIONS_DEPTH {
+                return Err(Error::SharingDepthOutOfRange);
+            }
+
             let media = self.medias_by_id.get(&request.media_id).ok_or(Error::MediaNotFound)?;
 
             // store the proposal
@@ -190,11 +324,36 @@ mod media {
                 max_denials: 1,
                 duration: constants::UPDATE_MEDIA_PROPOSAL_DURATION,
                 date: self.env().block_timestamp(),
+                extension: 0,
             });
             self.communities_by_proposal_key.insert(key, collaborators.iter().map(|(k, _)| (*k, ())).collect());
             Ok(())
         }
 
+        /// Extends the deadline of an `UpdateMediaProposal` by `extra`, e.g. when turnout is low. Only callable once by
+        /// the requester of an unexpired proposal, and by at most `constants::MAX_PROPOSAL_EXTENSION`.
+        #[ink(message)]
+        pub fn extend_proposal(&mut self, media_id: MediaId, requester: AccountId, extra: Timestamp) -> Result<()> {
+            if self.env().caller() != requester {
+                return Err(Error::RequesterRequired);
+            }
+            if extra == 0 || extra > constants::MAX_PROPOSAL_EXTENSION {
+                return Err(Error::InvalidProposalExtension);
+            }
+
+            let now = self.env().block_timestamp();
+            let key = ProposalKey { media_id, requester };
+            let proposal = self.proposals_by_key.get_mut(&key).ok_or(Error::ProposalNotFound)?;
+            if proposal.extension > 0 {
+                return Err(Error::ProposalAlreadyExtended);
+            }
+            if proposal.is_expired(now) {
+                return Err(Error::ProposalExpired);
+            }
+            proposal.extension = extra;
+            Ok(())
+        }
+
         /// Vote on an `UpdateMediaProposal`
         /// ### Arguments
         /// * media_id - the id of the media
@@ -261,7 +420,9 @@ mod media {
             Ok(())
         }
 
-        /// Replaces the data for the Media. Only callable by the medias `pod_address`.
+        /// Replaces the data for the Media. Only callable by the medias `pod_address`. The media type may only be
+        /// changed to a compatible type (see `MediaType::is_compatible_with`), other changes require the vote of an
+        /// update proposal.
         #[ink(message)]
         pub fn update_media(&mut self, media: Media) -> Result<Media> {
             let caller = self.env().caller();
@@ -270,6 +431,9 @@ mod media {
             if stored.pod_address != caller {
                 return Err(Error::PodAddressRequired);
             }
+            if !stored.r#type.is_compatible_with(&media.r#type) {
+                return Err(Error::IncompatibleMediaType);
+            }
             // since we already checked if the media exists, this unwrap will not panic.
             Ok(self.medias_by_id.insert(media.id, media).unwrap())
         }
@@ -294,15 +458,20 @@ mod media {
         #[ink(message)]
         pub fn open_media(&mut self, request: OpenMediaRequest) -> Result<()> {
             let media = self.medias_by_id.get(&request.media_id).ok_or(Error::MediaNotFound)?;
+            if !media.is_available(self.env().block_timestamp()) {
+                return Err(Error::MediaNotAvailable);
+            }
             let caller = self.env().caller();
 
             // get total payment
             let mut payment_amount = media.view_conditions.price;
 
-            // NOTE: this code looks suspicious. Why does payment become zero if a balance exists?
             // check if user accomplish entry token conditions
             for (token_account, requested) in &media.view_conditions.token_entry {
-                if Erc20::from_account_id(*token_account).balance_of(caller) >= *requested {
+                if erc20_balance_of(*token_account, caller) >= *requested {
+                    if media.view_conditions.entry_mode == EntryMode::SpendToEnter {
+                        erc20_transfer_from(*token_account, caller, media.pod_address, *requested)?;
+                    }
                     payment_amount = 0;
                     break;
                 }
@@ -310,16 +479,16 @@ mod media {
 
             // if payment is needed
             if payment_amount > 0 {
-                let mut viewing_token = Erc20::from_account_id(media.view_conditions.viewing_token);
+                let viewing_token = media.view_conditions.viewing_token;
 
                 // get the account that will be used to pay
                 let (payment_account, balance) = {
                     let reward_account = contract_utils::get_reward_account_id(self.env(), caller);
-                    let balance = viewing_token.balance_of(reward_account);
+                    let balance = erc20_balance_of(viewing_token, reward_account);
                     if balance >= payment_amount {
                         (reward_account, balance)
                     } else {
-                        (caller, viewing_token.balance_of(caller))
+                        (caller, erc20_balance_of(viewing_token, caller))
                     }
                 };
 
@@ -331,12 +500,7 @@ mod media {
                 // calculate sharing fees
                 let (shared, mut payments) = {
                     if let Some(sharing_id) = request.sharing_id {
-                        self.get_sharing_proportions(
-                            &media.view_conditions,
-                            payment_amount,
-                            sharing_id,
-                            constants::GET_SHARING_PROPORTIONS_DEPTH,
-                        )
+                        self.get_sharing_proportions(&media.view_conditions, payment_amount, sharing_id)
                     } else {
                         (0, HashMap::new())
                     }
@@ -345,10 +509,10 @@ mod media {
                 // calculate royalty fees
                 let collabs =
                     self.collaborators_by_media_id.get(&request.media_id).ok_or(Error::CollaboratorsNotFound)?;
-                let fee = utils::get_royalties(payment_amount, media.royalty, collabs, &mut payments);
+                let fee = utils::get_royalties(payment_amount, media.royalty, collabs, &mut payments)?;
 
                 // calculate owners profit
-                utils::get_owners_profit(payment_amount - shared - fee, collabs, &mut payments);
+                utils::get_owners_profit(payment_amount - shared - fee, collabs, &mut payments)?;
 
                 // make sure caller does not pay self
                 payments.take(&caller);
@@ -358,27 +522,29 @@ mod media {
                     ViewingType::Dynamic => {
                         // create a stream for each receiver
                         let now = self.env().block_timestamp();
+                        let mut stream_ids = Vec::new();
                         for (receiver, balance) in payments.into_iter() {
-                            self.erc1620.create_stream(
+                            stream_ids.push(erc1620_create_stream(
+                                &mut self.erc1620,
                                 *receiver,
                                 *balance,
-                                media.view_conditions.viewing_token,
+                                viewing_token,
                                 now,
                                 now + media.view_conditions.duration,
-                            )?;
+                            )?);
                         }
+                        self.streams_by_media_id.entry(request.media_id).or_insert_with(Vec::new).extend(stream_ids);
                     }
                     // make the transfers immediately
                     ViewingType::Fixed => {
                         for (receiver, balance) in payments.iter() {
-                            viewing_token.transfer_from(payment_account, *receiver, *balance)?;
+                            erc20_transfer_from(viewing_token, payment_account, *receiver, *balance)?;
                         }
                     }
                 }
             }
 
-            // send token rewards
-            todo!("send reward to pod-media")
+            // TODO: send token rewards, which requires the pod to approve the media contract
             // for (token_account, reward) in &media.view_conditions.token_reward {
             //     let token = Erc20::from_account_id(*token_account);
             //     if token.balance_of(media.pod_address) >= reward {
@@ -387,7 +553,7 @@ mod media {
             //     // seems weird it does nothing if the balance is not enough to send the reward
             // }
 
-            // Ok(())
+            Ok(())
         }
 
         /// Stop the streams used by `media_id` if they exist
@@ -395,13 +561,40 @@ mod media {
         pub fn close_media(&mut self, media_id: MediaId) -> Result<()> {
             if let Some(stream_ids) = self.streams_by_media_id.take(&media_id) {
                 for stream_id in stream_ids {
-                    self.erc1620.cancel_stream(stream_id)?;
+                    erc1620_cancel_stream(&mut self.erc1620, stream_id)?;
                 }
             }
 
             Ok(())
         }
 
+        /// Takes down a media, e.g. for a copyright claim. Every open payment stream is cancelled, which refunds the
+        /// viewers for the time that has not been streamed yet, and the media is marked as not uploaded so it can't
+        /// be opened again until it is re-uploaded. Only callable by the media's pod address or creator.
+        #[ink(message)]
+        pub fn takedown_media(&mut self, media_id: MediaId) -> Result<()> {
+            let caller = self.env().caller();
+            let media = self.medias_by_id.get_mut(&media_id).ok_or(Error::MediaNotFound)?;
+            if caller != media.pod_address && caller != media.creator {
+                return Err(Error::PodOrCreatorRequired);
+            }
+            media.is_uploaded = false;
+
+            let cancelled_streams = self.streams_by_media_id.take(&media_id).unwrap_or_default();
+            for stream_id in &cancelled_streams {
+                erc1620_cancel_stream(&mut self.erc1620, *stream_id)?;
+            }
+
+            self.env().emit_event(MediaTakenDown::new(TakenDownMediaOutput { media_id, cancelled_streams }));
+            Ok(())
+        }
+
+        /// Returns the ids of the payment streams that are currently open for `media_id`
+        #[ink(message)]
+        pub fn get_media_streams(&self, media_id: MediaId) -> Vec<erc1620::StreamId> {
+            self.streams_by_media_id.get(&media_id).cloned().unwrap_or_default()
+        }
+
         /// Validates the request and generates a `SharingId`
         /// * parent_id - Id of last vertex of the sharing chain
         /// * media_id - Symbol of the Media
@@ -431,35 +624,81 @@ mod media {
             Ok(sharing_id)
         }
 
+        /// Returns the sharing with id `sharing_id`
+        #[ink(message)]
+        pub fn get_sharing(&self, sharing_id: SharingId) -> Option<MediaSharing> {
+            self.media_sharings_by_id.get(&sharing_id).cloned()
+        }
+
+        /// Returns the accounts of the sharing chain that ends at `sharing_id`, starting with the sharer of
+        /// `sharing_id` and following the parents. At most `depth` parents are followed, capped at
+        /// `MAX_SHARING_PROPORTIONS_DEPTH`.
+        #[ink(message)]
+        pub fn get_sharing_chain_accounts(&self, sharing_id: SharingId, depth: u32) -> Vec<AccountId> {
+            let depth = depth.min(constants::MAX_SHARING_PROPORTIONS_DEPTH);
+            self.get_sharing_chain(sharing_id, usize::try_from(depth).expect("overflow"))
+        }
+
         /// Tip the media
         /// * media_id - the media id
         /// * amount - amount of token to tip
         /// * token	- The AccountId of the token to tip
+        ///
+        /// The protocol fee, if any, is paid to the fee recipient. The contract keeps it until the owner withdraws it
+        /// if the contract is the fee recipient.
         #[ink(message)]
-        pub fn tip_media(&self, request: TipMediaRequest) -> Result<()> {
+        pub fn tip_media(&mut self, request: TipMediaRequest) -> Result<()> {
             let media = self.medias_by_id.get(&request.media_id).ok_or(Error::MediaNotFound)?;
             let collabs = self.collaborators_by_media_id.get(&request.media_id).ok_or(Error::CollaboratorsNotFound)?;
             let caller = self.env().caller();
-            let mut token = Erc20::from_account_id(request.token);
-            let balance = token.balance_of(caller);
+            let balance = erc20_balance_of(request.token, caller);
             let payment_amount = request.amount;
             if balance < request.amount {
                 return Err(Error::InsufficientBalance);
             }
 
+            // take the protocol fee before distributing to the collaborators
+            let protocol_fee = utils::get_protocol_fee(payment_amount, *self.protocol_fee_bps);
+            let payment_amount = payment_amount - protocol_fee;
+
             // NOTE: this is the same code used in open_media
             // calculate royalty fees
             let mut payments = HashMap::new();
-            let fee = utils::get_royalties(payment_amount, media.royalty, collabs, &mut payments);
+            let fee = utils::get_royalties(payment_amount, media.royalty, collabs, &mut payments)?;
 
             // calculate owners profit
-            utils::get_owners_profit(payment_amount - fee, collabs, &mut payments);
+            utils::get_owners_profit(payment_amount - fee, collabs, &mut payments)?;
+
+            if protocol_fee > 0 {
+                let fee_recipient = *self.fee_recipient;
+                erc20_transfer_from(request.token, caller, fee_recipient, protocol_fee)?;
+                if fee_recipient == self.env().account_id() {
+                    *self.protocol_fees.entry(request.token).or_insert(0) += protocol_fee;
+                }
+            }
 
             for (receiver, balance) in payments.into_iter() {
-                token.transfer_from(caller, *receiver, *balance)?;
+                erc20_transfer_from(request.token, caller, *receiver, *balance)?;
             }
+
+            self.env().emit_event(MediaTipped::new(TippedMediaOutput {
+                media_id: request.media_id,
+                tipper: caller,
+                token: request.token,
+                amount: request.amount,
+                protocol_fee,
+            }));
             Ok(())
         }
+
+        /// Tip the media in its viewing token
+        /// * media_id - the media id
+        /// * amount - amount of the viewing token to tip
+        #[ink(message)]
+        pub fn tip_media_default(&mut self, media_id: MediaId, amount: Balance) -> Result<()> {
+            let token = self.medias_by_id.get(&media_id).ok_or(Error::MediaNotFound)?.view_conditions.viewing_token;
+            self.tip_media(TipMediaRequest { media_id, amount, token })
+        }
     }
 
     #[ink(impl)]
@@ -483,21 +722,24 @@ mod media {
                     } else {
                         break;
                     }
+                } else {
+                    // the sharing or its parent doesn't exist, the depth is not decreased so this must stop here
+                    break;
                 }
             }
 
             accounts
         }
 
-        /// Compute sharing proportions to be distributed between people who shared the media.
+        /// Compute sharing proportions to be distributed between people who shared the media. The chain is
+        /// followed up to `info.sharing_depth` parents.
         fn get_sharing_proportions(
             &self,
             info: &ViewInfo,
             price: Balance,
             sharing_id: SharingId,
-            depth: usize,
         ) -> (Balance, HashMap<AccountId, Balance>) {
-            let chain = self.get_sharing_chain(sharing_id, depth);
+            let chain = self.get_sharing_chain(sharing_id, usize::try_from(info.sharing_depth).expect("overflow"));
             let total = chain.len();
             let factor = Self::get_sharing_division_factor(total.try_into().expect("overflow"));
 
@@ -527,7 +769,7 @@ mod media {
             payment: Balance,
             collabs: &BTreeMap<AccountId, CollabShare>,
             into: &mut HashMap<AccountId, Balance>,
-        ) {
+        ) -> Result<()> {
             distribute_amount(payment, collabs, into)
         }
 
@@ -537,10 +779,15 @@ mod media {
             royalty: Balance,
             collabs: &BTreeMap<AccountId, CollabShare>,
             into: &mut HashMap<AccountId, Balance>,
-        ) -> Balance {
-            let fee = amount * royalty;
-            distribute_amount(fee, collabs, into);
-            fee
+        ) -> Result<Balance> {
+            let fee = amount.checked_mul(royalty).ok_or(Error::Overflow)?;
+            distribute_amount(fee, collabs, into)?;
+            Ok(fee)
+        }
+
+        /// The protocol fee of `protocol_fee_bps` basis points taken from `amount`
+        pub fn get_protocol_fee(amount: Balance, protocol_fee_bps: u16) -> Balance {
+            amount * Balance::from(protocol_fee_bps) / Balance::from(constants::MAX_BPS)
         }
 
         /// Multiplies amount * share for each item and adds or inserts into `into`
@@ -548,12 +795,900 @@ mod media {
             amount: Balance,
             receivers: impl IntoIterator<Item = (&'a AccountId, &'a CollabShare)>,
             into: &mut HashMap<AccountId, Balance>,
-        ) {
+        ) -> Result<()> {
             for (account, share) in receivers.into_iter() {
-                // TODO: is this math correct
-                let value = amount * (share / constants::COLLAB_SHARE_COUNT);
+                // multiply first, dividing first would round every partial share down to zero
+                let value = amount.checked_mul(*share).ok_or(Error::Overflow)? / constants::COLLAB_SHARE_COUNT;
                 into.entry(*account).and_modify(|x| *x += value).or_insert(value);
             }
+            Ok(())
+        }
+    }
+
+    /// Gets an ERC-20 token from an account id
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn get_erc20(token: AccountId) -> erc20::Erc20 { ink_env::call::FromAccountId::from_account_id(token) }
+
+    /// Returns the balance of `owner` in the ERC-20 `token`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn erc20_balance_of(token: AccountId, owner: AccountId) -> Balance { get_erc20(token).balance_of(owner) }
+
+    /// Transfers `value` of the ERC-20 `token` from the contract to `to`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn erc20_transfer(token: AccountId, to: AccountId, value: Balance) -> Result<()> {
+        Ok(get_erc20(token).transfer(to, value)?)
+    }
+
+    /// Transfers `value` of the ERC-20 `token` from `from` to `to`, using the contract's allowance
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn erc20_transfer_from(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+        Ok(get_erc20(token).transfer_from(from, to, value)?)
+    }
+
+    /// Mints the NFT of a new media to `recipient`, with the media's royalty if the NFT contract supports royalties
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn mint_media_nft(
+        erc721: &mut erc721::Erc721,
+        recipient: AccountId,
+        creator: AccountId,
+        royalty: Balance,
+    ) -> Result<MediaId> {
+        if erc721.supports(contract_utils::token_feature::ROYALTIES) {
+            Ok(erc721.mint_with_royalty(recipient, creator, royalty)?)
+        } else {
+            Ok(erc721.mint(recipient)?)
+        }
+    }
+
+    /// Creates a payment stream of `deposit` in the ERC-20 `token` from the contract to `recipient`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn erc1620_create_stream(
+        erc1620: &mut erc1620::Erc1620,
+        recipient: AccountId,
+        deposit: Balance,
+        token: AccountId,
+        start_time: Timestamp,
+        stop_time: Timestamp,
+    ) -> Result<erc1620::StreamId> {
+        Ok(erc1620.create_stream(recipient, deposit, token, start_time, stop_time, false, None)?)
+    }
+
+    /// Cancels a payment stream the contract created
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn erc1620_cancel_stream(erc1620: &mut erc1620::Erc1620, stream_id: erc1620::StreamId) -> Result<()> {
+        erc1620.cancel_stream(stream_id)?;
+        Ok(())
+    }
+
+    /// Calls `on_media_created` on `observer`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn notify_media_created(observer: AccountId, media_id: MediaId, creator: AccountId) -> ink_env::Result<()> {
+        use ink_env::call::{build_call, ExecutionInput, Selector};
+
+        build_call::<Environment>()
+            .callee(observer)
+            .gas_limit(0)
+            .exec_input(
+                ExecutionInput::new(Selector::new(constants::ON_MEDIA_CREATED_SELECTOR))
+                    .push_arg(media_id)
+                    .push_arg(creator),
+            )
+            .returns::<()>()
+            .fire()
+    }
+
+    #[cfg(test)]
+    use tests::{
+        mock_erc1620::{cancel_stream as erc1620_cancel_stream, create_stream as erc1620_create_stream},
+        mock_erc20::{
+            balance_of as erc20_balance_of, transfer as erc20_transfer, transfer_from as erc20_transfer_from,
+        },
+        mock_erc721::mint as mint_media_nft,
+        mock_observer::notify as notify_media_created,
+    };
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use contract_utils::test_utils;
+        use ink_env::call::FromAccountId;
+
+        /// In-memory ERC-20 balances that record every transfer, as token contracts can't be called off-chain
+        pub(super) mod mock_erc20 {
+            use super::*;
+            use std::{cell::RefCell, collections::BTreeMap};
+
+            thread_local! {
+                static BALANCES: RefCell<BTreeMap<(AccountId, AccountId), Balance>> = RefCell::new(BTreeMap::new());
+                static TRANSFERS: RefCell<Vec<(AccountId, AccountId, AccountId, Balance)>> = RefCell::new(Vec::new());
+            }
+
+            pub fn balance_of(token: AccountId, owner: AccountId) -> Balance {
+                BALANCES.with(|balances| balances.borrow().get(&(token, owner)).copied().unwrap_or_default())
+            }
+
+            pub fn transfer(token: AccountId, to: AccountId, value: Balance) -> Result<()> {
+                transfer_from(token, ink_env::account_id::<Environment>().unwrap(), to, value)
+            }
+
+            pub fn transfer_from(token: AccountId, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+                let from_balance = balance_of(token, from);
+                if from_balance < value {
+                    return Err(erc20::Error::InsufficientBalance.into());
+                }
+                set_balance(token, from, from_balance - value);
+                set_balance(token, to, balance_of(token, to) + value);
+                TRANSFERS.with(|transfers| transfers.borrow_mut().push((token, from, to, value)));
+                Ok(())
+            }
+
+            pub fn set_balance(token: AccountId, owner: AccountId, balance: Balance) {
+                BALANCES.with(|balances| balances.borrow_mut().insert((token, owner), balance));
+            }
+
+            /// The `(token, from, to, value)` of every transfer so far
+            pub fn transfers() -> Vec<(AccountId, AccountId, AccountId, Balance)> {
+                TRANSFERS.with(|transfers| transfers.borrow().clone())
+            }
+        }
+
+        /// Mints consecutive media NFTs, as the ERC-721 contract can't be called off-chain
+        pub(super) mod mock_erc721 {
+            use super::*;
+            use std::cell::Cell;
+
+            thread_local! {
+                static LAST_ID: Cell<MediaId> = Cell::new(0);
+            }
+
+            pub fn mint(
+                _erc721: &mut erc721::Erc721,
+                _recipient: AccountId,
+                _creator: AccountId,
+                _royalty: Balance,
+            ) -> Result<MediaId> {
+                LAST_ID.with(|id| {
+                    id.set(id.get() + 1);
+                    Ok(id.get())
+                })
+            }
+        }
+
+        /// Records created and cancelled streams, as the ERC-1620 contract can't be called off-chain
+        pub(super) mod mock_erc1620 {
+            use super::*;
+            use std::cell::RefCell;
+
+            thread_local! {
+                static STREAMS: RefCell<Vec<(AccountId, Balance)>> = RefCell::new(Vec::new());
+                static CANCELLED: RefCell<Vec<erc1620::StreamId>> = RefCell::new(Vec::new());
+            }
+
+            pub fn create_stream(
+                _erc1620: &mut erc1620::Erc1620,
+                recipient: AccountId,
+                deposit: Balance,
+                _token: AccountId,
+                _start_time: Timestamp,
+                _stop_time: Timestamp,
+            ) -> Result<erc1620::StreamId> {
+                STREAMS.with(|streams| {
+                    let mut streams = streams.borrow_mut();
+                    streams.push((recipient, deposit));
+                    Ok(streams.len() as erc1620::StreamId)
+                })
+            }
+
+            pub fn cancel_stream(_erc1620: &mut erc1620::Erc1620, stream_id: erc1620::StreamId) -> Result<()> {
+                CANCELLED.with(|cancelled| cancelled.borrow_mut().push(stream_id));
+                Ok(())
+            }
+
+            /// The `(recipient, deposit)` of every created stream, the id of a stream is its index plus one
+            pub fn streams() -> Vec<(AccountId, Balance)> { STREAMS.with(|streams| streams.borrow().clone()) }
+
+            pub fn cancelled() -> Vec<erc1620::StreamId> { CANCELLED.with(|cancelled| cancelled.borrow().clone()) }
+        }
+
+        /// Records the notifications sent to observers, as other contracts can't be called off-chain
+        pub(super) mod mock_observer {
+            use super::*;
+            use std::cell::{Cell, RefCell};
+
+            thread_local! {
+                static NOTIFICATIONS: RefCell<Vec<(AccountId, MediaId, AccountId)>> = RefCell::new(Vec::new());
+                static FAILING: Cell<bool> = Cell::new(false);
+            }
+
+            pub fn notify(observer: AccountId, media_id: MediaId, creator: AccountId) -> ink_env::Result<()> {
+                if FAILING.with(Cell::get) {
+                    return Err(ink_env::Error::CalleeTrapped);
+                }
+                NOTIFICATIONS.with(|notifications| notifications.borrow_mut().push((observer, media_id, creator)));
+                Ok(())
+            }
+
+            /// Makes every following notification fail
+            pub fn fail() { FAILING.with(|failing| failing.set(true)) }
+
+            pub fn notifications() -> Vec<(AccountId, MediaId, AccountId)> {
+                NOTIFICATIONS.with(|notifications| notifications.borrow().clone())
+            }
+        }
+
+        fn new_media_storage() -> MediaStorage {
+            let accounts = test_utils::default_accounts();
+            MediaStorage::new(
+                FromAccountId::from_account_id(accounts.frank),
+                FromAccountId::from_account_id(accounts.frank),
+            )
+        }
+
+        fn now() -> Timestamp { ink_env::block_timestamp::<Environment>().unwrap() }
+
+        fn viewing_token() -> AccountId { AccountId::from([0x10; 32]) }
+
+        /// A request for a media that can be viewed for free, created by alice with alice as its pod
+        fn create_media_request(r#type: MediaType) -> CreateMediaRequest {
+            let accounts = test_utils::default_accounts();
+            CreateMediaRequest {
+                creator_address: accounts.alice,
+                media_name: "media".into(),
+                pod_address: accounts.alice,
+                r#type,
+                view_conditions: ViewInfo {
+                    viewing_type: ViewingType::Fixed,
+                    viewing_token: viewing_token(),
+                    price: 0,
+                    sharing_percent: 0,
+                    is_streaming_live: false,
+                    streaming_proportions: Vec::new(),
+                    token_reward: Vec::new(),
+                    token_entry: BTreeMap::new(),
+                    duration: 0,
+                    sharing_depth: 0,
+                    entry_mode: EntryMode::HoldToWaive,
+                },
+                nft_conditions: Default::default(),
+                royalty: 0,
+                collabs: None,
+            }
+        }
+
+        /// Registers and uploads `media_id`, which is released at `release_date`. The caller must be the media's pod.
+        fn publish(instance: &mut MediaStorage, media_id: MediaId, release_date: Timestamp) {
+            let mut media: Media = instance.get_media(media_id).unwrap().into();
+            media.is_registered = true;
+            media.is_uploaded = true;
+            media.release_date = release_date;
+            instance.update_media(media).unwrap();
+        }
+
+        fn open(instance: &mut MediaStorage, media_id: MediaId) -> Result<()> {
+            instance.open_media(OpenMediaRequest { media_id, sharing_id: None })
+        }
+
+        fn contract() -> AccountId { ink_env::account_id::<Environment>().unwrap() }
+
+        type Event = <MediaStorage as ::ink_lang::BaseEvent>::Type;
+
+        fn last_event() -> Event {
+            let event = ink_env::test::recorded_events().last().unwrap();
+            <Event as scale::Decode>::decode(&mut &event.data[..]).unwrap()
+        }
+
+        /// Creates a media owned by bob (60%) and charlie (40%)
+        fn create_collab_media(instance: &mut MediaStorage) -> MediaId {
+            instance.create_media(collab_media_request(create_media_request(MediaType::Audio))).unwrap()
+        }
+
+        /// Makes bob (60%) and charlie (40%) the collaborators of the media of `request`
+        fn collab_media_request(request: CreateMediaRequest) -> CreateMediaRequest {
+            let accounts = test_utils::default_accounts();
+            let collabs =
+                vec![(accounts.bob, CollabShare::from_percent(60)), (accounts.charlie, CollabShare::from_percent(40))];
+            CreateMediaRequest { collabs: Some(collabs.into_iter().collect()), ..request }
+        }
+
+        /// A request to update `media_id` to `r#type`, keeping everything else
+        fn update_request(instance: &MediaStorage, media_id: MediaId, r#type: MediaType) -> UpdateMediaRequest {
+            let media = instance.get_media(media_id).unwrap();
+            UpdateMediaRequest {
+                media_id,
+                creator_address: media.creator,
+                media_name: media.media_name,
+                r#type,
+                view_conditions: media.view_conditions,
+                nft_conditions: media.nft_conditions,
+                royalty: media.royalty,
+                collabs: media.collabs,
+            }
+        }
+
+        /// Stores a sharing chain of `media_id` that bob starts and charlie, django, eve and frank continue, one after
+        /// the other, and returns the id of frank's sharing
+        fn store_sharing_chain(instance: &mut MediaStorage, media_id: MediaId) -> SharingId {
+            let accounts = test_utils::default_accounts();
+            let mut parent_id = None;
+            for (id, address) in
+                [accounts.bob, accounts.charlie, accounts.django, accounts.eve, accounts.frank].iter().enumerate()
+            {
+                let id = id as SharingId + 1;
+                instance.media_sharings_by_id.insert(id, MediaSharing { media_id, parent_id, address: *address, id });
+                parent_id = Some(id);
+            }
+            parent_id.unwrap()
+        }
+
+        /// Tips `amount` of the viewing token as the caller
+        fn tip(instance: &mut MediaStorage, media_id: MediaId, amount: Balance) -> Result<()> {
+            instance.tip_media(TipMediaRequest { media_id, amount, token: viewing_token() })
+        }
+
+        #[ink::test]
+        fn open_media_requires_availability() {
+            let mut instance = new_media_storage();
+            let media_id = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+
+            // a new media is neither registered nor uploaded
+            assert_eq!(open(&mut instance, media_id), Err(Error::MediaNotAvailable));
+
+            // a registered media has to be uploaded
+            let mut media: Media = instance.get_media(media_id).unwrap().into();
+            media.is_registered = true;
+            instance.update_media(media).unwrap();
+            assert_eq!(open(&mut instance, media_id), Err(Error::MediaNotAvailable));
+
+            // an uploaded media can't be opened before its release
+            publish(&mut instance, media_id, now() + 1_000);
+            assert_eq!(open(&mut instance, media_id), Err(Error::MediaNotAvailable));
+
+            test_utils::advance_time(2_000);
+            assert_eq!(open(&mut instance, media_id), Ok(()));
+        }
+
+        #[ink::test]
+        fn create_media_works_for_every_type() {
+            let mut instance = new_media_storage();
+            let types = [
+                MediaType::Audio,
+                MediaType::Video,
+                MediaType::LiveAudio,
+                MediaType::LiveVideo,
+                MediaType::Blog,
+                MediaType::BlogSnap,
+                MediaType::DigitalArt,
+                MediaType::Claimable,
+                MediaType::Image,
+                MediaType::Document,
+                MediaType::Other(42),
+            ];
+            for r#type in types.iter() {
+                let media_id = instance.create_media(create_media_request(*r#type)).unwrap();
+                assert_eq!(*r#type, instance.get_media(media_id).unwrap().r#type);
+            }
+        }
+
+        #[ink::test]
+        fn update_media_rejects_incompatible_type() {
+            let mut instance = new_media_storage();
+            let media_id = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+
+            let mut media: Media = instance.get_media(media_id).unwrap().into();
+            media.r#type = MediaType::Video;
+            assert_eq!(Err(Error::IncompatibleMediaType), instance.update_media(media.clone()));
+            media.r#type = MediaType::Other(0);
+            assert_eq!(Err(Error::IncompatibleMediaType), instance.update_media(media.clone()));
+            assert_eq!(MediaType::Audio, instance.get_media(media_id).unwrap().r#type);
+
+            media.r#type = MediaType::LiveAudio;
+            instance.update_media(media).unwrap();
+            assert_eq!(MediaType::LiveAudio, instance.get_media(media_id).unwrap().r#type);
+        }
+
+        #[ink::test]
+        fn update_proposal_changes_to_incompatible_type() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+
+            test_utils::set_caller(accounts.bob);
+            instance.create_update_media_proposal(update_request(&instance, media_id, MediaType::Video)).unwrap();
+            ink_env::test::pop_execution_context();
+            for collab in [accounts.bob, accounts.charlie].iter() {
+                assert_eq!(MediaType::Audio, instance.get_media(media_id).unwrap().r#type);
+                test_utils::set_caller(*collab);
+                let vote = UpdateMediaVote { media_id, requester_address: accounts.bob, vote: true };
+                instance.vote_media_update_proposal(vote).unwrap();
+                ink_env::test::pop_execution_context();
+            }
+            assert_eq!(MediaType::Video, instance.get_media(media_id).unwrap().r#type);
+        }
+
+        #[ink::test]
+        fn distribute_amount_keeps_small_amounts() {
+            let accounts = test_utils::default_accounts();
+            let collabs: BTreeMap<_, _> =
+                vec![(accounts.bob, CollabShare::from_percent(60)), (accounts.charlie, CollabShare::from_percent(40))]
+                    .into_iter()
+                    .collect();
+            let mut payments = HashMap::new();
+            payments.insert(accounts.bob, 1);
+
+            // dividing by `COLLAB_SHARE_COUNT` first would pay nothing for amounts below it
+            utils::distribute_amount(999, &collabs, &mut payments).unwrap();
+            assert_eq!(Some(&600), payments.get(&accounts.bob));
+            assert_eq!(Some(&399), payments.get(&accounts.charlie));
+        }
+
+        #[ink::test]
+        fn distribute_amount_reports_overflow() {
+            let accounts = test_utils::default_accounts();
+            let collabs: BTreeMap<_, _> = vec![(accounts.bob, CollabShare::from_percent(100))].into_iter().collect();
+            let mut payments = HashMap::new();
+
+            assert_eq!(utils::distribute_amount(Balance::MAX, &collabs, &mut payments), Err(Error::Overflow));
+            assert!(payments.get(&accounts.bob).is_none());
+        }
+
+        #[ink::test]
+        fn sharing_depth_limits_rewarded_sharers() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+            let sharing_id = store_sharing_chain(&mut instance, media_id);
+            let mut view_conditions = instance.get_media(media_id).unwrap().view_conditions;
+            view_conditions.sharing_percent = 50;
+
+            let rewarded = |instance: &MediaStorage, view_conditions: &ViewInfo| {
+                let (shared, payments) = instance.get_sharing_proportions(view_conditions, 1_000, sharing_id);
+                let mut rewarded: Vec<AccountId> = payments.keys().copied().collect();
+                rewarded.sort();
+                (shared, rewarded)
+            };
+            view_conditions.sharing_depth = 2;
+            let mut expected = vec![accounts.frank, accounts.eve];
+            expected.sort();
+            assert_eq!((500, expected), rewarded(&instance, &view_conditions));
+
+            view_conditions.sharing_depth = 4;
+            let mut expected = vec![accounts.frank, accounts.eve, accounts.django, accounts.charlie];
+            expected.sort();
+            assert_eq!((500, expected), rewarded(&instance, &view_conditions));
+        }
+
+        #[ink::test]
+        fn get_sharing_chain_stops_at_missing_sharing() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+
+            // an unknown sharing has no chain, rather than looping forever
+            assert!(instance.get_sharing_chain_accounts(1, constants::MAX_SHARING_PROPORTIONS_DEPTH).is_empty());
+
+            // the chain ends at a parent that is not stored
+            instance.media_sharings_by_id.insert(1, MediaSharing {
+                media_id,
+                parent_id: Some(42),
+                address: accounts.bob,
+                id: 1,
+            });
+            assert_eq!(vec![accounts.bob], instance.get_sharing_chain_accounts(1, 2));
+        }
+
+        #[ink::test]
+        fn tip_media_default_tips_in_viewing_token() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+            mock_erc20::set_balance(viewing_token(), accounts.alice, 1_000);
+
+            assert_eq!(Err(Error::MediaNotFound), instance.tip_media_default(media_id + 1, 100));
+            assert_eq!(Err(Error::InsufficientBalance), instance.tip_media_default(media_id, 1_001));
+            instance.tip_media_default(media_id, 1_000).unwrap();
+            assert_eq!(0, mock_erc20::balance_of(viewing_token(), accounts.alice));
+            assert_eq!(600, mock_erc20::balance_of(viewing_token(), accounts.bob));
+            assert_eq!(400, mock_erc20::balance_of(viewing_token(), accounts.charlie));
+
+            match last_event() {
+                Event::MediaTipped(MediaTipped { output }) => {
+                    assert_eq!(media_id, output.media_id);
+                    assert_eq!(accounts.alice, output.tipper);
+                    assert_eq!(viewing_token(), output.token);
+                    assert_eq!(1_000, output.amount);
+                    assert_eq!(0, output.protocol_fee);
+                }
+                _ => panic!("expected a MediaTipped event"),
+            }
+        }
+
+        #[ink::test]
+        fn media_streams_are_recorded_until_closed() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let mut request = collab_media_request(create_media_request(MediaType::Video));
+            request.view_conditions.viewing_type = ViewingType::Dynamic;
+            request.view_conditions.price = 100;
+            request.view_conditions.duration = 60_000;
+            let media_id = instance.create_media(request).unwrap();
+            publish(&mut instance, media_id, now());
+            mock_erc20::set_balance(viewing_token(), accounts.django, 100);
+            assert!(instance.get_media_streams(media_id).is_empty());
+
+            test_utils::set_caller(accounts.django);
+            open(&mut instance, media_id).unwrap();
+            ink_env::test::pop_execution_context();
+
+            // a stream for each collaborator
+            let mut streams = mock_erc1620::streams();
+            streams.sort();
+            assert_eq!(vec![(accounts.bob, 60), (accounts.charlie, 40)], streams);
+            assert_eq!(vec![1, 2], instance.get_media_streams(media_id));
+
+            instance.close_media(media_id).unwrap();
+            assert!(instance.get_media_streams(media_id).is_empty());
+            assert_eq!(vec![1, 2], mock_erc1620::cancelled());
+        }
+
+        /// Creates a media that costs 100 viewing tokens, or 5 entry tokens applied with `entry_mode`
+        fn create_entry_media(instance: &mut MediaStorage, entry_mode: EntryMode) -> MediaId {
+            let mut request = collab_media_request(create_media_request(MediaType::Video));
+            request.view_conditions.price = 100;
+            request.view_conditions.token_entry.insert(entry_token(), 5);
+            request.view_conditions.entry_mode = entry_mode;
+            let media_id = instance.create_media(request).unwrap();
+            publish(instance, media_id, now());
+            media_id
+        }
+
+        fn entry_token() -> AccountId { AccountId::from([0x11; 32]) }
+
+        #[ink::test]
+        fn hold_to_waive_keeps_entry_tokens() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_entry_media(&mut instance, EntryMode::HoldToWaive);
+            mock_erc20::set_balance(entry_token(), accounts.django, 5);
+
+            test_utils::set_caller(accounts.django);
+            open(&mut instance, media_id).unwrap();
+            open(&mut instance, media_id).unwrap();
+            ink_env::test::pop_execution_context();
+
+            assert_eq!(5, mock_erc20::balance_of(entry_token(), accounts.django));
+            assert!(mock_erc20::transfers().is_empty());
+        }
+
+        #[ink::test]
+        fn spend_to_enter_pays_entry_tokens_to_pod() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_entry_media(&mut instance, EntryMode::SpendToEnter);
+            mock_erc20::set_balance(entry_token(), accounts.django, 5);
+
+            test_utils::set_caller(accounts.django);
+            open(&mut instance, media_id).unwrap();
+            assert_eq!(0, mock_erc20::balance_of(entry_token(), accounts.django));
+            assert_eq!(5, mock_erc20::balance_of(entry_token(), accounts.alice));
+            assert_eq!(vec![(entry_token(), accounts.django, accounts.alice, 5)], mock_erc20::transfers());
+
+            // without entry tokens the viewing price has to be paid
+            assert_eq!(Err(Error::InsufficientBalance), open(&mut instance, media_id));
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn get_medias_keeps_order_of_ids() {
+            let mut instance = new_media_storage();
+            let audio = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+            let video = instance.create_media(create_media_request(MediaType::Video)).unwrap();
+            let image = instance.create_media(create_media_request(MediaType::Image)).unwrap();
+
+            let medias = instance.get_medias(vec![image, 99, audio, video]);
+            let types: Vec<_> = medias.iter().map(|media| media.as_ref().map(|media| media.r#type)).collect();
+            assert_eq!(vec![Some(MediaType::Image), None, Some(MediaType::Audio), Some(MediaType::Video)], types);
+            assert_eq!(Some(image), medias[0].as_ref().map(|media| media.id));
+            assert!(instance.get_medias(Vec::new()).is_empty());
+        }
+
+        #[ink::test]
+        fn get_medias_is_capped() {
+            let mut instance = new_media_storage();
+            let media_id = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+
+            let medias = instance.get_medias(vec![media_id; constants::MAX_GET_MEDIAS_COUNT + 10]);
+            assert_eq!(constants::MAX_GET_MEDIAS_COUNT, medias.len());
+            assert!(medias.iter().all(Option::is_some));
+        }
+
+        #[ink::test]
+        fn observer_is_notified_of_created_media() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let first = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(Err(Error::OwnerRequired), instance.set_observer(Some(accounts.eve)));
+            ink_env::test::pop_execution_context();
+            instance.set_observer(Some(accounts.eve)).unwrap();
+            assert_eq!(Some(accounts.eve), instance.observer());
+
+            let second = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+            assert_eq!(vec![(accounts.eve, second, accounts.alice)], mock_observer::notifications());
+            assert_ne!(first, second);
+
+            instance.set_observer(None).unwrap();
+            instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+            assert_eq!(1, mock_observer::notifications().len());
+        }
+
+        #[ink::test]
+        fn failing_observer_does_not_prevent_creation() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            instance.set_observer(Some(accounts.eve)).unwrap();
+            mock_observer::fail();
+
+            let media_id = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+            assert!(instance.get_media(media_id).is_some());
+            assert!(mock_observer::notifications().is_empty());
+        }
+
+        #[ink::test]
+        fn takedown_media_cancels_streams() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let mut request = collab_media_request(create_media_request(MediaType::Video));
+            request.creator_address = accounts.eve;
+            request.view_conditions.viewing_type = ViewingType::Dynamic;
+            request.view_conditions.price = 100;
+            request.view_conditions.duration = 60_000;
+            let media_id = instance.create_media(request).unwrap();
+            publish(&mut instance, media_id, now());
+            mock_erc20::set_balance(viewing_token(), accounts.django, 100);
+            test_utils::set_caller(accounts.django);
+            open(&mut instance, media_id).unwrap();
+            ink_env::test::pop_execution_context();
+
+            // only the pod (alice) or the creator (eve) may take the media down
+            test_utils::set_caller(accounts.django);
+            assert_eq!(Err(Error::PodOrCreatorRequired), instance.takedown_media(media_id));
+            ink_env::test::pop_execution_context();
+            assert_eq!(Err(Error::MediaNotFound), instance.takedown_media(media_id + 1));
+
+            test_utils::set_caller(accounts.eve);
+            instance.takedown_media(media_id).unwrap();
+            ink_env::test::pop_execution_context();
+            assert!(!instance.get_media(media_id).unwrap().is_uploaded);
+            assert!(instance.get_media_streams(media_id).is_empty());
+            assert_eq!(vec![1, 2], mock_erc1620::cancelled());
+            match last_event() {
+                Event::MediaTakenDown(MediaTakenDown { output }) => {
+                    assert_eq!(media_id, output.media_id);
+                    assert_eq!(vec![1, 2], output.cancelled_streams);
+                }
+                _ => panic!("expected a MediaTakenDown event"),
+            }
+
+            test_utils::set_caller(accounts.django);
+            assert_eq!(Err(Error::MediaNotAvailable), open(&mut instance, media_id));
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn get_sharing_follows_a_shared_chain() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+
+            test_utils::set_caller(accounts.bob);
+            let first = instance.share_media(ShareMediaRequest { media_id, parent_id: None }).unwrap();
+            let second = instance.share_media(ShareMediaRequest { media_id, parent_id: Some(first) }).unwrap();
+            let third = instance.share_media(ShareMediaRequest { media_id, parent_id: Some(second) }).unwrap();
+            ink_env::test::pop_execution_context();
+
+            assert_eq!(
+                Some(MediaSharing { media_id, parent_id: Some(second), address: accounts.bob, id: third }),
+                instance.get_sharing(third)
+            );
+            assert_eq!(None, instance.get_sharing(first).unwrap().parent_id);
+            assert_eq!(None, instance.get_sharing(third + 1));
+
+            assert_eq!(vec![accounts.bob; 3], instance.get_sharing_chain_accounts(third, 8));
+            assert_eq!(vec![accounts.bob; 3], instance.get_sharing_chain_accounts(third, u32::MAX));
+            assert_eq!(vec![accounts.bob; 2], instance.get_sharing_chain_accounts(third, 2));
+            assert_eq!(vec![accounts.bob; 2], instance.get_sharing_chain_accounts(second, 8));
+            assert!(instance.get_sharing_chain_accounts(third, 0).is_empty());
+
+            // only the sharer of the parent can continue the chain
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(
+                Err(Error::InvalidMediaSharingParentId),
+                instance.share_media(ShareMediaRequest { media_id, parent_id: Some(third) })
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn protocol_fee_is_taken_from_tips() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+            mock_erc20::set_balance(viewing_token(), accounts.alice, 1_000);
+            assert_eq!((0, accounts.alice), instance.protocol_fee());
+            instance.set_protocol_fee(1_000, accounts.django).unwrap();
+
+            tip(&mut instance, media_id, 1_000).unwrap();
+            assert_eq!(100, mock_erc20::balance_of(viewing_token(), accounts.django));
+            assert_eq!(540, mock_erc20::balance_of(viewing_token(), accounts.bob));
+            assert_eq!(360, mock_erc20::balance_of(viewing_token(), accounts.charlie));
+            match last_event() {
+                Event::MediaTipped(MediaTipped { output }) => {
+                    assert_eq!(1_000, output.amount);
+                    assert_eq!(100, output.protocol_fee);
+                }
+                _ => panic!("expected a MediaTipped event"),
+            }
+        }
+
+        #[ink::test]
+        fn protocol_fee_is_capped() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+            mock_erc20::set_balance(viewing_token(), accounts.alice, 1_000);
+
+            assert_eq!(Err(Error::InvalidProtocolFee), instance.set_protocol_fee(10_001, accounts.django));
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(Err(Error::OwnerRequired), instance.set_protocol_fee(100, accounts.bob));
+            ink_env::test::pop_execution_context();
+            assert_eq!((0, accounts.alice), instance.protocol_fee());
+
+            // a fee of 100% leaves nothing for the collaborators
+            instance.set_protocol_fee(constants::MAX_BPS, accounts.django).unwrap();
+            tip(&mut instance, media_id, 1_000).unwrap();
+            assert_eq!(1_000, mock_erc20::balance_of(viewing_token(), accounts.django));
+            assert_eq!(0, mock_erc20::balance_of(viewing_token(), accounts.bob));
+            assert_eq!(0, mock_erc20::balance_of(viewing_token(), accounts.charlie));
+        }
+
+        #[ink::test]
+        fn media_ids_paged_works() {
+            let mut instance = new_media_storage();
+            assert_eq!(0, instance.media_count());
+            assert!(instance.media_ids_paged(0, 10).is_empty());
+
+            let media_ids: Vec<MediaId> =
+                (0..60).map(|_| instance.create_media(create_media_request(MediaType::Audio)).unwrap()).collect();
+            assert_eq!(60, instance.media_count());
+
+            // the limit is capped at MAX_GET_MEDIAS_COUNT
+            assert_eq!(media_ids[..50].to_vec(), instance.media_ids_paged(0, 100));
+            assert_eq!(media_ids[50..].to_vec(), instance.media_ids_paged(50, 50));
+            assert_eq!(media_ids[3..5].to_vec(), instance.media_ids_paged(3, 2));
+            assert!(instance.media_ids_paged(60, 10).is_empty());
+            assert!(instance.media_ids_paged(u64::MAX, u64::MAX).is_empty());
+        }
+
+        #[ink::test]
+        fn extend_proposal_works_once() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+            let key = ProposalKey { media_id, requester: accounts.bob };
+
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(Err(Error::ProposalNotFound), instance.extend_proposal(media_id, accounts.bob, 1_000));
+            instance.create_update_media_proposal(update_request(&instance, media_id, MediaType::Video)).unwrap();
+            let deadline = instance.proposals_by_key.get(&key).unwrap().deadline();
+
+            assert_eq!(Err(Error::InvalidProposalExtension), instance.extend_proposal(media_id, accounts.bob, 0));
+            assert_eq!(
+                Err(Error::InvalidProposalExtension),
+                instance.extend_proposal(media_id, accounts.bob, constants::MAX_PROPOSAL_EXTENSION + 1)
+            );
+            instance.extend_proposal(media_id, accounts.bob, 1_000).unwrap();
+            assert_eq!(deadline + 1_000, instance.proposals_by_key.get(&key).unwrap().deadline());
+
+            // a proposal can only be extended once
+            assert_eq!(Err(Error::ProposalAlreadyExtended), instance.extend_proposal(media_id, accounts.bob, 1_000));
+            ink_env::test::pop_execution_context();
+            assert_eq!(deadline + 1_000, instance.proposals_by_key.get(&key).unwrap().deadline());
+        }
+
+        #[ink::test]
+        fn extend_proposal_requires_requester() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+
+            test_utils::set_caller(accounts.bob);
+            instance.create_update_media_proposal(update_request(&instance, media_id, MediaType::Video)).unwrap();
+            ink_env::test::pop_execution_context();
+
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(Err(Error::RequesterRequired), instance.extend_proposal(media_id, accounts.bob, 1_000));
+            ink_env::test::pop_execution_context();
+            let key = ProposalKey { media_id, requester: accounts.bob };
+            assert_eq!(0, instance.proposals_by_key.get(&key).unwrap().extension);
+        }
+
+        #[ink::test]
+        fn get_collaborators_works() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+            let without_collabs = instance.create_media(create_media_request(MediaType::Audio)).unwrap();
+
+            let expected: BTreeMap<_, _> =
+                vec![(accounts.bob, CollabShare::from_percent(60)), (accounts.charlie, CollabShare::from_percent(40))]
+                    .into_iter()
+                    .collect();
+            assert_eq!(Some(expected), instance.get_collaborators(media_id));
+            assert_eq!(Some(BTreeMap::new()), instance.get_collaborators(without_collabs));
+            assert_eq!(None, instance.get_collaborators(without_collabs + 1));
+        }
+
+        #[ink::test]
+        fn get_collaborators_after_fractionalise() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+
+            test_utils::set_caller(accounts.bob);
+            let sharings = vec![(accounts.django, 1)].into_iter().collect();
+            instance.fractionalise_media_collab(FractionaliseCollabRequest { media_id, sharings }).unwrap();
+            ink_env::test::pop_execution_context();
+
+            let expected: BTreeMap<_, _> = vec![
+                (accounts.charlie, CollabShare::from_percent(40)),
+                (accounts.django, CollabShare::from_percent(60)),
+            ]
+            .into_iter()
+            .collect();
+            assert_eq!(Some(expected), instance.get_collaborators(media_id));
+        }
+
+        #[ink::test]
+        fn protocol_fees_accrue_and_are_withdrawn() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+            mock_erc20::set_balance(viewing_token(), accounts.alice, 1_200);
+            instance.set_protocol_fee(500, contract()).unwrap();
+            assert_eq!((500, contract()), instance.protocol_fee());
+
+            tip(&mut instance, media_id, 1_000).unwrap();
+            tip(&mut instance, media_id, 200).unwrap();
+            assert_eq!(60, instance.accrued_protocol_fees(viewing_token()));
+            assert_eq!(0, instance.accrued_protocol_fees(AccountId::from([0x11; 32])));
+            // the contract also holds funds that are not fees
+            mock_erc20::set_balance(viewing_token(), contract(), 1_060);
+
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(Err(Error::OwnerRequired), instance.withdraw_protocol_fees(viewing_token(), accounts.bob));
+            ink_env::test::pop_execution_context();
+
+            assert_eq!(Ok(60), instance.withdraw_protocol_fees(viewing_token(), accounts.eve));
+            assert_eq!(60, mock_erc20::balance_of(viewing_token(), accounts.eve));
+            assert_eq!(1_000, mock_erc20::balance_of(viewing_token(), contract()));
+            assert_eq!(0, instance.accrued_protocol_fees(viewing_token()));
+            assert_eq!(Ok(0), instance.withdraw_protocol_fees(viewing_token(), accounts.eve));
+            assert_eq!(60, mock_erc20::balance_of(viewing_token(), accounts.eve));
+        }
+
+        #[ink::test]
+        fn protocol_fees_paid_to_another_recipient_do_not_accrue() {
+            let accounts = test_utils::default_accounts();
+            let mut instance = new_media_storage();
+            let media_id = create_collab_media(&mut instance);
+            mock_erc20::set_balance(viewing_token(), accounts.alice, 1_000);
+            instance.set_protocol_fee(500, accounts.django).unwrap();
+
+            tip(&mut instance, media_id, 1_000).unwrap();
+            assert_eq!(50, mock_erc20::balance_of(viewing_token(), accounts.django));
+            assert_eq!(0, instance.accrued_protocol_fees(viewing_token()));
+            assert_eq!(Ok(0), instance.withdraw_protocol_fees(viewing_token(), accounts.eve));
         }
     }
 }
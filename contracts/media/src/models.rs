@@ -9,9 +9,34 @@ use ink_prelude::{collections::BTreeMap, string::String, vec::Vec};
 use ink_storage::traits::{PackedLayout, SpreadLayout};
 use scale::{Decode, Encode};
 
-/// A share of a media that collabs can own
+/// A share of a media that collabs can own. A share of `constants::COLLAB_SHARE_COUNT` is the whole media.
 pub type CollabShare = u128;
 
+/// Converts a `CollabShare` to and from percentages
+pub trait CollabShareExt {
+    /// The share that is `pct` percent of the media
+    fn from_percent(pct: u8) -> Self;
+    /// The share that is `bps` basis points (hundredths of a percent) of the media
+    fn from_bps(bps: u16) -> Self;
+    /// The share as a whole percentage of the media, rounded down
+    fn to_percent(&self) -> u8;
+}
+
+impl CollabShareExt for CollabShare {
+    fn from_percent(pct: u8) -> Self { CollabShare::from(pct) * constants::COLLAB_SHARE_COUNT / 100 }
+
+    fn from_bps(bps: u16) -> Self { CollabShare::from(bps) * constants::COLLAB_SHARE_COUNT / 10_000 }
+
+    fn to_percent(&self) -> u8 {
+        let pct = self.saturating_mul(100) / constants::COLLAB_SHARE_COUNT;
+        if pct > u8::MAX.into() {
+            u8::MAX
+        } else {
+            pct as u8
+        }
+    }
+}
+
 /// Used by multiple modules
 pub mod other {
     use super::*;
@@ -28,6 +53,35 @@ pub mod other {
         BlogSnap,
         DigitalArt,
         Claimable,
+        Image,
+        Document,
+        /// A type that isn't known to the contract, identified by its number
+        Other(u8),
+    }
+
+    impl MediaType {
+        /// Whether a media of type `self` may be changed to `other` without a collaborator vote. Types are
+        /// compatible when they present the same kind of content, e.g. `Audio` and `LiveAudio`. `Other` types are
+        /// only compatible with themselves.
+        pub fn is_compatible_with(&self, other: &MediaType) -> bool {
+            match (self, other) {
+                (MediaType::Other(a), MediaType::Other(b)) => a == b,
+                (MediaType::Other(_), _) | (_, MediaType::Other(_)) => false,
+                _ => self.family() == other.family(),
+            }
+        }
+
+        /// Groups the known types by the kind of content they present
+        fn family(&self) -> u8 {
+            match self {
+                MediaType::Audio | MediaType::LiveAudio => 0,
+                MediaType::Video | MediaType::LiveVideo => 1,
+                MediaType::Blog | MediaType::BlogSnap | MediaType::Document => 2,
+                MediaType::DigitalArt | MediaType::Image => 3,
+                MediaType::Claimable => 4,
+                MediaType::Other(_) => 5,
+            }
+        }
     }
 
     /// Info about the media viewing
@@ -44,10 +98,30 @@ pub mod other {
         pub is_streaming_live: bool,
         pub streaming_proportions: Vec<(String, Balance)>,
         pub token_reward: Vec<(AccountId, Balance)>,
-        /// This appears to be the minimum amount of a token a user must have to open the media?
+        /// The amount of a token a user must have to open the media. See `entry_mode` for how it is used.
         pub token_entry: BTreeMap<AccountId, Balance>,
         /// Duration in case that the media viewing type is Dynamic
         pub duration: u64,
+        /// The number of parents in the sharing chain that are rewarded when the media is opened. Must not
+        /// exceed `constants::MAX_SHARING_PROPORTIONS_DEPTH`.
+        pub sharing_depth: u32,
+        /// How `token_entry` is applied when the media is opened
+        pub entry_mode: EntryMode,
+    }
+
+    /// How the entry tokens of a media are used to open it
+    ///
+    /// Entry tokens let a media be opened with a token instead of the viewing price. With `HoldToWaive` the
+    /// entry token works as a membership pass: it is never spent, so the holder can open the media as often as
+    /// they like. With `SpendToEnter` the entry token works as a ticket: it is paid to the media's pod every
+    /// time the media is opened.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+    pub enum EntryMode {
+        /// Holding the required amount of any entry token waives the payment
+        HoldToWaive,
+        /// The required amount of an entry token is transferred to the pod instead of the payment
+        SpendToEnter,
     }
 
     #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, PackedLayout, SpreadLayout)]
@@ -101,6 +175,41 @@ pub mod storage {
         pub royalty: Balance,
     }
 
+    impl Media {
+        /// Whether the media can be opened at `now`: it must be registered, uploaded and released
+        pub fn is_available(&self, now: Timestamp) -> bool {
+            self.is_registered && self.is_uploaded && self.release_date <= now
+        }
+
+        /// Postpones the release of the registered, unreleased media to `new_date` on behalf of `caller`, who must
+        /// be the creator. Returns the previous release date.
+        pub fn reschedule_release(
+            &mut self,
+            caller: AccountId,
+            now: Timestamp,
+            new_date: Timestamp,
+        ) -> core::result::Result<Timestamp, crate::errors::RescheduleReleaseError> {
+            use crate::errors::RescheduleReleaseError::*;
+
+            if new_date < now {
+                return Err(ReleaseDateMustBeInFuture);
+            }
+            if self.creator != caller {
+                return Err(Unauthorized);
+            }
+            if !self.is_registered {
+                return Err(MediaNotRegistered);
+            }
+            if self.release_date <= now {
+                return Err(MediaAlreadyReleased);
+            }
+            if new_date <= self.release_date {
+                return Err(ReleaseCanOnlyBePostponed);
+            }
+            Ok(core::mem::replace(&mut self.release_date, new_date))
+        }
+    }
+
     // UpdateMediaProposal is the structure that holds the voters for a media update
     #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
@@ -123,6 +232,8 @@ pub mod storage {
         pub duration: u64,
         /// The time stamp the proposal was created
         pub date: u64,
+        /// The amount of time the requester has extended the proposal by
+        pub extension: u64,
     }
 
     impl UpdateMediaProposal {
@@ -140,8 +251,11 @@ pub mod storage {
             VoteCount { yes_count, no_count }
         }
 
+        /// The time the proposal expires, including its extension
+        pub fn deadline(&self) -> u64 { self.date + self.duration + self.extension }
+
         /// Is the time expired
-        pub fn is_expired(&self, now: u64) -> bool { self.date + self.duration <= now }
+        pub fn is_expired(&self, now: u64) -> bool { self.deadline() <= now }
     }
 
     /// Count of votes
@@ -283,7 +397,8 @@ pub mod input {
 pub mod output {
     use super::*;
 
-    /// Information about a Media
+    /// Information about a Media, as returned by `get_media`. Its layout must match `MediaInfo` in
+    /// `reference/additional-types.json` for off-chain clients to decode it.
     #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
     pub struct MediaInfo {
@@ -348,4 +463,172 @@ pub mod event_output {
         /// The id of the SharingMedia
         pub sharing_id: SharingId,
     }
+
+    #[derive(Debug, Encode, Decode, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct TippedMediaOutput {
+        /// The id of the media that was tipped
+        pub media_id: MediaId,
+        /// The account that sent the tip
+        pub tipper: AccountId,
+        /// The ERC-20 token the tip was paid in
+        pub token: AccountId,
+        /// The amount that was tipped, including the protocol fee
+        pub amount: Balance,
+        /// The part of `amount` that was paid to the fee recipient
+        pub protocol_fee: Balance,
+    }
+
+    #[derive(Debug, Encode, Decode, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct WithdrawnProtocolFeesOutput {
+        /// The ERC-20 token the fees were paid in
+        pub token: AccountId,
+        /// The account the fees were transferred to
+        pub to: AccountId,
+        /// The amount of fees that was withdrawn
+        pub amount: Balance,
+    }
+
+    #[derive(Debug, Encode, Decode, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct TakenDownMediaOutput {
+        /// The id of the media that was taken down
+        pub media_id: MediaId,
+        /// The payment streams that were cancelled
+        pub cancelled_streams: Vec<erc1620::StreamId>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_percent_works() {
+        assert_eq!(0, CollabShare::from_percent(0));
+        assert_eq!(constants::COLLAB_SHARE_COUNT / 100, CollabShare::from_percent(1));
+        assert_eq!(constants::COLLAB_SHARE_COUNT / 2, CollabShare::from_percent(50));
+        assert_eq!(constants::COLLAB_SHARE_COUNT, CollabShare::from_percent(100));
+    }
+
+    #[test]
+    fn from_bps_works() {
+        assert_eq!(0, CollabShare::from_bps(0));
+        assert_eq!(constants::COLLAB_SHARE_COUNT / 10_000, CollabShare::from_bps(1));
+        assert_eq!(CollabShare::from_percent(25), CollabShare::from_bps(2_500));
+        assert_eq!(constants::COLLAB_SHARE_COUNT, CollabShare::from_bps(10_000));
+    }
+
+    #[test]
+    fn to_percent_works() {
+        assert_eq!(0, CollabShare::from_bps(99).to_percent());
+        assert_eq!(1, CollabShare::from_bps(199).to_percent());
+        assert_eq!(37, CollabShare::from_percent(37).to_percent());
+        assert_eq!(100, constants::COLLAB_SHARE_COUNT.to_percent());
+        // saturates instead of wrapping
+        assert_eq!(u8::MAX, (constants::COLLAB_SHARE_COUNT * 3).to_percent());
+        assert_eq!(u8::MAX, CollabShare::MAX.to_percent());
+    }
+
+    /// A registered media of account 1 released at 2_000
+    fn registered_media() -> Media {
+        let account = |x: u8| AccountId::from([x; 32]);
+        Media {
+            creator: account(1),
+            media_name: "a media".into(),
+            id: 7,
+            pod_address: account(2),
+            r#type: MediaType::Other(3),
+            release_date: 2_000,
+            view_conditions: ViewInfo {
+                viewing_type: ViewingType::Fixed,
+                viewing_token: account(3),
+                price: 1_000,
+                sharing_percent: 10,
+                is_streaming_live: false,
+                streaming_proportions: vec![],
+                token_reward: vec![],
+                token_entry: Default::default(),
+                duration: 60_000,
+                sharing_depth: 3,
+                entry_mode: EntryMode::HoldToWaive,
+            },
+            nft_conditions: NftInfo { funding_token: account(8), price: 9 },
+            is_registered: true,
+            is_uploaded: false,
+            royalty: 2,
+        }
+    }
+
+    #[test]
+    fn reschedule_release_postpones() {
+        use crate::errors::RescheduleReleaseError::*;
+
+        let mut media = registered_media();
+        let creator = media.creator;
+        assert_eq!(Ok(2_000), media.reschedule_release(creator, 1_000, 3_000));
+        assert_eq!(3_000, media.release_date);
+
+        // moving the release forward or keeping the date is rejected
+        assert_eq!(Err(ReleaseCanOnlyBePostponed), media.reschedule_release(creator, 1_000, 2_500));
+        assert_eq!(Err(ReleaseCanOnlyBePostponed), media.reschedule_release(creator, 1_000, 3_000));
+        assert_eq!(3_000, media.release_date);
+
+        // as is a date in the past
+        assert_eq!(Err(ReleaseDateMustBeInFuture), media.reschedule_release(creator, 1_000, 999));
+    }
+
+    #[test]
+    fn reschedule_release_checks_media() {
+        use crate::errors::RescheduleReleaseError::*;
+
+        let mut media = registered_media();
+        let creator = media.creator;
+        assert_eq!(Err(Unauthorized), media.reschedule_release(AccountId::from([5; 32]), 1_000, 3_000));
+        // released media keep their date
+        assert_eq!(Err(MediaAlreadyReleased), media.reschedule_release(creator, 2_000, 3_000));
+
+        media.is_registered = false;
+        assert_eq!(Err(MediaNotRegistered), media.reschedule_release(creator, 1_000, 3_000));
+        assert_eq!(2_000, media.release_date);
+    }
+
+    #[test]
+    fn media_info_round_trips() {
+        let account = |x: u8| AccountId::from([x; 32]);
+        let media_info = MediaInfo {
+            creator: account(1),
+            media_name: "a media".into(),
+            id: 7,
+            pod_address: account(2),
+            r#type: MediaType::Other(3),
+            release_date: 1_600_000_000_000,
+            view_conditions: ViewInfo {
+                viewing_type: ViewingType::Dynamic,
+                viewing_token: account(3),
+                price: 1_000,
+                sharing_percent: 10,
+                is_streaming_live: true,
+                streaming_proportions: vec![("first".into(), 60), ("second".into(), 40)],
+                token_reward: vec![(account(4), 5), (account(5), 6)],
+                token_entry: vec![(account(6), 7), (account(7), 8)].into_iter().collect(),
+                duration: 60_000,
+                sharing_depth: 3,
+                entry_mode: EntryMode::SpendToEnter,
+            },
+            nft_conditions: NftInfo { funding_token: account(8), price: 9 },
+            is_registered: true,
+            is_uploaded: true,
+            royalty: 2,
+            collabs: vec![(account(1), CollabShare::from_percent(70)), (account(9), CollabShare::from_percent(30))]
+                .into_iter()
+                .collect(),
+        };
+
+        let encoded = media_info.encode();
+        let decoded = MediaInfo::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(media_info, decoded);
+        assert_eq!(encoded, decoded.encode());
+    }
 }
@@ -5,7 +5,7 @@ mod models;
 
 #[ink::contract]
 mod auction {
-    use contract_utils::{TokenStandard, ZERO_ACCOUNT};
+    use contract_utils::{debug_log, TokenStandard, ZERO_ACCOUNT};
     use ink_prelude::{vec, vec::Vec};
     use ink_storage::collections::HashMap as StorageHashMap;
     use multi_token::MultiToken;
@@ -21,6 +21,17 @@ mod auction {
         owner: AccountId,
         /// Allowed Accounts
         allowed_accounts: StorageHashMap<AccountId, ()>,
+        /// NFTs held for open auctions, by (media address, media token id)
+        escrowed_nfts: StorageHashMap<(AccountId, u64), ()>,
+        /// Sum of the highest bids held for open auctions, by ERC20 token address
+        escrowed_bids: StorageHashMap<AccountId, Balance>,
+        /// The last bid nonce used by a bidder, by (Token Address, Owner, Bidder)
+        bid_nonces: StorageHashMap<(AccountId, AccountId, AccountId), u64>,
+        /// How long after its end time an auction can still be cancelled or reset, in milliseconds
+        cancel_grace: u64,
+        /// Refunds of outbid bids that could not be transferred, by (Token Address, Bidder). They stay escrowed
+        /// until the bidder withdraws them.
+        pending_refunds: StorageHashMap<(AccountId, AccountId), Balance>,
     }
 
     #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
@@ -45,12 +56,30 @@ mod auction {
         AuctionNotFound,
         /// Unable to bid into withdrawn auction
         AuctionHasBeenWithdrawn,
+        /// Unable to bid into cancelled auction
+        AuctionHasBeenCancelled,
         /// Transfer Error
         Transfer,
         /// Insufficient bid amount
         InsufficientBidAmount,
+        /// The opening bid is not accepted by the opening policy of the auction
+        BidBelowReserve,
         /// Cannot withdraw an empty auction
         AuctionHasNoBid,
+        /// The token is held for an open auction
+        TokenIsEscrowed,
+        /// The bid nonce is not greater than the last nonce the bidder used for this auction
+        DuplicateBid,
+        /// The auction can only be modified before it starts and before the first bid
+        CannotModifyAfterStart,
+        /// The operation is not supported by this kind of auction
+        WrongAuctionKind,
+        /// The end price of a Dutch auction is greater than its start price
+        InvalidPrice,
+        /// The auction can only be settled after its end time
+        AuctionNotEnded,
+        /// The caller has no pending refund for the token
+        NoPendingRefund,
     }
 
     /// Event emitted when an auction is created.
@@ -71,6 +100,12 @@ mod auction {
         output: Output,
     }
 
+    /// Event emitted when an ended auction is settled
+    #[ink(event)]
+    pub struct AuctionSettled {
+        output: Output,
+    }
+
     /// Event emitted when auction is canceled and it transfer back the tokens
     #[ink(event)]
     pub struct AuctionCanceledTransfer {
@@ -83,24 +118,59 @@ mod auction {
         output: Output,
     }
 
+    /// Event emitted when a Dutch auction is bought
+    #[ink(event)]
+    pub struct DutchAuctionBought {
+        output: Output,
+    }
+
+    /// Event emitted when the parameters of an auction are updated
+    #[ink(event)]
+    pub struct AuctionUpdated {
+        output: Output,
+    }
+
     /// Event emitted when auction is reseted
     #[ink(event)]
     pub struct AuctionReset {
         output: Output,
     }
 
+    /// Event emitted when an outbid bidder withdraws a refund that could not be transferred when they were outbid
+    #[ink(event)]
+    pub struct RefundWithdrawn {
+        output: Output,
+    }
+
+    /// Event emitted when the contract owner rescues a token that is not tied to an auction
+    #[ink(event)]
+    pub struct TokenRescued {
+        output: Output,
+    }
+
     /// The Auction result type.
     pub type Result<T> = core::result::Result<T, Error>;
 
     /// one day in milliseconds.
-    const ONE_DAY: u64 = 864_000_000;
+    const ONE_DAY: u64 = 86_400_000;
+    /// The maximum number of auctions returned by `active_auctions_paged`
+    const MAX_AUCTIONS_PAGE_SIZE: u32 = 50;
 
     impl Auction {
         #[ink(constructor)]
         #[allow(clippy::new_without_default)]
         pub fn new() -> Self {
             let caller = Self::env().caller();
-            Self { auctions: Default::default(), owner: caller, allowed_accounts: Default::default() }
+            Self {
+                auctions: Default::default(),
+                owner: caller,
+                allowed_accounts: Default::default(),
+                escrowed_nfts: Default::default(),
+                escrowed_bids: Default::default(),
+                bid_nonces: Default::default(),
+                cancel_grace: ONE_DAY,
+                pending_refunds: Default::default(),
+            }
         }
 
         /// Returns the actual timestamp
@@ -122,10 +192,37 @@ mod auction {
             self.allowed_accounts.keys().cloned().collect::<Vec<AccountId>>()
         }
 
-        /// Returns the list of active auction (not withdrawn)
+        /// Returns the list of active auction, the same as `auctions_by_status(AuctionStatus::Open)`. Reset auctions
+        /// accept bids as well and are listed by `auctions_by_status(AuctionStatus::Reset)`. The output grows with the
+        /// number of auctions and can exceed the gas or output limits, use `active_auctions_paged` instead.
+        #[ink(message)]
+        pub fn get_active_auctions(&self) -> Vec<AuctionModel> { self.auctions_by_status(AuctionStatus::Open) }
+
+        /// Returns up to `limit` active auctions, skipping the first `start`. `limit` is capped at
+        /// `MAX_AUCTIONS_PAGE_SIZE`.
+        #[ink(message)]
+        pub fn active_auctions_paged(&self, start: u32, limit: u32) -> Vec<AuctionModel> {
+            self.auctions
+                .values()
+                .filter(|x| x.status == AuctionStatus::Open)
+                .skip(start as usize)
+                .take(limit.min(MAX_AUCTIONS_PAGE_SIZE) as usize)
+                .cloned()
+                .collect()
+        }
+
+        /// Returns the number of active auctions
+        #[ink(message)]
+        pub fn active_auction_count(&self) -> u32 {
+            self.auctions.values().filter(|x| x.status == AuctionStatus::Open).count() as u32
+        }
+
+        /// Returns the list of auctions with the given status
+        /// Params:
+        /// *status: the status to filter on
         #[ink(message)]
-        pub fn get_active_auctions(&self) -> Vec<AuctionModel> {
-            self.auctions.values().filter(|x| !x.withdrawn).cloned().collect()
+        pub fn auctions_by_status(&self, status: AuctionStatus) -> Vec<AuctionModel> {
+            self.auctions.values().filter(|x| x.status == status).cloned().collect()
         }
 
         /// Approve user and add to approved users
@@ -154,6 +251,21 @@ mod auction {
             Ok(())
         }
 
+        /// Set how long after its end time an auction can still be cancelled or reset. Only callable by the contract
+        /// owner.
+        /// Params:
+        /// *cancel_grace: the grace period in milliseconds
+        #[ink(message)]
+        pub fn set_cancel_grace(&mut self, cancel_grace: u64) -> Result<()> {
+            self.ensure_contract_owner(self.env().caller())?;
+            self.cancel_grace = cancel_grace;
+            Ok(())
+        }
+
+        /// Returns how long after its end time an auction can still be cancelled or reset, in milliseconds
+        #[ink(message)]
+        pub fn cancel_grace(&self) -> u64 { self.cancel_grace }
+
         /// Create an auction
         /// Params:
         /// *input: CreateAuctionRequest
@@ -162,8 +274,8 @@ mod auction {
             let caller = self.env().caller();
             self.ensure_allowed_user(caller)?;
 
-            // Check that auction doesn't exist already
-            if self.get_auction_by_pair(input.token_address, caller).is_some() {
+            // Check that no auction is running for the pair already, a settled or cancelled one is replaced
+            if self.get_auction_by_pair(input.token_address, caller).map_or(false, |x| ensure_open(&x).is_ok()) {
                 return Err(Error::AuctionAlreadyExist);
             }
             // check time
@@ -174,13 +286,13 @@ mod auction {
             if input.start_time >= input.end_time {
                 return Err(Error::InvalidTime);
             }
+            if input.auction_kind == AuctionKind::Dutch && input.end_price > input.start_price {
+                return Err(Error::InvalidPrice);
+            }
 
             let current_account_id = self.env().account_id();
-            let mut erc721 = MultiToken { account_id: input.media_address, standard: TokenStandard::Erc721 };
-            match erc721.transfer_from(caller, current_account_id, Some(input.media_token_id), None) {
-                Err(_) => return Err(Error::Transfer),
-                Ok(f) => f,
-            };
+            let erc721 = MultiToken { account_id: input.media_address, standard: TokenStandard::Erc721 };
+            transfer_token_from(erc721, caller, current_account_id, Some(input.media_token_id), None)?;
 
             let auction = AuctionModel {
                 owner: caller,
@@ -194,9 +306,14 @@ mod auction {
                 media_token_id: input.media_token_id,
                 token_address: input.token_address,
                 ipfs_hash: input.ipfs_hash.clone(),
-                withdrawn: false,
+                status: AuctionStatus::Open,
+                auction_kind: input.auction_kind,
+                start_price: input.start_price,
+                end_price: input.end_price,
+                opening_policy: input.opening_policy,
             };
             self.auctions.insert((input.token_address, caller), auction.clone());
+            self.escrowed_nfts.insert((input.media_address, input.media_token_id), ());
 
             self.env().emit_event(AuctionCreated {
                 output: Output {
@@ -229,35 +346,55 @@ mod auction {
             if now < auction.start_time || now > auction.end_time {
                 return Err(Error::InvalidTime);
             }
-            if auction.withdrawn {
-                return Err(Error::AuctionHasBeenWithdrawn);
+            ensure_open(&auction)?;
+            if auction.auction_kind != AuctionKind::English {
+                return Err(Error::WrongAuctionKind);
             }
-            if input.amount <= (auction.gathered + auction.bid_increment) {
-                return Err(Error::InsufficientBidAmount);
+            let nonce_key = (input.token_address, input.owner, caller);
+            if let (Some(nonce), Some(last)) = (input.nonce, self.bid_nonces.get(&nonce_key)) {
+                if nonce <= *last {
+                    return Err(Error::DuplicateBid);
+                }
             }
-            if input.amount <= auction.reserve_price {
+            debug_log!("place_bid: {:?} bids {} (current {})", caller, input.amount, auction.gathered);
+            // the opening bid must satisfy the opening policy, later bids must outbid the highest bid by the increment
+            let is_first_bid = auction.bidder == ZERO_ACCOUNT;
+            if is_first_bid {
+                if !auction.opening_policy.accepts(input.amount, auction.reserve_price) {
+                    return Err(Error::BidBelowReserve);
+                }
+            } else if input.amount <= (auction.gathered + auction.bid_increment) {
                 return Err(Error::InsufficientBidAmount);
             }
 
             // Send bid to contract. If success save bid in storage
-            let is_first_bid = auction.bidder == ZERO_ACCOUNT;
             let current_account_id = self.env().account_id();
-            let mut erc20 = MultiToken { account_id: auction.token_address, standard: TokenStandard::Erc20 };
-            match erc20.transfer_from(caller, current_account_id, None, Some(input.amount)) {
+            let erc20 = MultiToken { account_id: auction.token_address, standard: TokenStandard::Erc20 };
+            match transfer_token_from(erc20, caller, current_account_id, None, Some(input.amount)) {
                 Err(_) => Err(Error::Transfer),
                 Ok(_) => {
-                    // transfer last amount to preceding bidder
-                    if !is_first_bid {
-                        match erc20.transfer(auction.bidder, None, Some(auction.gathered)) {
-                            Err(_) => return Err(Error::Transfer),
-                            Ok(f) => f,
-                        };
-                    }
-
-                    let last_bidder = auction.bidder;
+                    // record the new bid before refunding, so the token never observes the outbid state
+                    let (last_bidder, last_amount) = (auction.bidder, auction.gathered);
+                    *self.escrowed_bids.entry(auction.token_address).or_insert(0) += input.amount;
                     auction.gathered = input.amount;
                     auction.bidder = caller;
                     self.auctions.insert((input.token_address, input.owner), auction.clone());
+                    if let Some(nonce) = input.nonce {
+                        self.bid_nonces.insert(nonce_key, nonce);
+                    }
+
+                    // transfer last amount to preceding bidder. A failed refund can't revert the bid, as storage is
+                    // not rolled back on `Err`, so it stays escrowed until the bidder withdraws it.
+                    let mut refunded = false;
+                    if !is_first_bid {
+                        if transfer_token(erc20, last_bidder, None, Some(last_amount)).is_ok() {
+                            self.release_bid(auction.token_address, last_amount);
+                            refunded = true;
+                        } else {
+                            *self.pending_refunds.entry((auction.token_address, last_bidder)).or_insert(0) +=
+                                last_amount;
+                        }
+                    }
 
                     let mut transactions = vec![Transfer {
                         r#type: "transfer".as_bytes().to_vec(),
@@ -266,13 +403,13 @@ mod auction {
                         to: current_account_id,
                         amount: 1,
                     }];
-                    if !is_first_bid {
+                    if refunded {
                         transactions.push(Transfer {
                             r#type: "transfer".as_bytes().to_vec(),
                             token: "Erc20".as_bytes().to_vec(),
                             from: current_account_id,
                             to: last_bidder,
-                            amount: input.amount,
+                            amount: last_amount,
                         });
                     }
 
@@ -283,6 +420,112 @@ mod auction {
             }
         }
 
+        /// Withdraw the refunds of the caller's outbid bids that could not be transferred when they were outbid
+        /// Params:
+        /// *token_address: address of the ERC20 contract
+        #[ink(message)]
+        pub fn withdraw_refund(&mut self, token_address: AccountId) -> Result<Balance> {
+            let caller = self.env().caller();
+            let amount = self.pending_refund(token_address, caller);
+            if amount == 0 {
+                return Err(Error::NoPendingRefund);
+            }
+
+            let erc20 = MultiToken { account_id: token_address, standard: TokenStandard::Erc20 };
+            transfer_token(erc20, caller, None, Some(amount))?;
+            self.pending_refunds.take(&(token_address, caller));
+            self.release_bid(token_address, amount);
+
+            self.env().emit_event(RefundWithdrawn {
+                output: Output {
+                    auctions: vec![],
+                    transactions: vec![Transfer {
+                        r#type: "transfer".as_bytes().to_vec(),
+                        token: "Erc20".as_bytes().to_vec(),
+                        from: self.env().account_id(),
+                        to: caller,
+                        amount,
+                    }],
+                },
+            });
+
+            Ok(amount)
+        }
+
+        /// Returns the refund of outbid bids `bidder` has yet to withdraw
+        /// Params:
+        /// *token_address: address of the ERC20 contract
+        /// *bidder: address of the bidder
+        #[ink(message)]
+        pub fn pending_refund(&self, token_address: AccountId, bidder: AccountId) -> Balance {
+            self.pending_refunds.get(&(token_address, bidder)).copied().unwrap_or(0)
+        }
+
+        /// Buy the NFT of a Dutch auction at its current price, which ends the auction. The price is transferred
+        /// directly from the caller to the auction owner.
+        /// Params:
+        /// *token_address: address of the ERC20 contract
+        /// *owner: address of auction owner
+        #[ink(message)]
+        pub fn buy_dutch(&mut self, token_address: AccountId, owner: AccountId) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_allowed_user(caller)?;
+
+            let mut auction = self.get_auction_by_pair(token_address, owner).ok_or(Error::AuctionNotFound)?;
+            if auction.auction_kind != AuctionKind::Dutch {
+                return Err(Error::WrongAuctionKind);
+            }
+            let now = self.env().block_timestamp();
+            if now < auction.start_time || now > auction.end_time {
+                return Err(Error::InvalidTime);
+            }
+            ensure_open(&auction)?;
+            let price = auction.current_dutch_price(now);
+
+            // ERC20 goes from the buyer to the owner, the ERC721 to the buyer
+            let erc20 = MultiToken { account_id: auction.token_address, standard: TokenStandard::Erc20 };
+            transfer_token_from(erc20, caller, auction.owner, None, Some(price))?;
+            let erc721 = MultiToken { account_id: auction.media_address, standard: TokenStandard::Erc721 };
+            transfer_token(erc721, caller, Some(auction.media_token_id), None)?;
+
+            auction.gathered = price;
+            auction.bidder = caller;
+            auction.status = AuctionStatus::Settled;
+            self.auctions.insert((token_address, owner), auction.clone());
+            self.escrowed_nfts.take(&(auction.media_address, auction.media_token_id));
+
+            let transactions = vec![
+                Transfer {
+                    r#type: "transfer".as_bytes().to_vec(),
+                    token: "Erc721".as_bytes().to_vec(),
+                    from: self.env().account_id(),
+                    to: caller,
+                    amount: 1,
+                },
+                Transfer {
+                    r#type: "transfer".as_bytes().to_vec(),
+                    token: "Erc20".as_bytes().to_vec(),
+                    from: caller,
+                    to: auction.owner,
+                    amount: price,
+                },
+            ];
+            self.env().emit_event(DutchAuctionBought { output: Output { auctions: vec![auction], transactions } });
+
+            Ok(())
+        }
+
+        /// Returns the current price of a Dutch auction, or `None` if there is no such auction
+        /// Params:
+        /// *token_address: address of the ERC20 contract
+        /// *owner: address of auction owner
+        #[ink(message)]
+        pub fn current_dutch_price(&self, token_address: AccountId, owner: AccountId) -> Option<Balance> {
+            self.get_auction_by_pair(token_address, owner)
+                .filter(|auction| auction.auction_kind == AuctionKind::Dutch)
+                .map(|auction| auction.current_dutch_price(self.env().block_timestamp()))
+        }
+
         /// Withdraw an auction
         /// Params:
         /// *input: WithdrawAuctionRequest
@@ -293,28 +536,11 @@ mod auction {
                 self.get_auction_by_pair(input.token_address, input.owner).ok_or(Error::AuctionNotFound)?;
 
             self.ensure_auction_owner(auction.owner, caller)?;
-            if auction.withdrawn {
-                return Err(Error::AuctionHasBeenWithdrawn);
-            }
+            ensure_open(&auction)?;
             if auction.bidder == ZERO_ACCOUNT {
                 return Err(Error::AuctionHasNoBid);
             }
-
-            // ERC721 transferred to bidder
-            let mut erc721 = MultiToken { account_id: auction.media_address, standard: TokenStandard::Erc721 };
-            match erc721.transfer(auction.bidder, Some(auction.media_token_id), None) {
-                Err(_) => return Err(Error::Transfer),
-                Ok(f) => f,
-            }
-
-            // Amount of ERC20 is transferred to owner
-            let mut erc20 = MultiToken { account_id: auction.token_address, standard: TokenStandard::Erc20 };
-            match erc20.transfer(auction.owner, None, Some(auction.gathered)) {
-                Err(_) => return Err(Error::Transfer),
-                Ok(f) => f,
-            }
-
-            auction.withdrawn = true;
+            self.transfer_to_winner(&mut auction)?;
             auction.gathered = 0;
             self.auctions.insert((input.token_address, input.owner), auction.clone());
 
@@ -343,42 +569,77 @@ mod auction {
             Ok(())
         }
 
+        /// Settle an auction after its end time, transferring the NFT to the highest bidder and the bid to the
+        /// owner like `withdraw_auction`. Anyone may settle an ended auction. An auction without bids can't be
+        /// settled, the owner cancels it instead to get the NFT back.
+        /// Params:
+        /// *token_address: address of the ERC20 contract
+        /// *owner: address of auction owner
+        #[ink(message)]
+        pub fn settle(&mut self, token_address: AccountId, owner: AccountId) -> Result<()> {
+            let mut auction = self.get_auction_by_pair(token_address, owner).ok_or(Error::AuctionNotFound)?;
+            if self.env().block_timestamp() <= auction.end_time {
+                return Err(Error::AuctionNotEnded);
+            }
+            ensure_open(&auction)?;
+            if auction.bidder == ZERO_ACCOUNT {
+                return Err(Error::AuctionHasNoBid);
+            }
+            self.transfer_to_winner(&mut auction)?;
+
+            let transactions = vec![
+                Transfer {
+                    r#type: "transfer".as_bytes().to_vec(),
+                    token: "Erc721".as_bytes().to_vec(),
+                    from: self.env().account_id(),
+                    to: auction.bidder,
+                    amount: 1,
+                },
+                Transfer {
+                    r#type: "transfer".as_bytes().to_vec(),
+                    token: "Erc20".as_bytes().to_vec(),
+                    from: self.env().account_id(),
+                    to: auction.owner,
+                    amount: auction.gathered,
+                },
+            ];
+            self.env().emit_event(AuctionSettled { output: Output { auctions: vec![auction], transactions } });
+
+            Ok(())
+        }
+
         /// Cancel an auction
         /// Params:
         /// *input: CancelAuctionRequest
         #[ink(message)]
         pub fn cancel_auction(&mut self, input: CancelAuctionRequest) -> Result<()> {
             let caller = self.env().caller();
-            let auction = self.get_auction_by_pair(input.token_address, input.owner).ok_or(Error::AuctionNotFound)?;
+            let mut auction =
+                self.get_auction_by_pair(input.token_address, input.owner).ok_or(Error::AuctionNotFound)?;
 
             self.ensure_auction_owner(auction.owner, caller)?;
 
-            if self.env().block_timestamp() > (auction.end_time + ONE_DAY) {
+            if self.env().block_timestamp() > auction.end_time.saturating_add(self.cancel_grace) {
                 return Err(Error::InvalidTime);
             }
-            if auction.withdrawn {
-                return Err(Error::AuctionHasBeenWithdrawn);
-            }
+            ensure_open(&auction)?;
 
             //Transfer to last bidder
             let is_first_bid = auction.bidder == ZERO_ACCOUNT;
             if !is_first_bid {
-                let mut erc20 = MultiToken { account_id: auction.token_address, standard: TokenStandard::Erc20 };
-                match erc20.transfer(auction.bidder, None, Some(auction.gathered)) {
-                    Err(_) => return Err(Error::Transfer),
-                    Ok(f) => f,
-                }
+                let erc20 = MultiToken { account_id: auction.token_address, standard: TokenStandard::Erc20 };
+                transfer_token(erc20, auction.bidder, None, Some(auction.gathered))?;
             }
 
             // Transfer ERC721 back to owner
-            let mut erc721 = MultiToken { account_id: auction.media_address, standard: TokenStandard::Erc721 };
-            match erc721.transfer(auction.owner, Some(auction.media_token_id), None) {
-                Err(_) => return Err(Error::Transfer),
-                Ok(f) => f,
-            }
+            let erc721 = MultiToken { account_id: auction.media_address, standard: TokenStandard::Erc721 };
+            transfer_token(erc721, auction.owner, Some(auction.media_token_id), None)?;
 
             let last_bidder = auction.bidder;
-            self.auctions.take(&(auction.token_address, auction.owner));
+            auction.status = AuctionStatus::Cancelled;
+            self.auctions.insert((auction.token_address, auction.owner), auction.clone());
+            self.escrowed_nfts.take(&(auction.media_address, auction.media_token_id));
+            self.release_bid(auction.token_address, auction.gathered);
 
             let mut transactions = vec![Transfer {
                 r#type: "transfer".as_bytes().to_vec(),
@@ -402,6 +663,36 @@ mod auction {
             Ok(())
         }
 
+        /// Update the reserve price of an auction. Only the auction owner can do this, before the auction starts and
+        /// before the first bid.
+        /// Params:
+        /// *token_address: address of the ERC20 contract
+        /// *owner: address of auction owner
+        /// *new_reserve: the new minimum amount to bid
+        #[ink(message)]
+        pub fn update_reserve(
+            &mut self,
+            token_address: AccountId,
+            owner: AccountId,
+            new_reserve: Balance,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let mut auction = self.get_auction_by_pair(token_address, owner).ok_or(Error::AuctionNotFound)?;
+            self.ensure_auction_owner(auction.owner, caller)?;
+            ensure_open(&auction)?;
+            if auction.bidder != ZERO_ACCOUNT || self.env().block_timestamp() >= auction.start_time {
+                return Err(Error::CannotModifyAfterStart);
+            }
+
+            auction.reserve_price = new_reserve;
+            self.auctions.insert((token_address, owner), auction.clone());
+
+            let output = Output { auctions: vec![auction], transactions: Vec::new() };
+            self.env().emit_event(AuctionUpdated { output });
+
+            Ok(())
+        }
+
         /// Reset an auction
         /// Params:
         /// *input: ResetAuctionRequest
@@ -413,12 +704,10 @@ mod auction {
 
             self.ensure_auction_owner(auction.owner, caller)?;
 
-            if self.env().block_timestamp() > (auction.end_time + ONE_DAY) {
+            if self.env().block_timestamp() > auction.end_time.saturating_add(self.cancel_grace) {
                 return Err(Error::InvalidTime);
             }
-            if auction.withdrawn {
-                return Err(Error::AuctionHasBeenWithdrawn);
-            }
+            ensure_open(&auction)?;
             let now = self.env().block_timestamp();
             if now > input.end_time {
                 return Err(Error::InvalidTime);
@@ -427,15 +716,14 @@ mod auction {
             //Transfer to last bidder
             let is_first_bid = auction.bidder == ZERO_ACCOUNT;
             if !is_first_bid {
-                let mut erc20 = MultiToken { account_id: auction.token_address, standard: TokenStandard::Erc20 };
-                match erc20.transfer(auction.bidder, None, Some(auction.gathered)) {
-                    Err(_) => return Err(Error::Transfer),
-                    Ok(f) => f,
-                }
+                let erc20 = MultiToken { account_id: auction.token_address, standard: TokenStandard::Erc20 };
+                transfer_token(erc20, auction.bidder, None, Some(auction.gathered))?;
             }
 
             let last_bidder = auction.bidder;
             let amount_transferred_to_bidder = auction.gathered;
+            self.escrowed_nfts.take(&(auction.media_address, auction.media_token_id));
+            self.release_bid(auction.token_address, auction.gathered);
             auction.owner = input.owner;
             auction.media_address = input.media_address;
             auction.media_token_id = input.media_token_id;
@@ -446,8 +734,10 @@ mod auction {
             auction.start_time = now;
             auction.gathered = 0;
             auction.bidder = ZERO_ACCOUNT;
+            auction.status = AuctionStatus::Reset;
 
             self.auctions.insert((input.token_address, input.owner), auction.clone());
+            self.escrowed_nfts.insert((auction.media_address, auction.media_token_id), ());
 
             let mut transactions: Vec<Transfer> = vec![];
             if !is_first_bid {
@@ -465,6 +755,62 @@ mod auction {
             Ok(())
         }
 
+        /// Transfer a token held by the contract that is not tied to an open auction, e.g. after a reset that
+        /// failed mid-way. Only callable by the contract owner.
+        /// Params:
+        /// *standard: the standard of the token
+        /// *token: address of the token contract
+        /// *token_id: id of the token, required for ERC721 and ERC1155
+        /// *amount: amount to transfer, required for ERC20 and ERC1155
+        /// *to: the recipient
+        #[ink(message)]
+        pub fn rescue_token(
+            &mut self,
+            standard: TokenStandard,
+            token: AccountId,
+            token_id: Option<u64>,
+            amount: Option<Balance>,
+            to: AccountId,
+        ) -> Result<()> {
+            self.ensure_contract_owner(self.env().caller())?;
+
+            let current_account_id = self.env().account_id();
+            let multi_token = MultiToken { account_id: token, standard };
+            match standard {
+                TokenStandard::Erc721 => {
+                    let token_id = token_id.ok_or(Error::Transfer)?;
+                    if self.escrowed_nfts.contains_key(&(token, token_id)) {
+                        return Err(Error::TokenIsEscrowed);
+                    }
+                }
+                TokenStandard::Erc20 => {
+                    let escrowed = self.escrowed_bids.get(&token).copied().unwrap_or(0);
+                    let free = token_balance_of(multi_token, current_account_id).saturating_sub(escrowed);
+                    if amount.ok_or(Error::Transfer)? > free {
+                        return Err(Error::TokenIsEscrowed);
+                    }
+                }
+                // auctions never hold ERC1155 tokens
+                TokenStandard::Erc1155 => {}
+            }
+            transfer_token(multi_token, to, token_id, amount)?;
+
+            self.env().emit_event(TokenRescued {
+                output: Output {
+                    auctions: vec![],
+                    transactions: vec![Transfer {
+                        r#type: "transfer".as_bytes().to_vec(),
+                        token: token_standard_name(standard).as_bytes().to_vec(),
+                        from: current_account_id,
+                        to,
+                        amount: amount.unwrap_or(1),
+                    }],
+                },
+            });
+
+            Ok(())
+        }
+
         /// Ensure that caller is the owner of the auction
         /// Params:
         /// *owner: AccountId of the auction owner
@@ -499,5 +845,563 @@ mod auction {
             }
             Ok(())
         }
+
+        /// Transfer the NFT to the highest bidder and the bid to the owner, and mark the auction as settled
+        fn transfer_to_winner(&mut self, auction: &mut AuctionModel) -> Result<()> {
+            // ERC721 transferred to bidder
+            let erc721 = MultiToken { account_id: auction.media_address, standard: TokenStandard::Erc721 };
+            transfer_token(erc721, auction.bidder, Some(auction.media_token_id), None)?;
+
+            // Amount of ERC20 is transferred to owner
+            let erc20 = MultiToken { account_id: auction.token_address, standard: TokenStandard::Erc20 };
+            transfer_token(erc20, auction.owner, None, Some(auction.gathered))?;
+
+            auction.status = AuctionStatus::Settled;
+            self.auctions.insert((auction.token_address, auction.owner), auction.clone());
+            self.escrowed_nfts.take(&(auction.media_address, auction.media_token_id));
+            self.release_bid(auction.token_address, auction.gathered);
+            Ok(())
+        }
+
+        /// Stop tracking `amount` of `token` as a bid held for an auction
+        fn release_bid(&mut self, token: AccountId, amount: Balance) {
+            if let Some(escrowed) = self.escrowed_bids.get_mut(&token) {
+                *escrowed = escrowed.saturating_sub(amount);
+            }
+        }
+    }
+
+    /// The token name used in `Transfer` outputs
+    fn token_standard_name(standard: TokenStandard) -> &'static str {
+        match standard {
+            TokenStandard::Erc20 => "Erc20",
+            TokenStandard::Erc721 => "Erc721",
+            TokenStandard::Erc1155 => "Erc1155",
+        }
+    }
+
+    /// Ensure that the auction accepts bids, otherwise returns why it does not
+    fn ensure_open(auction: &AuctionModel) -> Result<()> {
+        match auction.status {
+            AuctionStatus::Open | AuctionStatus::Reset => Ok(()),
+            AuctionStatus::Settled => Err(Error::AuctionHasBeenWithdrawn),
+            AuctionStatus::Cancelled => Err(Error::AuctionHasBeenCancelled),
+        }
+    }
+
+    /// Transfers `amount` tokens with id `token_id` from the contract to `to`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn transfer_token(
+        mut token: MultiToken,
+        to: AccountId,
+        token_id: Option<u64>,
+        amount: Option<Balance>,
+    ) -> Result<()> {
+        token.transfer(to, token_id, amount).map_err(|_| Error::Transfer)
+    }
+
+    /// Transfers `amount` tokens with id `token_id` from `from` to `to`, using the contract's allowance
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn transfer_token_from(
+        mut token: MultiToken,
+        from: AccountId,
+        to: AccountId,
+        token_id: Option<u64>,
+        amount: Option<Balance>,
+    ) -> Result<()> {
+        token.transfer_from(from, to, token_id, amount).map_err(|_| Error::Transfer)
+    }
+
+    /// Returns the ERC20 balance of `account`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn token_balance_of(mut token: MultiToken, account: AccountId) -> Balance { token.balance_of(account) }
+
+    #[cfg(test)]
+    use tests::mock_token::{
+        balance_of as token_balance_of, transfer as transfer_token, transfer_from as transfer_token_from,
+    };
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use contract_utils::test_utils;
+
+        /// In-memory ERC20 balances and ERC721 owners, as token contracts can't be called off-chain
+        pub(super) mod mock_token {
+            use super::*;
+            use std::{cell::RefCell, collections::BTreeMap};
+
+            thread_local! {
+                static BALANCES: RefCell<BTreeMap<(AccountId, AccountId), Balance>> = RefCell::new(BTreeMap::new());
+                static OWNERS: RefCell<BTreeMap<(AccountId, u64), AccountId>> = RefCell::new(BTreeMap::new());
+                static FAILING_RECIPIENT: RefCell<Option<AccountId>> = RefCell::new(None);
+            }
+
+            pub fn balance_of(token: MultiToken, account: AccountId) -> Balance {
+                assert_eq!(TokenStandard::Erc20, token.standard);
+                BALANCES.with(|x| x.borrow().get(&(token.account_id, account)).copied().unwrap_or_default())
+            }
+
+            pub fn transfer(
+                token: MultiToken,
+                to: AccountId,
+                token_id: Option<u64>,
+                amount: Option<Balance>,
+            ) -> Result<()> {
+                transfer_from(token, ink_env::account_id::<Environment>().unwrap(), to, token_id, amount)
+            }
+
+            pub fn transfer_from(
+                token: MultiToken,
+                from: AccountId,
+                to: AccountId,
+                token_id: Option<u64>,
+                amount: Option<Balance>,
+            ) -> Result<()> {
+                if FAILING_RECIPIENT.with(|x| *x.borrow() == Some(to)) {
+                    return Err(Error::Transfer);
+                }
+                match token.standard {
+                    TokenStandard::Erc20 => {
+                        let amount = amount.ok_or(Error::Transfer)?;
+                        let from_balance = balance_of(token, from).checked_sub(amount).ok_or(Error::Transfer)?;
+                        let to_balance = balance_of(token, to);
+                        set_balance(token.account_id, from, from_balance);
+                        set_balance(token.account_id, to, to_balance + amount);
+                    }
+                    _ => {
+                        let key = (token.account_id, token_id.ok_or(Error::Transfer)?);
+                        if owner_of(key.0, key.1) != Some(from) {
+                            return Err(Error::Transfer);
+                        }
+                        OWNERS.with(|x| x.borrow_mut().insert(key, to));
+                    }
+                }
+                Ok(())
+            }
+
+            pub fn set_balance(token: AccountId, account: AccountId, balance: Balance) {
+                BALANCES.with(|x| x.borrow_mut().insert((token, account), balance));
+            }
+
+            pub fn set_owner(token: AccountId, token_id: u64, owner: AccountId) {
+                OWNERS.with(|x| x.borrow_mut().insert((token, token_id), owner));
+            }
+
+            pub fn owner_of(token: AccountId, token_id: u64) -> Option<AccountId> {
+                OWNERS.with(|x| x.borrow().get(&(token, token_id)).copied())
+            }
+
+            /// Makes every following transfer to `recipient` fail, or none if `None`
+            pub fn fail_transfers_to(recipient: Option<AccountId>) {
+                FAILING_RECIPIENT.with(|x| *x.borrow_mut() = recipient)
+            }
+        }
+
+        const MEDIA: [u8; 32] = [0x10; 32];
+        const TOKEN: [u8; 32] = [0x20; 32];
+
+        fn media() -> AccountId { AccountId::from(MEDIA) }
+
+        fn token() -> AccountId { AccountId::from(TOKEN) }
+
+        fn contract() -> AccountId { ink_env::account_id::<Environment>().unwrap() }
+
+        fn now() -> u64 { ink_env::block_timestamp::<Environment>().unwrap() }
+
+        /// Creates the contract with alice as its owner and alice, bob and charlie as allowed users, each holding
+        /// 1_000 tokens. Alice owns the media NFT 1.
+        fn new_auction() -> Auction {
+            let accounts = test_utils::default_accounts();
+            let mut auction = Auction::new();
+            for user in [accounts.alice, accounts.bob, accounts.charlie].iter() {
+                auction.approve_user(*user).unwrap();
+                mock_token::set_balance(token(), *user, 1_000);
+            }
+            mock_token::set_owner(media(), 1, accounts.alice);
+            auction
+        }
+
+        /// An auction of the media NFT 1 from now until 10 seconds later, with a reserve of 100
+        fn create_request(auction_kind: AuctionKind) -> CreateAuctionRequest {
+            CreateAuctionRequest {
+                media_address: media(),
+                media_token_id: 1,
+                token_address: token(),
+                bid_increment: 10,
+                start_time: now(),
+                end_time: now() + 10_000,
+                reserve_price: 100,
+                ipfs_hash: Vec::new(),
+                auction_kind,
+                start_price: 1_000,
+                end_price: 0,
+                opening_policy: OpeningPolicy::AtLeastReserve,
+            }
+        }
+
+        /// Places a bid on alice's auction as `bidder`
+        fn bid(auction: &mut Auction, bidder: AccountId, amount: Balance, nonce: Option<u64>) -> Result<()> {
+            let owner = test_utils::default_accounts().alice;
+            test_utils::set_caller(bidder);
+            let result = auction.place_bid(PlaceBidRequest { token_address: token(), owner, amount, nonce });
+            ink_env::test::pop_execution_context();
+            result
+        }
+
+        fn status(auction: &mut Auction) -> AuctionStatus {
+            let owner = test_utils::default_accounts().alice;
+            auction.get_auction_by_pair(token(), owner).unwrap().status
+        }
+
+        #[ink::test]
+        fn create_auction_opens_it() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+
+            assert_eq!(AuctionStatus::Open, status(&mut auction));
+            assert_eq!(Some(contract()), mock_token::owner_of(media(), 1));
+            assert_eq!(1, auction.get_active_auctions().len());
+            assert_eq!(Err(Error::AuctionAlreadyExist), auction.create_auction(create_request(AuctionKind::English)));
+            assert_eq!(accounts.alice, auction.get_active_auctions()[0].owner);
+        }
+
+        #[ink::test]
+        fn place_bid_keeps_auction_open() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            bid(&mut auction, accounts.bob, 100, None).unwrap();
+
+            assert_eq!(AuctionStatus::Open, status(&mut auction));
+            assert_eq!(100, mock_token::balance_of(MultiToken::new(token(), TokenStandard::Erc20), contract()));
+        }
+
+        #[ink::test]
+        fn withdraw_auction_settles_it() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            bid(&mut auction, accounts.bob, 100, None).unwrap();
+            auction.withdraw_auction(WithdrawAuctionRequest { token_address: token(), owner: accounts.alice }).unwrap();
+
+            let withdrawn = auction.get_auction_by_pair(token(), accounts.alice).unwrap();
+            assert_eq!(AuctionStatus::Settled, withdrawn.status);
+            assert_eq!(0, withdrawn.gathered);
+            assert_eq!(Some(accounts.bob), mock_token::owner_of(media(), 1));
+            assert_eq!(1_100, mock_token::balance_of(MultiToken::new(token(), TokenStandard::Erc20), accounts.alice));
+            assert!(auction.get_active_auctions().is_empty());
+
+            // alice can auction another NFT for the same token
+            mock_token::set_owner(media(), 2, accounts.alice);
+            let request = create_request(AuctionKind::English);
+            auction.create_auction(CreateAuctionRequest { media_token_id: 2, ..request }).unwrap();
+            assert_eq!(AuctionStatus::Open, status(&mut auction));
+        }
+
+        #[ink::test]
+        fn settle_settles_it() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            bid(&mut auction, accounts.bob, 100, None).unwrap();
+            assert_eq!(Err(Error::AuctionNotEnded), auction.settle(token(), accounts.alice));
+            test_utils::advance_time(12_000);
+            auction.settle(token(), accounts.alice).unwrap();
+
+            assert_eq!(AuctionStatus::Settled, status(&mut auction));
+            assert_eq!(Some(accounts.bob), mock_token::owner_of(media(), 1));
+        }
+
+        #[ink::test]
+        fn cancel_auction_cancels_it() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            bid(&mut auction, accounts.bob, 100, None).unwrap();
+            auction.cancel_auction(CancelAuctionRequest { token_address: token(), owner: accounts.alice }).unwrap();
+
+            assert_eq!(AuctionStatus::Cancelled, status(&mut auction));
+            assert_eq!(Some(accounts.alice), mock_token::owner_of(media(), 1));
+            assert_eq!(1_000, mock_token::balance_of(MultiToken::new(token(), TokenStandard::Erc20), accounts.bob));
+            assert!(auction.get_active_auctions().is_empty());
+            assert_eq!(Err(Error::AuctionHasBeenCancelled), bid(&mut auction, accounts.charlie, 200, None));
+        }
+
+        #[ink::test]
+        fn create_auction_after_cancel() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            auction.cancel_auction(CancelAuctionRequest { token_address: token(), owner: accounts.alice }).unwrap();
+
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            let recreated = auction.get_auction_by_pair(token(), accounts.alice).unwrap();
+            assert_eq!(AuctionStatus::Open, recreated.status);
+            assert_eq!((ZERO_ACCOUNT, 0), (recreated.bidder, recreated.gathered));
+            assert_eq!(Some(contract()), mock_token::owner_of(media(), 1));
+            bid(&mut auction, accounts.bob, 100, None).unwrap();
+        }
+
+        #[ink::test]
+        fn reset_auction_resets_it() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            bid(&mut auction, accounts.bob, 100, None).unwrap();
+            auction
+                .reset_auction(ResetAuctionRequest {
+                    media_address: media(),
+                    media_token_id: 1,
+                    token_address: token(),
+                    owner: accounts.alice,
+                    bid_increment: 10,
+                    end_time: now() + 10_000,
+                    reserve_price: 50,
+                    ipfs_hash: Vec::new(),
+                })
+                .unwrap();
+
+            assert_eq!(AuctionStatus::Reset, status(&mut auction));
+            assert_eq!(1_000, mock_token::balance_of(MultiToken::new(token(), TokenStandard::Erc20), accounts.bob));
+            assert!(auction.get_active_auctions().is_empty());
+            assert_eq!(0, auction.active_auction_count());
+            assert_eq!(1, auction.auctions_by_status(AuctionStatus::Reset).len());
+
+            // a reset auction accepts bids, and stays reset
+            bid(&mut auction, accounts.charlie, 50, None).unwrap();
+            assert_eq!(AuctionStatus::Reset, status(&mut auction));
+        }
+
+        #[ink::test]
+        fn bid_nonces_must_increase() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            bid(&mut auction, accounts.bob, 100, Some(5)).unwrap();
+            bid(&mut auction, accounts.charlie, 200, Some(1)).unwrap();
+
+            assert_eq!(Err(Error::DuplicateBid), bid(&mut auction, accounts.bob, 300, Some(5)));
+            assert_eq!(Err(Error::DuplicateBid), bid(&mut auction, accounts.bob, 300, Some(4)));
+            bid(&mut auction, accounts.bob, 300, Some(6)).unwrap();
+            // bids without a nonce are not checked
+            bid(&mut auction, accounts.charlie, 400, None).unwrap();
+        }
+
+        #[ink::test]
+        fn active_auctions_paged_works() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            // alice auctions the media NFTs 1 to 60, each for a different token
+            for i in 1..=60u8 {
+                mock_token::set_owner(media(), u64::from(i), accounts.alice);
+                let request = create_request(AuctionKind::English);
+                auction
+                    .create_auction(CreateAuctionRequest {
+                        media_token_id: u64::from(i),
+                        token_address: AccountId::from([i + 0x80; 32]),
+                        ..request
+                    })
+                    .unwrap();
+            }
+
+            assert_eq!(60, auction.active_auction_count());
+            assert_eq!(60, auction.get_active_auctions().len());
+            // the limit is capped at MAX_AUCTIONS_PAGE_SIZE
+            let first_page = auction.active_auctions_paged(0, 100);
+            assert_eq!(MAX_AUCTIONS_PAGE_SIZE as usize, first_page.len());
+            let second_page = auction.active_auctions_paged(50, 50);
+            assert_eq!(10, second_page.len());
+            assert_eq!(auction.get_active_auctions(), [first_page, second_page].concat());
+            assert_eq!(3, auction.active_auctions_paged(57, 5).len());
+            // starting past the end returns nothing
+            assert!(auction.active_auctions_paged(60, 10).is_empty());
+            assert!(auction.active_auctions_paged(u32::MAX, 10).is_empty());
+
+            // the count follows the auctions leaving the active list
+            let token_address = AccountId::from([0x81; 32]);
+            auction.cancel_auction(CancelAuctionRequest { token_address, owner: accounts.alice }).unwrap();
+            assert_eq!(59, auction.active_auction_count());
+            assert_eq!(59, auction.get_active_auctions().len());
+            assert_eq!(9, auction.active_auctions_paged(50, 50).len());
+        }
+
+        #[ink::test]
+        fn update_reserve_works_before_start() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            let request = create_request(AuctionKind::English);
+            let start_time = now() + 5_000;
+            auction
+                .create_auction(CreateAuctionRequest { start_time, end_time: start_time + 10_000, ..request })
+                .unwrap();
+
+            auction.update_reserve(token(), accounts.alice, 500).unwrap();
+            assert_eq!(500, auction.get_auction_by_pair(token(), accounts.alice).unwrap().reserve_price);
+
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(Err(Error::OnlyAuctionOwnerAllowed), auction.update_reserve(token(), accounts.alice, 50));
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn update_reserve_fails_after_start() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            let request = create_request(AuctionKind::English);
+            let start_time = now() + 5_000;
+            auction
+                .create_auction(CreateAuctionRequest { start_time, end_time: start_time + 10_000, ..request })
+                .unwrap();
+            test_utils::advance_time(6_000);
+
+            assert_eq!(Err(Error::CannotModifyAfterStart), auction.update_reserve(token(), accounts.alice, 500));
+            assert_eq!(100, auction.get_auction_by_pair(token(), accounts.alice).unwrap().reserve_price);
+        }
+
+        #[ink::test]
+        fn update_reserve_fails_after_bid() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            bid(&mut auction, accounts.bob, 100, None).unwrap();
+
+            assert_eq!(Err(Error::CannotModifyAfterStart), auction.update_reserve(token(), accounts.alice, 500));
+            assert_eq!(100, auction.get_auction_by_pair(token(), accounts.alice).unwrap().reserve_price);
+        }
+
+        #[ink::test]
+        fn buy_dutch_works() {
+            let accounts = test_utils::default_accounts();
+            let erc20 = MultiToken::new(token(), TokenStandard::Erc20);
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::Dutch)).unwrap();
+            assert_eq!(Some(1_000), auction.current_dutch_price(token(), accounts.alice));
+            assert_eq!(Err(Error::WrongAuctionKind), bid(&mut auction, accounts.bob, 1_000, None));
+
+            test_utils::advance_time(5_000);
+            let price = auction.current_dutch_price(token(), accounts.alice).unwrap();
+            assert!(price < 1_000);
+            test_utils::set_caller(accounts.bob);
+            auction.buy_dutch(token(), accounts.alice).unwrap();
+            ink_env::test::pop_execution_context();
+
+            let bought = auction.get_auction_by_pair(token(), accounts.alice).unwrap();
+            assert_eq!(AuctionStatus::Settled, bought.status);
+            assert_eq!((accounts.bob, price), (bought.bidder, bought.gathered));
+            assert_eq!(Some(accounts.bob), mock_token::owner_of(media(), 1));
+            assert_eq!(1_000 - price, mock_token::balance_of(erc20, accounts.bob));
+            assert_eq!(1_000 + price, mock_token::balance_of(erc20, accounts.alice));
+
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(Err(Error::AuctionHasBeenWithdrawn), auction.buy_dutch(token(), accounts.alice));
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn opening_bid_must_reach_reserve() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+
+            assert_eq!(Err(Error::BidBelowReserve), bid(&mut auction, accounts.bob, 99, None));
+            assert_eq!(ZERO_ACCOUNT, auction.get_auction_by_pair(token(), accounts.alice).unwrap().bidder);
+            bid(&mut auction, accounts.bob, 100, None).unwrap();
+            assert_eq!(accounts.bob, auction.get_auction_by_pair(token(), accounts.alice).unwrap().bidder);
+        }
+
+        #[ink::test]
+        fn any_above_zero_accepts_opening_bid_below_reserve() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            let request = create_request(AuctionKind::English);
+            auction
+                .create_auction(CreateAuctionRequest { opening_policy: OpeningPolicy::AnyAboveZero, ..request })
+                .unwrap();
+
+            assert_eq!(Err(Error::BidBelowReserve), bid(&mut auction, accounts.bob, 0, None));
+            bid(&mut auction, accounts.bob, 1, None).unwrap();
+        }
+
+        #[ink::test]
+        fn place_bid_keeps_failed_refund_escrowed() {
+            let accounts = test_utils::default_accounts();
+            let erc20 = MultiToken::new(token(), TokenStandard::Erc20);
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            bid(&mut auction, accounts.bob, 100, Some(1)).unwrap();
+
+            // the refund to bob fails, the new bid is recorded and bob's bid stays escrowed for him
+            mock_token::fail_transfers_to(Some(accounts.bob));
+            bid(&mut auction, accounts.charlie, 200, Some(1)).unwrap();
+            let outbid = auction.get_auction_by_pair(token(), accounts.alice).unwrap();
+            assert_eq!((accounts.charlie, 200), (outbid.bidder, outbid.gathered));
+            assert_eq!(Some(&300), auction.escrowed_bids.get(&token()));
+            assert_eq!(100, auction.pending_refund(token(), accounts.bob));
+            assert_eq!(900, mock_token::balance_of(erc20, accounts.bob));
+            assert_eq!(
+                Err(Error::TokenIsEscrowed),
+                auction.rescue_token(TokenStandard::Erc20, token(), None, Some(1), accounts.alice)
+            );
+
+            // bob recovers the refund once transfers to bob work again
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(Err(Error::Transfer), auction.withdraw_refund(token()));
+            mock_token::fail_transfers_to(None);
+            assert_eq!(Ok(100), auction.withdraw_refund(token()));
+            assert_eq!(Err(Error::NoPendingRefund), auction.withdraw_refund(token()));
+            ink_env::test::pop_execution_context();
+
+            assert_eq!(1_000, mock_token::balance_of(erc20, accounts.bob));
+            assert_eq!(0, auction.pending_refund(token(), accounts.bob));
+            assert_eq!(Some(&200), auction.escrowed_bids.get(&token()));
+        }
+
+        #[ink::test]
+        fn rescue_token_rescues_orphan_nft() {
+            let accounts = test_utils::default_accounts();
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            mock_token::set_owner(media(), 2, contract());
+
+            assert_eq!(
+                Err(Error::TokenIsEscrowed),
+                auction.rescue_token(TokenStandard::Erc721, media(), Some(1), None, accounts.eve)
+            );
+            auction.rescue_token(TokenStandard::Erc721, media(), Some(2), None, accounts.eve).unwrap();
+            assert_eq!(Some(contract()), mock_token::owner_of(media(), 1));
+            assert_eq!(Some(accounts.eve), mock_token::owner_of(media(), 2));
+
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(
+                Err(Error::OnlyOwnerAllowed),
+                auction.rescue_token(TokenStandard::Erc721, media(), Some(2), None, accounts.bob)
+            );
+            ink_env::test::pop_execution_context();
+        }
+
+        #[ink::test]
+        fn rescue_token_keeps_escrowed_bids() {
+            let accounts = test_utils::default_accounts();
+            let erc20 = MultiToken::new(token(), TokenStandard::Erc20);
+            let mut auction = new_auction();
+            auction.create_auction(create_request(AuctionKind::English)).unwrap();
+            bid(&mut auction, accounts.bob, 100, None).unwrap();
+            // 50 tokens were sent to the contract by mistake
+            mock_token::set_balance(token(), contract(), 150);
+
+            assert_eq!(
+                Err(Error::TokenIsEscrowed),
+                auction.rescue_token(TokenStandard::Erc20, token(), None, Some(51), accounts.eve)
+            );
+            auction.rescue_token(TokenStandard::Erc20, token(), None, Some(50), accounts.eve).unwrap();
+            assert_eq!(50, mock_token::balance_of(erc20, accounts.eve));
+            assert_eq!(100, mock_token::balance_of(erc20, contract()));
+            assert_eq!(
+                Err(Error::TokenIsEscrowed),
+                auction.rescue_token(TokenStandard::Erc20, token(), None, Some(1), accounts.eve)
+            );
+        }
     }
 }
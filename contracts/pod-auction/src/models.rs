@@ -34,8 +34,77 @@ pub struct AuctionModel {
     pub token_address: AccountId,
     /// IPFS hash
     pub ipfs_hash: Vec<u8>,
-    /// is the auction already withdrawn
-    pub withdrawn: bool,
+    /// where the auction is in its lifecycle
+    pub status: AuctionStatus,
+    /// how the price of the auction is determined
+    pub auction_kind: AuctionKind,
+    /// price of a Dutch auction at `start_time`
+    pub start_price: Balance,
+    /// price of a Dutch auction at `end_time`
+    pub end_price: Balance,
+    /// which opening bids an English auction accepts
+    pub opening_policy: OpeningPolicy,
+}
+
+impl AuctionModel {
+    /// Returns the price of a Dutch auction at `now`. The price decreases linearly from `start_price` at
+    /// `start_time` to `end_price` at `end_time`.
+    pub fn current_dutch_price(&self, now: u64) -> Balance {
+        if now <= self.start_time {
+            return self.start_price;
+        }
+        if now >= self.end_time {
+            return self.end_price;
+        }
+        let elapsed = Balance::from(now - self.start_time);
+        let duration = Balance::from(self.end_time - self.start_time);
+        let decrease = self.start_price.saturating_sub(self.end_price) * elapsed / duration;
+        self.start_price - decrease
+    }
+}
+
+/// How the price of an auction is determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, StorageLayout))]
+pub enum AuctionKind {
+    /// ascending bids, the highest bidder wins
+    English,
+    /// the price decreases over time, the first buyer wins
+    Dutch,
+}
+
+/// Which opening bids an English auction accepts. Later bids always have to outbid the highest bid by the increment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, StorageLayout))]
+pub enum OpeningPolicy {
+    /// the opening bid must be at least the reserve price
+    AtLeastReserve,
+    /// any positive opening bid is accepted
+    AnyAboveZero,
+}
+
+impl OpeningPolicy {
+    /// Returns true if `amount` is an acceptable opening bid for an auction with `reserve_price`
+    pub fn accepts(&self, amount: Balance, reserve_price: Balance) -> bool {
+        match self {
+            OpeningPolicy::AtLeastReserve => amount > 0 && amount >= reserve_price,
+            OpeningPolicy::AnyAboveZero => amount > 0,
+        }
+    }
+}
+
+/// The lifecycle of an auction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, SpreadLayout, PackedLayout)]
+#[cfg_attr(feature = "std", derive(::scale_info::TypeInfo, StorageLayout))]
+pub enum AuctionStatus {
+    /// accepting bids
+    Open,
+    /// the NFT went to the highest bidder and the bid to the owner
+    Settled,
+    /// the NFT went back to the owner and the bid back to the bidder
+    Cancelled,
+    /// reopened by the owner with new parameters, accepting bids
+    Reset,
 }
 
 /// The create Auction request
@@ -58,6 +127,14 @@ pub struct CreateAuctionRequest {
     pub reserve_price: Balance,
     /// IPFS hash
     pub ipfs_hash: Vec<u8>,
+    /// how the price of the auction is determined
+    pub auction_kind: AuctionKind,
+    /// price of a Dutch auction at `start_time`, ignored for English auctions
+    pub start_price: Balance,
+    /// price of a Dutch auction at `end_time`, ignored for English auctions
+    pub end_price: Balance,
+    /// which opening bids are accepted, ignored for Dutch auctions
+    pub opening_policy: OpeningPolicy,
 }
 
 /// The place a bid in auction request
@@ -70,6 +147,9 @@ pub struct PlaceBidRequest {
     pub owner: AccountId,
     /// amount to bid
     pub amount: Balance,
+    /// Optional nonce that makes the bid safe to retry. Nonces must strictly increase per bidder and auction, a bid
+    /// that repeats or goes below the bidder's last nonce is rejected with `DuplicateBid`.
+    pub nonce: Option<u64>,
 }
 
 /// The withdraw auction request
@@ -134,3 +214,54 @@ pub struct Transfer {
     pub to: AccountId,
     pub amount: Balance,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dutch_auction(start_price: Balance, end_price: Balance) -> AuctionModel {
+        AuctionModel {
+            owner: AccountId::from([0x1; 32]),
+            start_time: 1_000,
+            end_time: 11_000,
+            bid_increment: 0,
+            reserve_price: 0,
+            gathered: 0,
+            bidder: AccountId::from([0x0; 32]),
+            media_address: AccountId::from([0x2; 32]),
+            media_token_id: 1,
+            token_address: AccountId::from([0x3; 32]),
+            ipfs_hash: Vec::new(),
+            status: AuctionStatus::Open,
+            auction_kind: AuctionKind::Dutch,
+            start_price,
+            end_price,
+            opening_policy: OpeningPolicy::AtLeastReserve,
+        }
+    }
+
+    #[test]
+    fn current_dutch_price_at_start() {
+        let auction = dutch_auction(1_000, 0);
+        assert_eq!(1_000, auction.current_dutch_price(0));
+        assert_eq!(1_000, auction.current_dutch_price(1_000));
+    }
+
+    #[test]
+    fn current_dutch_price_decreases_linearly() {
+        let auction = dutch_auction(1_000, 0);
+        assert_eq!(900, auction.current_dutch_price(2_000));
+        assert_eq!(500, auction.current_dutch_price(6_000));
+        assert_eq!(1, auction.current_dutch_price(10_990));
+
+        let auction = dutch_auction(1_000, 100);
+        assert_eq!(550, auction.current_dutch_price(6_000));
+    }
+
+    #[test]
+    fn current_dutch_price_at_and_after_end() {
+        let auction = dutch_auction(1_000, 100);
+        assert_eq!(100, auction.current_dutch_price(11_000));
+        assert_eq!(100, auction.current_dutch_price(u64::MAX));
+    }
+}
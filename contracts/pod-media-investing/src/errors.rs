@@ -20,6 +20,39 @@ pub enum Error {
     #[error(display = "pod is not in investing state")]
     PodNotInInvestState,
 
+    #[error(display = "pod is not in trading state")]
+    PodNotInTradingState,
+
+    #[error(display = "pod is not in formation state")]
+    PodNotInFormationState,
+
+    #[error(display = "the AMM curve parameters are invalid")]
+    InvalidCurveParams,
+
+    #[error(display = "the AMM calculation overflowed")]
+    AmmOverflow,
+
+    #[error(display = "amount exceeds the supply released by the AMM")]
+    ExceedsReleasedSupply,
+
+    #[error(display = "insufficient pod token balance")]
+    InsufficientPodTokens,
+
+    #[error(display = "investment exceeds the per-investor cap")]
+    InvestorCapExceeded,
+
+    #[error(display = "the investor's total investment overflowed")]
+    InvestmentOverflow,
+
+    #[error(display = "the account is not on the pod's allowlist")]
+    NotAllowlisted,
+
+    #[error(display = "the unsold pod tokens were already reclaimed")]
+    UnsoldAlreadyReclaimed,
+
+    #[error(display = "the funding token and the pod token cannot be swept")]
+    CannotSweepPodAsset,
+
     #[error(display = "erc20 error: {}", _0)]
     Erc20(#[error(source)] erc20::Error),
 
@@ -31,6 +64,26 @@ pub enum Error {
 
     #[error(display = "media's release date must be in the future")]
     ReleaseDateMustBeInFuture,
+
+    #[error(display = "media has already been released")]
+    MediaAlreadyReleased,
+
+    #[error(display = "media's release can only be postponed")]
+    ReleaseCanOnlyBePostponed,
+}
+
+impl From<media::errors::RescheduleReleaseError> for Error {
+    fn from(error: media::errors::RescheduleReleaseError) -> Self {
+        use media::errors::RescheduleReleaseError::*;
+
+        match error {
+            ReleaseDateMustBeInFuture => Error::ReleaseDateMustBeInFuture,
+            Unauthorized => Error::Unauthorized,
+            MediaNotRegistered => Error::MediaNotRegistered,
+            MediaAlreadyReleased => Error::MediaAlreadyReleased,
+            ReleaseCanOnlyBePostponed => Error::ReleaseCanOnlyBePostponed,
+        }
+    }
 }
 
 /// Errors encountered during the validation of a `CreateInvestingPodRequest`.
@@ -45,4 +98,6 @@ pub enum InvestingPodValidationError {
     SpreadMustBeSmallerThanOne,
     #[error(display = "funding token price must be greater than zero")]
     FundingTokenPriceCannotBeZero,
+    #[error(display = "the AMM parameters must describe a curve whose price rises with the supply")]
+    InvalidAmmCurve,
 }
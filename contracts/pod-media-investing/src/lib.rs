@@ -9,15 +9,51 @@ pub mod models;
 mod pod_media_investing {
     use crate::{
         errors::Error,
-        models::{CreateInvestingPodRequest, InvestingPodState, InvestingPodStatus, RegisterMediaRequest},
+        models::{
+            CreateInvestingPodRequest, CreateMediaRequest, InvestingPodState, InvestingPodStatus, RegisterMediaRequest,
+        },
     };
+    use amm::Rounding;
     use contract_utils::AccountIdExt;
     use erc20::Erc20;
-    use ink_storage::collections::Vec as StorageVec;
-    use media::{models::MediaId, MediaStorage};
+    use ink_env::call::{FromAccountId, ToAccountId};
+    use ink_prelude::{vec, vec::Vec};
+    use ink_storage::collections::{HashMap as StorageHashMap, Vec as StorageVec};
+    use media::{
+        models::{Media, MediaId},
+        MediaStorage,
+    };
+    use pod_registry::PodKind;
 
     type Result<T> = core::result::Result<T, Error>;
 
+    /// Emitted when the creator reclaims the pod tokens that were not sold during the investing period.
+    #[ink(event)]
+    pub struct UnsoldReclaimed {
+        #[ink(topic)]
+        creator: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted when the creator sweeps a token that was sent to the pod by mistake.
+    #[ink(event)]
+    pub struct ForeignTokenSwept {
+        #[ink(topic)]
+        token: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted when the release of a media is postponed.
+    #[ink(event)]
+    pub struct ReleaseRescheduled {
+        #[ink(topic)]
+        media_id: MediaId,
+        previous_date: Timestamp,
+        new_date: Timestamp,
+    }
+
     /// An InvestingPod is a media pod which goes through three states:
     ///
     /// `Formation`: The creator uploads and registers media.
@@ -41,6 +77,16 @@ mod pod_media_investing {
         media_ids: StorageVec<MediaId>,
         state: InvestingPodState,
         media: MediaStorage,
+        max_per_investor: Option<Balance>,
+        /// Funding tokens invested so far by each account.
+        investments: StorageHashMap<AccountId, Balance>,
+        /// When enabled, only accounts on the allowlist may invest.
+        allowlist_enabled: bool,
+        allowlist: StorageHashMap<AccountId, ()>,
+        /// Pod tokens sold by `invest_pod` during the investing period.
+        supply_invested: Balance,
+        /// Whether the creator reclaimed the pod tokens that were not sold during the investing period.
+        unsold_reclaimed: bool,
     }
 
     impl InvestingPod {
@@ -79,6 +125,10 @@ mod pod_media_investing {
                 })
                 .collect();
 
+            if let Some(mut registry) = request.registry {
+                registry.register_pod(caller, pod_address, PodKind::Investing).expect("registering pod");
+            }
+
             Self {
                 creator: caller,
                 created_at: Self::env().block_timestamp(),
@@ -87,6 +137,12 @@ mod pod_media_investing {
                 funding_token_price: request.funding_token_price,
                 funding_target: request.funding_target,
                 funding_date: request.funding_date,
+                max_per_investor: request.max_per_investor,
+                investments: Default::default(),
+                allowlist_enabled: false,
+                allowlist: Default::default(),
+                supply_invested: 0,
+                unsold_reclaimed: false,
                 max_price: request.max_price,
                 max_supply: request.max_supply,
                 amm_curve: request.amm,
@@ -160,8 +216,26 @@ mod pod_media_investing {
             Ok(())
         }
 
+        /// Postpones the release of a registered media to `new_date`.
+        ///
+        /// # Restrictions
+        ///
+        /// * May only be called by the media creator.
+        /// * The media may not have been released yet.
+        /// * `new_date` must be later than the current release date.
+        #[ink(message)]
+        pub fn reschedule_release(&mut self, media_id: MediaId, new_date: Timestamp) -> Result<()> {
+            let mut media: Media = self.media.get_media(media_id).ok_or(Error::MediaNotFound)?.into();
+            let previous_date =
+                media.reschedule_release(self.env().caller(), self.env().block_timestamp(), new_date)?;
+            self.media.update_media(media)?;
+            self.env().emit_event(ReleaseRescheduled { media_id, previous_date, new_date });
+            Ok(())
+        }
+
         /// Purchases tokens from the pod for the funding price. Once the pods reaches the funding
-        /// target, it will transition to trading state.
+        /// target, it will transition to trading state. The total invested by the caller may not exceed
+        /// `max_per_investor`, if set. While the allowlist is enabled, only allowlisted accounts may invest.
         #[ink(message)]
         pub fn invest_pod(&mut self, amount: Balance) -> Result<()> {
             let caller = self.env().caller();
@@ -176,15 +250,26 @@ mod pod_media_investing {
                 return Err(Error::PodNotInInvestState);
             }
 
+            if self.allowlist_enabled && !self.allowlist.contains_key(&caller) {
+                return Err(Error::NotAllowlisted);
+            }
+
             // this should never panic since both funding_target and state.raised_funds are checked
             // before by the contract.
             let remaining = self.funding_target.checked_sub(self.state.raised_funds).unwrap();
             let amount = core::cmp::min(remaining, amount);
             let amount_pod_tokens = amount / self.funding_token_price;
 
+            let invested = self.invested(caller).checked_add(amount).ok_or(Error::InvestmentOverflow)?;
+            if matches!(self.max_per_investor, Some(cap) if invested > cap) {
+                return Err(Error::InvestorCapExceeded);
+            }
+
             self.funding_token.transfer_from(caller, contract_account_id, amount)?;
             self.pod_token.transfer(caller, amount_pod_tokens)?;
             self.state.raised_funds += amount;
+            self.supply_invested += amount_pod_tokens;
+            self.investments.insert(caller, invested);
 
             if self.state.raised_funds >= self.funding_target {
                 self.state.status = InvestingPodStatus::Trading
@@ -198,11 +283,7 @@ mod pod_media_investing {
         pub fn buy_pod_tokens(&mut self, amount: Balance) -> Result<()> {
             let caller = self.env().caller();
             let contract_account_id = self.env().account_id();
-            let amm = self.amm();
-
-            // this will panic for ridiculous numbers (Balance::MAX for example). The caller should
-            // never be passing in those numbers.
-            let charged_amount = amm.buy(self.state.supply_released, amount).unwrap();
+            let charged_amount = self.quote_buy(amount)?;
 
             // Balance should always be convertible to u128.
             self.funding_token.transfer_from(caller, contract_account_id, charged_amount)?;
@@ -211,31 +292,267 @@ mod pod_media_investing {
             Ok(())
         }
 
-        /// Sells pod tokens using the pod's AMM.
+        /// Sells pod tokens using the pod's AMM. At most the supply released by the AMM can be sold, and the caller
+        /// must hold the tokens.
         #[ink(message)]
         pub fn sell_pod_tokens(&mut self, amount: Balance) -> Result<()> {
             let caller = self.env().caller();
-            let amm = self.amm();
-
-            // this will panic for ridiculous numbers (Balance::MAX for example). The caller should
-            // never be passing in those numbers.
-            let charged_amount = amm.sell(self.state.supply_released, amount).unwrap();
+            let supply_released = self.state.supply_released.checked_sub(amount).ok_or(Error::ExceedsReleasedSupply)?;
+            if self.pod_token.balance_of(caller) < amount {
+                return Err(Error::InsufficientPodTokens);
+            }
+            let charged_amount = self.quote_sell(amount)?;
             self.pod_token.burn_from(caller, amount)?;
 
             // Balance should always be convertible to u128.
             self.funding_token.transfer(caller, charged_amount)?;
-            self.state.supply_released = self.state.supply_released.checked_sub(amount).unwrap();
+            self.state.supply_released = supply_released;
             Ok(())
         }
 
+        /// The current spot price of a pod token on the pod's AMM.
+        #[ink(message)]
+        pub fn market_price(&self) -> Result<Balance> {
+            self.amm().market_price(self.state.supply_released).ok_or(Error::AmmOverflow)
+        }
+
+        /// The funding tokens `buy_pod_tokens(amount)` would currently charge.
+        #[ink(message)]
+        pub fn quote_buy(&self, amount: Balance) -> Result<Balance> {
+            self.amm().buy_rounded(self.state.supply_released, amount, Rounding::TowardPool).ok_or(Error::AmmOverflow)
+        }
+
+        /// The funding tokens `sell_pod_tokens(amount)` would currently pay out.
+        #[ink(message)]
+        pub fn quote_sell(&self, amount: Balance) -> Result<Balance> {
+            self.amm().sell_rounded(self.state.supply_released, amount, Rounding::TowardPool).ok_or(Error::AmmOverflow)
+        }
+
         /// AccountId of the pod creator.
         #[ink(message)]
         pub fn creator(&self) -> AccountId { self.creator }
 
+        /// Returns `(raised_funds, funding_target, percent)`, where `percent` is the share of the funding target
+        /// that has been raised, capped at 100.
+        #[ink(message)]
+        pub fn funding_progress(&self) -> (Balance, Balance, u8) {
+            let raised_funds = self.state.raised_funds;
+            (raised_funds, self.funding_target, funding_percent(raised_funds, self.funding_target))
+        }
+
+        /// Transfers the pod tokens that were not sold during the investing period to the creator and returns the
+        /// amount. Pod tokens sent to the pod by other accounts are not part of the unsold supply. May only be called
+        /// once by the pod creator once the pod is trading.
+        #[ink(message)]
+        pub fn reclaim_unsold(&mut self) -> Result<Balance> {
+            self.ensure_creator()?;
+            if !self.state.status.is_trading() {
+                return Err(Error::PodNotInTradingState);
+            }
+            if self.unsold_reclaimed {
+                return Err(Error::UnsoldAlreadyReclaimed);
+            }
+
+            let amount = unsold_supply(self.funding_target, self.funding_token_price, self.supply_invested);
+            if amount > 0 {
+                self.pod_token.transfer(self.creator, amount)?;
+            }
+            self.unsold_reclaimed = true;
+            self.env().emit_event(UnsoldReclaimed { creator: self.creator, amount });
+            Ok(amount)
+        }
+
+        /// Transfers the pod's full balance of `token` to `to`, to recover ERC-20 tokens that were sent to the pod by
+        /// mistake. The funding token and the pod token can't be swept. May only be called by the pod creator.
+        #[ink(message)]
+        pub fn sweep_foreign_token(&mut self, token: AccountId, to: AccountId) -> Result<()> {
+            self.ensure_creator()?;
+            if token == ToAccountId::to_account_id(&self.funding_token)
+                || token == ToAccountId::to_account_id(&self.pod_token)
+            {
+                return Err(Error::CannotSweepPodAsset);
+            }
+
+            let mut foreign_token: Erc20 = FromAccountId::from_account_id(token);
+            let amount = foreign_token.balance_of(self.env().account_id());
+            if amount > 0 {
+                foreign_token.transfer(to, amount)?;
+            }
+            self.env().emit_event(ForeignTokenSwept { token, to, amount });
+            Ok(())
+        }
+
+        /// Lists the accounts known to the pod that hold a role on the pod token, with their raw role bits (see
+        /// `erc20::Role`). Known accounts are the creator and the pod itself, which mints pod tokens through the AMM.
+        #[ink(message)]
+        pub fn token_admins(&self) -> Vec<(AccountId, u8)> {
+            let mut accounts = vec![self.creator, self.env().account_id()];
+            accounts.dedup();
+            accounts
+                .into_iter()
+                .map(|account| (account, self.pod_token.roles_of(account)))
+                .filter(|(_, roles)| *roles != 0)
+                .collect()
+        }
+
+        /// Grants the Minter role on the pod token to `account`, so auxiliary contracts can mint pod tokens, e.g.
+        /// through `MultiToken::mint`. May only be called by the pod creator.
+        #[ink(message)]
+        pub fn grant_minter_to(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_creator()?;
+            self.pod_token.add_minter(account)?;
+            Ok(())
+        }
+
+        /// The address of the pod token.
+        #[ink(message)]
+        pub fn pod_token(&self) -> AccountId { ToAccountId::to_account_id(&self.pod_token) }
+
+        /// Adds `investor` to the allowlist. May only be called by the pod creator.
+        #[ink(message)]
+        pub fn add_investor(&mut self, investor: AccountId) -> Result<()> {
+            self.ensure_creator()?;
+            self.allowlist.insert(investor, ());
+            Ok(())
+        }
+
+        /// Removes `investor` from the allowlist. May only be called by the pod creator.
+        #[ink(message)]
+        pub fn remove_investor(&mut self, investor: AccountId) -> Result<()> {
+            self.ensure_creator()?;
+            self.allowlist.take(&investor);
+            Ok(())
+        }
+
+        /// Enables or disables restricting investments to the allowlist. May only be called by the pod creator.
+        #[ink(message)]
+        pub fn set_allowlist_enabled(&mut self, enabled: bool) -> Result<()> {
+            self.ensure_creator()?;
+            self.allowlist_enabled = enabled;
+            Ok(())
+        }
+
+        /// Returns whether `investor` may invest in the pod.
+        #[ink(message)]
+        pub fn is_allowlisted(&self, investor: AccountId) -> bool {
+            !self.allowlist_enabled || self.allowlist.contains_key(&investor)
+        }
+
+        /// Replaces the parameters of the AMM used once the pod is trading. May only be called by the pod creator
+        /// while the pod is in `Formation`. Fails with `InvalidCurveParams` if no AMM can be built from them, or if the
+        /// AMM would have a flat price.
+        #[ink(message)]
+        pub fn update_curve(&mut self, max_price: Balance, max_supply: Balance, curve: amm::Curve) -> Result<()> {
+            self.ensure_creator()?;
+            if !self.state.status.is_formation() {
+                return Err(Error::PodNotInFormationState);
+            }
+            let amm = amm::Amm::new(curve, self.funding_token_price, max_price, max_supply, amm::DEFAULT_PRECISION);
+            if !amm.map_or(false, |amm| amm.is_well_formed()) {
+                return Err(Error::InvalidCurveParams);
+            }
+            self.max_price = max_price;
+            self.max_supply = max_supply;
+            self.amm_curve = curve;
+            Ok(())
+        }
+
+        /// Creates `medias` and adds them to the pod, returning their ids. Each added media has to be registered
+        /// before the pod moves on to `Investing`.
+        ///
+        /// # Restrictions
+        ///
+        /// * May only be called by the pod creator.
+        /// * The pod must be in `Formation`.
+        #[ink(message)]
+        pub fn add_media(&mut self, medias: Vec<CreateMediaRequest>) -> Result<Vec<MediaId>> {
+            self.ensure_creator()?;
+            if !self.state.status.is_formation() {
+                return Err(Error::PodNotInFormationState);
+            }
+
+            let (caller, pod_address) = (self.env().caller(), self.env().account_id());
+            let mut media_ids = Vec::with_capacity(medias.len());
+            for media in medias {
+                let media_id = self.media.create_media(media.into_media_request(caller, pod_address))?;
+                self.media_ids.push(media_id);
+                media_ids.push(media_id);
+            }
+            self.state.total_media = self.media_ids.len();
+            Ok(media_ids)
+        }
+
+        /// Funding tokens invested so far by `investor`.
+        #[ink(message)]
+        pub fn invested(&self, investor: AccountId) -> Balance { self.investments.get(&investor).copied().unwrap_or(0) }
+
+        /// The current state of the pod.
+        #[ink(message)]
+        pub fn pod_state(&self) -> InvestingPodState { self.state.clone() }
+
+        fn ensure_creator(&self) -> Result<()> {
+            if self.env().caller() != self.creator {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+
         pub fn amm(&self) -> amm::Amm {
-            // When pod parameters are set, check should be made so that the Amm is in a correct state.
+            // The constructor and `update_curve` make sure a well-formed Amm can be built from the parameters.
             // later we will move to a dedicated AMM contract which ensures this even more.
-            amm::Amm::new(self.amm_curve, self.funding_token_price, self.max_price, self.max_supply).unwrap()
+            amm::Amm::new(
+                self.amm_curve,
+                self.funding_token_price,
+                self.max_price,
+                self.max_supply,
+                amm::DEFAULT_PRECISION,
+            )
+            .unwrap()
+        }
+    }
+
+    /// Percentage of `funding_target` covered by `raised_funds`, capped at 100. A zero target counts as fully
+    /// funded.
+    fn funding_percent(raised_funds: Balance, funding_target: Balance) -> u8 {
+        if funding_target == 0 {
+            return 100;
+        }
+        let percent = raised_funds.checked_mul(100).map_or(100, |x| x / funding_target);
+        core::cmp::min(percent, 100) as u8
+    }
+
+    /// Pod tokens minted to the pod for the investing period, `funding_target / funding_token_price`, that were not
+    /// sold, given that `supply_invested` were sold.
+    fn unsold_supply(funding_target: Balance, funding_token_price: Balance, supply_invested: Balance) -> Balance {
+        (funding_target / funding_token_price).saturating_sub(supply_invested)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn funding_percent_works() {
+            assert_eq!(0, funding_percent(0, 1_000));
+            assert_eq!(0, funding_percent(9, 1_000));
+            assert_eq!(25, funding_percent(250, 1_000));
+            assert_eq!(100, funding_percent(1_000, 1_000));
+        }
+
+        #[test]
+        fn funding_percent_is_capped() {
+            assert_eq!(100, funding_percent(1_001, 1_000));
+            assert_eq!(100, funding_percent(Balance::MAX, 1_000));
+            assert_eq!(100, funding_percent(0, 0));
+        }
+
+        #[test]
+        fn unsold_supply_works() {
+            assert_eq!(100, unsold_supply(28_000, 280, 0));
+            assert_eq!(1, unsold_supply(28_000, 280, 99));
+            assert_eq!(0, unsold_supply(28_000, 280, 100));
+            // more can't have been sold than was minted, but the unsold supply never goes negative
+            assert_eq!(0, unsold_supply(28_000, 280, 101));
         }
     }
 }
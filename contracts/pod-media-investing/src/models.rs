@@ -3,6 +3,7 @@ use erc20::Erc20;
 use ink_prelude::{string::String, vec::Vec};
 use ink_storage::traits::{PackedLayout, SpreadLayout};
 use media::MediaStorage;
+use pod_registry::PodRegistry;
 
 use crate::errors::InvestingPodValidationError;
 use num_traits::Zero;
@@ -36,6 +37,8 @@ pub struct CreateInvestingPodRequest {
     pub max_supply: Balance,
     /// Date after which the funding period of a pod closes, regardless of reaching the funding goal.
     pub funding_date: Timestamp,
+    /// Maximum amount of funding tokens a single account may invest. `None` means no limit.
+    pub max_per_investor: Option<Balance>,
     /// Hash uses of the erc20 contract deployed for the pod token. Caller must ensure that the wasm
     /// for the contract has already been uploaded.
     pub erc20_code_hash: Hash,
@@ -43,6 +46,8 @@ pub struct CreateInvestingPodRequest {
     pub media_contract: MediaStorage,
     /// Medias to be created upon pod instantiation.
     pub medias: Vec<CreateMediaRequest>,
+    /// Registry the pod registers itself with upon instantiation, if any. The creator then confirms it there.
+    pub registry: Option<PodRegistry>,
 }
 
 impl CreateInvestingPodRequest {
@@ -64,6 +69,12 @@ impl CreateInvestingPodRequest {
         if self.funding_token_price.is_zero() {
             return Err(FundingTokenPriceCannotBeZero);
         }
+
+        let amm =
+            amm::Amm::new(self.amm, self.funding_token_price, self.max_price, self.max_supply, amm::DEFAULT_PRECISION);
+        if !amm.map_or(false, |amm| amm.is_well_formed()) {
+            return Err(InvalidAmmCurve);
+        }
         Ok(())
     }
 }
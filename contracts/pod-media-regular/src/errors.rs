@@ -22,4 +22,24 @@ pub enum Error {
 
     #[error(display = "media's release date must be in the future")]
     ReleaseDateMustBeInFuture,
+
+    #[error(display = "media has already been released")]
+    MediaAlreadyReleased,
+
+    #[error(display = "media's release can only be postponed")]
+    ReleaseCanOnlyBePostponed,
+}
+
+impl From<media::errors::RescheduleReleaseError> for Error {
+    fn from(error: media::errors::RescheduleReleaseError) -> Self {
+        use media::errors::RescheduleReleaseError::*;
+
+        match error {
+            ReleaseDateMustBeInFuture => Error::ReleaseDateMustBeInFuture,
+            Unauthorized => Error::Unauthorized,
+            MediaNotRegistered => Error::MediaNotRegistered,
+            MediaAlreadyReleased => Error::MediaAlreadyReleased,
+            ReleaseCanOnlyBePostponed => Error::ReleaseCanOnlyBePostponed,
+        }
+    }
 }
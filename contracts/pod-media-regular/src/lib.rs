@@ -7,22 +7,41 @@ pub mod models;
 
 #[ink::contract]
 mod pod_media_investing {
-    use crate::models::{CreatePodRequest, RegisterMediaRequest};
+    use crate::models::{CreateMediaRequest, CreatePodRequest, RegisterMediaRequest};
 
     use crate::errors::Error;
 
+    use ink_prelude::vec::Vec;
     use media::models::MediaId;
+    use pod_registry::PodKind;
 
     cfg_if::cfg_if! {
         if #[cfg(not(feature = "ink-as-dependency"))] {
             use ink_storage::collections::Vec as StorageVec;
-            use media::MediaStorage;
+            use media::{models::Media, MediaStorage};
             use crate::models::PodState;
         }
     }
 
     type Result<T> = core::result::Result<T, Error>;
 
+    /// Emitted when the pod is created, linking the pod to its creator and media.
+    #[ink(event)]
+    pub struct PodCreated {
+        #[ink(topic)]
+        creator: AccountId,
+        media_ids: Vec<MediaId>,
+    }
+
+    /// Emitted when the release of a media is postponed.
+    #[ink(event)]
+    pub struct ReleaseRescheduled {
+        #[ink(topic)]
+        media_id: MediaId,
+        previous_date: Timestamp,
+        new_date: Timestamp,
+    }
+
     /// Defines the storage of your contract.
     /// Add new fields to the below struct in order
     /// to add new static storage fields to your contract.
@@ -45,7 +64,7 @@ mod pod_media_investing {
 
             let mut media_contract = request.media_contract;
 
-            let media_ids: StorageVec<u64> = request
+            let media_ids: Vec<MediaId> = request
                 .medias
                 .into_iter()
                 .map(|media| {
@@ -54,6 +73,13 @@ mod pod_media_investing {
                 })
                 .collect();
 
+            if let Some(mut registry) = request.registry {
+                registry.register_pod(caller, pod_address, PodKind::Regular).expect("registering pod");
+            }
+
+            Self::env().emit_event(PodCreated { creator: caller, media_ids: media_ids.clone() });
+            let media_ids: StorageVec<MediaId> = media_ids.into_iter().collect();
+
             Self {
                 creator: Self::env().caller(),
                 media: media_contract,
@@ -119,6 +145,46 @@ mod pod_media_investing {
             Ok(())
         }
 
+        /// Postpones the release of a registered media to `new_date`.
+        ///
+        /// # Restrictions
+        ///
+        /// * May only be called by the media creator.
+        /// * The media may not have been released yet.
+        /// * `new_date` must be later than the current release date.
+        #[ink(message)]
+        pub fn reschedule_release(&mut self, media_id: MediaId, new_date: Timestamp) -> Result<()> {
+            let mut media: Media = self.media.get_media(media_id).ok_or(Error::MediaNotFound)?.into();
+            let previous_date =
+                media.reschedule_release(self.env().caller(), self.env().block_timestamp(), new_date)?;
+            self.media.update_media(media)?;
+            self.env().emit_event(ReleaseRescheduled { media_id, previous_date, new_date });
+            Ok(())
+        }
+
+        /// Creates `medias` and adds them to the pod, returning their ids.
+        ///
+        /// # Restrictions
+        ///
+        /// * May only be called by the pod creator.
+        #[ink(message)]
+        pub fn add_media(&mut self, medias: Vec<CreateMediaRequest>) -> Result<Vec<MediaId>> {
+            let caller = self.env().caller();
+            if caller != self.creator {
+                return Err(Error::Unauthorized);
+            }
+
+            let pod_address = self.env().account_id();
+            let mut media_ids = Vec::with_capacity(medias.len());
+            for media in medias {
+                let media_id = self.media.create_media(media.into_media_request(caller, pod_address))?;
+                self.media_ids.push(media_id);
+                media_ids.push(media_id);
+            }
+            self.state.total_media = self.media_ids.len();
+            Ok(media_ids)
+        }
+
         /// AccountId of the pod creator.
         #[ink(message)]
         pub fn creator(&self) -> AccountId { self.creator }
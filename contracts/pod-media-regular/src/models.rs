@@ -4,6 +4,7 @@ use media::{
     models::{CollabShare, MediaId, MediaType, NftInfo, ViewInfo, ViewingType},
     MediaStorage,
 };
+use pod_registry::PodRegistry;
 
 
 pub type Balance = <ink_env::DefaultEnvironment as ink_env::Environment>::Balance;
@@ -18,6 +19,8 @@ pub struct CreatePodRequest {
     pub endowment: Balance,
     pub media_contract: MediaStorage,
     pub medias: Vec<CreateMediaRequest>,
+    /// Registry the pod registers itself with, if any. The creator then confirms it there.
+    pub registry: Option<PodRegistry>,
 }
 
 pub type Collabs = BTreeMap<AccountId, CollabShare>;
@@ -0,0 +1,234 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(unused_must_use)]
+
+pub use contract::PodRegistry;
+
+use ink_lang as ink;
+use ink_storage::traits::{PackedLayout, SpreadLayout};
+use scale::{Decode, Encode};
+
+/// Error types
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Encode, Decode, err_derive::Error)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Error {
+    /// Only the pod itself or the owner may register a pod
+    #[error(display = "Only the pod itself or the owner may register a pod")]
+    Unauthorized,
+    /// The pod has already been registered
+    #[error(display = "The pod has already been registered")]
+    PodAlreadyRegistered,
+    /// The pod is not waiting for its creator to confirm it
+    #[error(display = "The pod is not waiting for its creator to confirm it")]
+    PodNotPending,
+    /// The creator already has the maximum number of pods
+    #[error(display = "The creator already has the maximum number of pods")]
+    TooManyPods,
+}
+
+/// The result type for this contract
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// The kind of pod contract that was registered
+#[derive(Debug, Encode, Decode, SpreadLayout, PackedLayout, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+pub enum PodKind {
+    /// A `pod-media-regular` contract
+    Regular,
+    /// A `pod-media-investing` contract
+    Investing,
+}
+
+#[ink::contract]
+mod contract {
+    use super::*;
+    use ink_prelude::vec::Vec;
+
+    #[cfg(not(feature = "ink-as-dependency"))]
+    use ink_storage::{collections::HashMap, lazy::Lazy};
+
+    /// The maximum number of pods registered for a single creator
+    pub const MAX_PODS_PER_CREATOR: usize = 100;
+
+    /// Event emitted when `register_pod` is successful
+    #[ink(event)]
+    pub struct PodRegistered {
+        #[ink(topic)]
+        creator: AccountId,
+        #[ink(topic)]
+        pod_account: AccountId,
+        kind: PodKind,
+    }
+
+    /// Index of pods by creator
+    #[ink(storage)]
+    pub struct PodRegistry {
+        /// Pod accounts by creator, in registration order
+        pods_by_creator: HashMap<AccountId, Vec<AccountId>>,
+        /// The kind of every registered pod
+        kinds: HashMap<AccountId, PodKind>,
+        /// Pods that registered themselves, waiting for the creator they named to confirm them
+        pending: HashMap<AccountId, (AccountId, PodKind)>,
+        /// The owner of the contract
+        owner: Lazy<AccountId>,
+    }
+
+    impl PodRegistry {
+        /// Creates a new instance
+        #[ink(constructor)]
+        #[allow(clippy::new_without_default)]
+        pub fn new() -> Self {
+            Self {
+                pods_by_creator: Default::default(),
+                kinds: Default::default(),
+                pending: Default::default(),
+                owner: Lazy::new(Self::env().caller()),
+            }
+        }
+
+        /// Registers `pod_account` as a pod of `creator`. Pods call this from their constructor, so the caller must
+        /// be the pod itself, and the pod is only listed for `creator` once `creator` confirms it with `confirm_pod`.
+        /// The owner may also register pods that were deployed before the registry existed, which are listed right
+        /// away.
+        #[ink(message)]
+        pub fn register_pod(&mut self, creator: AccountId, pod_account: AccountId, kind: PodKind) -> Result<()> {
+            let caller = self.env().caller();
+            if caller != pod_account && caller != *self.owner {
+                return Err(Error::Unauthorized);
+            }
+            if self.kinds.contains_key(&pod_account) || self.pending.contains_key(&pod_account) {
+                return Err(Error::PodAlreadyRegistered);
+            }
+            if caller == *self.owner {
+                return self.add_pod(creator, pod_account, kind);
+            }
+            self.pending.insert(pod_account, (creator, kind));
+            Ok(())
+        }
+
+        /// Confirms that the caller created `pod_account`, which registered itself for the caller, and lists it.
+        #[ink(message)]
+        pub fn confirm_pod(&mut self, pod_account: AccountId) -> Result<()> {
+            let (creator, kind) = *self.pending.get(&pod_account).ok_or(Error::PodNotPending)?;
+            if self.env().caller() != creator {
+                return Err(Error::Unauthorized);
+            }
+            self.add_pod(creator, pod_account, kind)?;
+            self.pending.take(&pod_account);
+            Ok(())
+        }
+
+        /// Returns the creator `pod_account` registered itself for, while the creator has not confirmed it
+        #[ink(message)]
+        pub fn pending_creator(&self, pod_account: AccountId) -> Option<AccountId> {
+            self.pending.get(&pod_account).map(|(creator, _)| *creator)
+        }
+
+        /// Returns the pods registered for `creator`, in registration order
+        #[ink(message)]
+        pub fn pods_by_creator(&self, creator: AccountId) -> Vec<AccountId> {
+            self.pods_by_creator.get(&creator).cloned().unwrap_or_default()
+        }
+
+        /// Returns the kind of `pod_account`, or `None` if it is not registered
+        #[ink(message)]
+        pub fn pod_kind(&self, pod_account: AccountId) -> Option<PodKind> { self.kinds.get(&pod_account).copied() }
+
+        /// Lists `pod_account` as a pod of `creator`
+        fn add_pod(&mut self, creator: AccountId, pod_account: AccountId, kind: PodKind) -> Result<()> {
+            let pods = self.pods_by_creator.entry(creator).or_insert_with(Vec::new);
+            if pods.len() >= MAX_PODS_PER_CREATOR {
+                return Err(Error::TooManyPods);
+            }
+            pods.push(pod_account);
+            self.kinds.insert(pod_account, kind);
+            self.env().emit_event(PodRegistered { creator, pod_account, kind });
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use contract_utils::test_utils;
+        use ink_prelude::vec;
+
+        #[ink::test]
+        fn test_register_pods() {
+            let mut registry = PodRegistry::new();
+            let accounts = test_utils::default_accounts();
+
+            // register two pods for the same creator
+            registry.register_pod(accounts.bob, accounts.charlie, PodKind::Regular).unwrap();
+            registry.register_pod(accounts.bob, accounts.django, PodKind::Investing).unwrap();
+            assert_eq!(test_utils::recorded_event_count(), 2);
+            assert_eq!(registry.pods_by_creator(accounts.bob), vec![accounts.charlie, accounts.django]);
+            assert_eq!(registry.pod_kind(accounts.django), Some(PodKind::Investing));
+            assert!(registry.pods_by_creator(accounts.eve).is_empty());
+
+            // a pod can only be registered once
+            assert_eq!(
+                registry.register_pod(accounts.eve, accounts.charlie, PodKind::Regular),
+                Err(Error::PodAlreadyRegistered)
+            );
+
+            // a pod may register itself, but not other pods
+            test_utils::set_caller(accounts.eve);
+            registry.register_pod(accounts.bob, accounts.eve, PodKind::Regular).unwrap();
+            assert_eq!(registry.pending_creator(accounts.eve), Some(accounts.bob));
+            assert_eq!(registry.register_pod(accounts.bob, accounts.frank, PodKind::Regular), Err(Error::Unauthorized));
+            ink_env::test::pop_execution_context();
+
+            // the pod is listed once bob confirms it
+            assert_eq!(registry.pods_by_creator(accounts.bob), vec![accounts.charlie, accounts.django]);
+            test_utils::set_caller(accounts.bob);
+            registry.confirm_pod(accounts.eve).unwrap();
+            ink_env::test::pop_execution_context();
+            assert_eq!(registry.pods_by_creator(accounts.bob), vec![accounts.charlie, accounts.django, accounts.eve]);
+            assert_eq!(registry.pending_creator(accounts.eve), None);
+            assert_eq!(registry.pod_kind(accounts.eve), Some(PodKind::Regular));
+        }
+
+        #[ink::test]
+        fn test_pod_cannot_spoof_creator() {
+            let mut registry = PodRegistry::new();
+            let accounts = test_utils::default_accounts();
+
+            // eve's pod claims to be bob's
+            test_utils::set_caller(accounts.eve);
+            registry.register_pod(accounts.bob, accounts.eve, PodKind::Regular).unwrap();
+            // registering it again, even for another creator, fails
+            assert_eq!(
+                registry.register_pod(accounts.eve, accounts.eve, PodKind::Regular),
+                Err(Error::PodAlreadyRegistered)
+            );
+            // only bob can confirm it
+            assert_eq!(registry.confirm_pod(accounts.eve), Err(Error::Unauthorized));
+            ink_env::test::pop_execution_context();
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(registry.confirm_pod(accounts.eve), Err(Error::Unauthorized));
+            assert_eq!(registry.confirm_pod(accounts.frank), Err(Error::PodNotPending));
+            ink_env::test::pop_execution_context();
+
+            assert!(registry.pods_by_creator(accounts.bob).is_empty());
+            assert_eq!(registry.pod_kind(accounts.eve), None);
+            assert_eq!(test_utils::recorded_event_count(), 0);
+        }
+
+        #[ink::test]
+        fn test_pods_per_creator_are_capped() {
+            let mut registry = PodRegistry::new();
+            let accounts = test_utils::default_accounts();
+
+            for i in 0..MAX_PODS_PER_CREATOR {
+                let pod_account = AccountId::from([i as u8 + 1; 32]);
+                registry.register_pod(accounts.bob, pod_account, PodKind::Regular).unwrap();
+            }
+            assert_eq!(registry.pods_by_creator(accounts.bob).len(), MAX_PODS_PER_CREATOR);
+            assert_eq!(
+                registry.register_pod(accounts.bob, AccountId::from([0xff; 32]), PodKind::Regular),
+                Err(Error::TooManyPods)
+            );
+            assert_eq!(registry.pod_kind(AccountId::from([0xff; 32])), None);
+        }
+    }
+}
@@ -55,6 +55,19 @@ pub enum UpdateOracleStateError {
     AuthzError(#[error(source)] OwnerError),
 }
 
+#[derive(Debug, Error, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum UpdateCollateralError {
+    #[error(display = "a collateral with the ticker is already accepted")]
+    AlreadyExists,
+    #[error(display = "collateral not found")]
+    DoesNotExist,
+    #[error(display = "the primary collateral cannot be removed")]
+    PrimaryCollateral,
+    #[error(display = "authorization error: {}", _0)]
+    AuthzError(#[error(source)] OwnerError),
+}
+
 #[derive(Debug, Error, PartialEq, Eq, scale::Encode, scale::Decode)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
 pub enum ConvertError {
@@ -72,6 +85,9 @@ pub enum ConvertError {
 
     #[error(display = "invalid price: price exceeded i128")]
     InvalidPrice,
+
+    #[error(display = "the conversion exceeds the limit of the current rate limit window")]
+    RateLimited,
 }
 
 impl ConvertError {
@@ -79,3 +95,38 @@ impl ConvertError {
         ConvertError::TokenNotFound { token: token.into() }
     }
 }
+
+#[derive(Debug, Error, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum EmergencyRedeemError {
+    #[error(display = "emergency mode is not active")]
+    NotActive,
+
+    #[error(display = "{}", _0)]
+    MathError(MathError),
+
+    #[error(display = "transfer error: {}", _0)]
+    Erc20(#[error(source)] erc20::Error),
+}
+
+impl EmergencyRedeemError {
+    pub fn math_error(msg: impl Into<String>) -> EmergencyRedeemError {
+        EmergencyRedeemError::MathError(MathError(msg.into()))
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum ConstructorError {
+    #[error(display = "ticker is not registered with the token-accounts contract: {}", ticker)]
+    TokenNotFound { ticker: String },
+
+    #[error(display = "ticker is registered, but not as an erc20 token: {}", ticker)]
+    InvalidTokenStandard { ticker: String },
+
+    #[error(display = "token {} is missing erc20 metadata: {}", ticker, reason)]
+    MissingMetadata { ticker: String, reason: String },
+
+    #[error(display = "token {} no longer matches the token-accounts registration", ticker)]
+    RegistrationMismatch { ticker: String },
+}
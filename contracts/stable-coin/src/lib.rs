@@ -1,5 +1,4 @@
 #![cfg_attr(not(feature = "std"), no_std)]
-#![feature(type_ascription)]
 
 mod errors;
 mod models;
@@ -10,7 +9,8 @@ use ink_lang as ink;
 mod stablecoin {
     use crate::{
         errors::{
-            GetPriceError, OracleError, OwnerError, RegisterOracleError, SubmitPriceError, UpdateOracleStateError,
+            GetPriceError, OracleError, OwnerError, RegisterOracleError, SubmitPriceError, UpdateCollateralError,
+            UpdateOracleStateError,
         },
         models::{
             Oracle, OracleState, PriceBucket, RegisterOracleRequest, SubmitPriceRequest, Ticker,
@@ -18,17 +18,21 @@ mod stablecoin {
         },
     };
 
-    use multi_token::MultiToken;
-    use token_accounts::TokenAccounts;
+    use contract_utils::TokenStandard;
+    use token_accounts::{Token, TokenAccounts};
 
     use crate::{
-        errors::ConvertError,
-        models::{ConvertRequest, TokenData, TokenSpec},
+        errors::{ConstructorError, ConvertError, EmergencyRedeemError},
+        models::{ConvertRequest, RateLimit, TokenData, TokenSpec},
     };
-    use ink_env::call::FromAccountId;
+    use ink_env::call::{FromAccountId, ToAccountId};
+    use ink_prelude::vec::Vec;
     use ink_storage::{collections::HashMap, Lazy};
     use rust_decimal::Decimal;
 
+    /// The canonical number of decimals of aggregated prices. Oracle prices are scaled to it before aggregation.
+    const PRICE_DECIMALS: u32 = contract_utils::constants::DECIMAL_COUNT;
+
     /// The Stablecoin smartcontract implements a simple swap between a collateral and stablecoin
     /// based on the burning and minting of the respective coins. Centralized oracles provide the
     /// data for the conversion.
@@ -37,15 +41,36 @@ mod stablecoin {
         owner: Lazy<AccountId>,
         prices: HashMap<Ticker, PriceBucket>,
         oracles: HashMap<AccountId, Oracle>,
+        /// The number of decimals each oracle submits prices in.
+        price_decimals: HashMap<AccountId, u8>,
 
         /// Erc20 contract account id of the stable coin. (pUSD).
         stable: Lazy<TokenSpec>,
 
         /// Erc20 contract account id of the collateral. (Privi).
         collateral: Lazy<TokenSpec>,
-    }
 
+        /// Collaterals accepted by `convert_to_pusd` in addition to `collateral`, by oracle ticker.
+        collaterals: HashMap<Ticker, TokenSpec>,
+
+        /// The token-accounts contract the tokens were resolved from. `None` if created through `new_raw`.
+        token_accounts: Lazy<Option<AccountId>>,
+
+        /// Collateral paid per stable coin in emergency mode, with `PRICE_DECIMALS` decimals. Emergency mode is
+        /// active if this is set.
+        emergency_rate: Lazy<Option<Balance>>,
 
+        /// Limits conversions per window of time. Conversions are not limited if this is not set.
+        rate_limit: Lazy<Option<RateLimit>>,
+        /// The time the current rate limit window started.
+        window_start: Lazy<Timestamp>,
+        /// The amount of stable coins converted in the current rate limit window.
+        window_total: Lazy<Balance>,
+
+        /// Whether `get_price` falls back to the most recently submitted price if the weighted average can't be
+        /// computed, e.g. because all volumes are zero.
+        allow_single_oracle_fallback: Lazy<bool>,
+    }
 
     /// Emitted when an oracle submits a new price. Contains the newest price state.
     #[ink(event)]
@@ -116,6 +141,23 @@ mod stablecoin {
         fn from(output: ConversionOutput) -> Self { Self { output } }
     }
 
+    /// Emitted when the contract owner activates or deactivates emergency mode.
+    #[ink(event)]
+    pub struct EmergencyModeUpdated {
+        output: EmergencyModeUpdatedOutput,
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct EmergencyModeUpdatedOutput {
+        /// The redemption rate, `None` if emergency mode was deactivated.
+        pub rate: Option<Balance>,
+    }
+
+    impl From<EmergencyModeUpdatedOutput> for EmergencyModeUpdated {
+        fn from(output: EmergencyModeUpdatedOutput) -> Self { Self { output } }
+    }
+
     impl Stablecoin {
         /// Constructs the contract. Note that it uses the token-accounts contract to determine the
         /// actual assets, which can thus be swapped by changing the assets in the token-accounts
@@ -127,20 +169,33 @@ mod stablecoin {
         /// with the token-accounts contract, and that this contract has mint and burn roles for both.
         #[ink(constructor)]
         pub fn new(stable: Ticker, collateral: Ticker, token_accounts: AccountId) -> Self {
-            let token_accounts: TokenAccounts = FromAccountId::from_account_id(token_accounts);
-            let stable =
-                TokenSpec::new(token_accounts.get_token(stable).map(MultiToken::from).unwrap().as_erc20().unwrap())
-                    .unwrap();
+            Self::new_checked(stable, collateral, token_accounts)
+        }
+
+        /// Constructs the contract like [new](Self::new), but resolves both tickers before any state is
+        /// written. Since constructors cannot return a `Result`, an invalid setup aborts the instantiation
+        /// with a message describing the [ConstructorError](crate::errors::ConstructorError).
+        #[ink(constructor)]
+        pub fn new_checked(stable: Ticker, collateral: Ticker, token_accounts: AccountId) -> Self {
+            let registry: TokenAccounts = FromAccountId::from_account_id(token_accounts);
+            let stable = resolve_token(&registry, stable).unwrap_or_else(|e| panic!("invalid stable token: {}", e));
             let collateral =
-                TokenSpec::new(token_accounts.get_token(collateral).map(MultiToken::from).unwrap().as_erc20().unwrap())
-                    .unwrap();
+                resolve_token(&registry, collateral).unwrap_or_else(|e| panic!("invalid collateral token: {}", e));
 
             Self {
                 owner: Lazy::new(Self::env().caller()),
                 stable: Lazy::new(stable),
                 collateral: Lazy::new(collateral),
+                collaterals: Default::default(),
                 prices: Default::default(),
                 oracles: Default::default(),
+                price_decimals: Default::default(),
+                emergency_rate: Lazy::new(None),
+                rate_limit: Lazy::new(None),
+                window_start: Lazy::new(0),
+                window_total: Lazy::new(0),
+                allow_single_oracle_fallback: Lazy::new(false),
+                token_accounts: Lazy::new(Some(token_accounts)),
             }
         }
 
@@ -155,9 +210,37 @@ mod stablecoin {
                 owner: Lazy::new(Self::env().caller()),
                 stable: Lazy::new(stable),
                 collateral: Lazy::new(collateral),
+                collaterals: Default::default(),
                 prices: Default::default(),
                 oracles: Default::default(),
+                price_decimals: Default::default(),
+                emergency_rate: Lazy::new(None),
+                rate_limit: Lazy::new(None),
+                window_start: Lazy::new(0),
+                window_total: Lazy::new(0),
+                allow_single_oracle_fallback: Lazy::new(false),
+                token_accounts: Lazy::new(None),
+            }
+        }
+
+        /// Checks that the stable and collateral tokens are still registered as erc20 tokens with the
+        /// token-accounts contract, and that the registrations point to the tokens this contract uses.
+        /// Contracts created through `new_raw` have nothing to validate against and always succeed.
+        #[ink(message)]
+        pub fn validate_setup(&self) -> Result<(), ConstructorError> {
+            let token_accounts = match *self.token_accounts {
+                Some(token_accounts) => token_accounts,
+                None => return Ok(()),
+            };
+            let registry: TokenAccounts = FromAccountId::from_account_id(token_accounts);
+
+            for spec in [&*self.stable, &*self.collateral].iter() {
+                let account_id = check_token(&spec.ticker, registry.get_token(spec.ticker.clone()))?;
+                if account_id != ToAccountId::to_account_id(&spec.erc20) {
+                    return Err(ConstructorError::RegistrationMismatch { ticker: spec.ticker.clone() });
+                }
             }
+            Ok(())
         }
 
         /// Obtains the current price bucket.
@@ -190,17 +273,21 @@ mod stablecoin {
                 token: token.clone(),
                 prices: Default::default(),
                 volumes: Default::default(),
+                timestamps: Default::default(),
             });
 
             bucket.prices.insert(oracle.address, request.price);
             bucket.volumes.insert(oracle.address, request.volume);
+            bucket.timestamps.insert(oracle.address, self.env().block_timestamp());
             let bucket = bucket.clone();
 
             self.env().emit_event(PriceSubmitted::from(PriceSubmittedOutput { oracle: oracle.address, ticker: token }));
             Ok(bucket)
         }
 
-        /// Obtains the current price of the ticker by weighted average of oracle data.
+        /// Obtains the current price of the ticker by weighted average of oracle data. The price has
+        /// `PRICE_DECIMALS` decimals, regardless of the decimals the oracles submitted prices in. If the average
+        /// can't be computed and the single oracle fallback is allowed, the most recently submitted price is used.
         ///
         /// # Arguments
         ///
@@ -209,18 +296,58 @@ mod stablecoin {
         pub fn get_price(&self, token: Ticker) -> Result<i128, GetPriceError> {
             let bucket = self.get_price_bucket(token).ok_or(GetPriceError::BucketNotFound)?;
 
-            let (total, sum) = bucket.prices.iter().fold((0, 0): (i128, i128), |(total, sum), (address, &price)| {
-                let volume = bucket.volumes.get(address).unwrap(); // Can only fail if a price was added without a volume.
-                (total.saturating_add((*volume).into()), sum.saturating_add(price.saturating_mul(*volume).into()))
-            });
+            let mut total: i128 = 0;
+            let mut sum: i128 = 0;
+            for (address, &price) in bucket.prices.iter() {
+                // Can only fail if a price was added without a volume.
+                let volume: i128 = (*bucket.volumes.get(address).unwrap()).into();
+                let decimals = self.price_decimals.get(address).copied().unwrap_or(PRICE_DECIMALS as u8);
+                let price = normalize_price(price, decimals)?;
+                total = total.saturating_add(volume);
+                sum = sum.saturating_add(price.saturating_mul(volume));
+            }
 
-            sum.checked_div(total).ok_or_else(|| GetPriceError::math_error("checked division of sum / total errored"))
+            match sum.checked_div(total) {
+                Some(price) => Ok(price),
+                None if *self.allow_single_oracle_fallback => self.latest_price(&bucket),
+                None => Err(GetPriceError::math_error("checked division of sum / total errored")),
+            }
         }
 
+        /// Allows or disallows `get_price` to fall back to the most recently submitted price.
+        ///
+        /// # Restrictions
+        ///
+        /// May only be called by the contract owner.
+        #[ink(message)]
+        pub fn set_single_oracle_fallback(&mut self, allow: bool) -> Result<(), OwnerError> {
+            self.ensure_is_owner()?;
+            *self.allow_single_oracle_fallback = allow;
+            Ok(())
+        }
+
+        /// Returns true if `get_price` may fall back to the most recently submitted price.
+        #[ink(message)]
+        pub fn allows_single_oracle_fallback(&self) -> bool { *self.allow_single_oracle_fallback }
+
         /// Obtains the oracle. Is `None` if not registered.
         #[ink(message)]
         pub fn get_oracle(&self, address: AccountId) -> Option<Oracle> { self.oracles.get(&address).cloned() }
 
+        /// Obtains all registered oracles, regardless of their state.
+        #[ink(message)]
+        pub fn list_oracles(&self) -> Vec<Oracle> { self.oracles.values().cloned().collect() }
+
+        /// Obtains the latest price submitted by `oracle` for `token`, together with the block timestamp of the
+        /// submission. Is `None` if the oracle never submitted a price for the token.
+        #[ink(message)]
+        pub fn last_submission(&self, oracle: AccountId, token: Ticker) -> Option<(i128, Timestamp)> {
+            let bucket = self.prices.get(&token)?;
+            let price = bucket.prices.get(&oracle)?;
+            let timestamp = bucket.timestamps.get(&oracle)?;
+            Some(((*price).into(), *timestamp))
+        }
+
         /// Adds a new oracle to the set of allowed oracles. The oracle's state is set to `Allowed`.
         ///
         /// # Restrictions
@@ -241,6 +368,9 @@ mod stablecoin {
             let oracle = Oracle { address: request.address, name: request.name, state: OracleState::Allowed };
 
             self.oracles.insert(request.address, oracle.clone());
+            if let Some(decimals) = request.price_decimals {
+                self.price_decimals.insert(request.address, decimals);
+            }
 
             self.env().emit_event(OracleRegistered::from(OracleRegisteredOutput { oracle: oracle.clone() }));
 
@@ -276,6 +406,56 @@ mod stablecoin {
             Ok(oracle)
         }
 
+        /// Accepts `collateral` as an additional collateral for `convert_to_pusd`. Its ticker is used to look up
+        /// the oracle price.
+        ///
+        /// # Restrictions
+        ///
+        /// May only be called by the contract owner.
+        #[ink(message)]
+        pub fn add_collateral(&mut self, collateral: TokenData) -> Result<(), UpdateCollateralError> {
+            self.ensure_is_owner()?;
+
+            if self.collateral_spec(&collateral.ticker).is_some() {
+                return Err(UpdateCollateralError::AlreadyExists);
+            }
+            self.collaterals.insert(collateral.ticker.clone(), TokenSpec::from_data(collateral));
+            Ok(())
+        }
+
+        /// Stops accepting the additional collateral `ticker`. The primary collateral cannot be removed.
+        ///
+        /// # Restrictions
+        ///
+        /// May only be called by the contract owner.
+        #[ink(message)]
+        pub fn remove_collateral(&mut self, ticker: Ticker) -> Result<(), UpdateCollateralError> {
+            self.ensure_is_owner()?;
+
+            if ticker == self.collateral.ticker {
+                return Err(UpdateCollateralError::PrimaryCollateral);
+            }
+            self.collaterals.take(&ticker).ok_or(UpdateCollateralError::DoesNotExist)?;
+            Ok(())
+        }
+
+        /// Obtains the tickers of all accepted collaterals, starting with the primary collateral.
+        #[ink(message)]
+        pub fn collateral_tickers(&self) -> Vec<Ticker> {
+            core::iter::once(self.collateral.ticker.clone()).chain(self.collaterals.keys().cloned()).collect()
+        }
+
+        fn collateral_spec(&self, ticker: &str) -> Option<TokenSpec> {
+            if self.collateral.ticker == ticker {
+                return Some((*self.collateral).clone());
+            }
+            self.collaterals.get(ticker).cloned()
+        }
+
+        fn ensure_collateral(&self, ticker: Ticker) -> Result<TokenSpec, ConvertError> {
+            self.collateral_spec(&ticker).ok_or_else(|| ConvertError::token_not_found(ticker))
+        }
+
         fn ensure_is_oracle(&self) -> Result<Oracle, OracleError> {
             self.oracles.get(&self.env().caller()).cloned().ok_or(OracleError)
         }
@@ -298,29 +478,158 @@ mod stablecoin {
             self.convert(request, self.stable.clone(), self.collateral.clone())
         }
 
-        /// Swaps Privi for pUSD based on oracle provided prices.
+        /// Swaps an accepted collateral for pUSD based on oracle provided prices.
         ///
         /// # Arguments
         ///
         /// * [ConvertRequest](crate::models::ConvertRequest): request specifying the conversion
+        /// * collateral_ticker: the ticker of the collateral to burn, see `collateral_tickers`
+        #[ink(message)]
+        pub fn convert_to_pusd(
+            &mut self,
+            request: ConvertRequest,
+            collateral_ticker: Ticker,
+        ) -> Result<(), ConvertError> {
+            let collateral = self.ensure_collateral(collateral_ticker)?;
+            self.convert(request, collateral, self.stable.clone())
+        }
+
+        /// The most recently submitted price in `bucket`, normalized to `PRICE_DECIMALS`.
+        fn latest_price(&self, bucket: &PriceBucket) -> Result<i128, GetPriceError> {
+            let (address, _) = bucket
+                .timestamps
+                .iter()
+                .max_by_key(|(_, timestamp)| **timestamp)
+                .ok_or_else(|| GetPriceError::math_error("no price was submitted"))?;
+            // Can only fail if a timestamp was added without a price.
+            let price = *bucket.prices.get(address).unwrap();
+            let decimals = self.price_decimals.get(address).copied().unwrap_or(PRICE_DECIMALS as u8);
+            normalize_price(price, decimals)
+        }
+
+        /// Limits the amount of stable coins converted from or to per window of time, or removes the limit with
+        /// `None`. The current window is kept.
+        ///
+        /// Windows are fixed rather than rolling: a window starts with the first conversion after the previous one
+        /// ended, so up to twice the limit can be converted around the end of a window.
+        ///
+        /// # Restrictions
+        ///
+        /// May only be called by the contract owner.
         #[ink(message)]
-        pub fn convert_to_pusd(&mut self, request: ConvertRequest) -> Result<(), ConvertError> {
-            self.convert(request, self.collateral.clone(), self.stable.clone())
+        pub fn set_rate_limit(&mut self, rate_limit: Option<RateLimit>) -> Result<(), OwnerError> {
+            self.ensure_is_owner()?;
+            *self.rate_limit = rate_limit;
+            Ok(())
         }
 
-        fn convert(&self, request: ConvertRequest, mut from: TokenSpec, mut to: TokenSpec) -> Result<(), ConvertError> {
-            let from_price =
-                Decimal::from_i128_with_scale(self.get_price(from.ticker.clone())?, from.decimal_count.into());
-            let to_price = Decimal::from_i128_with_scale(self.get_price(to.ticker.clone())?, to.decimal_count.into());
+        /// Obtains the conversion rate limit, if any.
+        #[ink(message)]
+        pub fn rate_limit(&self) -> Option<RateLimit> { *self.rate_limit }
+
+        /// Returns true if emergency mode is active.
+        #[ink(message)]
+        pub fn is_emergency(&self) -> bool { self.emergency_rate.is_some() }
+
+        /// Activates emergency mode. While active, holders may redeem the stable coin for collateral at the fixed
+        /// `rate`, without relying on oracle prices.
+        ///
+        /// # Restrictions
+        ///
+        /// May only be called by the contract owner.
+        ///
+        /// # Arguments
+        ///
+        /// * rate: collateral paid per stable coin, with `PRICE_DECIMALS` decimals.
+        #[ink(message)]
+        pub fn activate_emergency(&mut self, rate: Balance) -> Result<(), OwnerError> {
+            self.ensure_is_owner()?;
+            *self.emergency_rate = Some(rate);
+            self.env().emit_event(EmergencyModeUpdated::from(EmergencyModeUpdatedOutput { rate: Some(rate) }));
+            Ok(())
+        }
+
+        /// Deactivates emergency mode.
+        ///
+        /// # Restrictions
+        ///
+        /// May only be called by the contract owner.
+        #[ink(message)]
+        pub fn deactivate_emergency(&mut self) -> Result<(), OwnerError> {
+            self.ensure_is_owner()?;
+            *self.emergency_rate = None;
+            self.env().emit_event(EmergencyModeUpdated::from(EmergencyModeUpdatedOutput { rate: None }));
+            Ok(())
+        }
+
+        /// Burns `amount` of the caller's pUSD and mints Privi at the emergency rate, converting between the
+        /// decimals of both tokens. Returns the amount of Privi minted.
+        ///
+        /// # Restrictions
+        ///
+        /// Emergency mode must be active.
+        #[ink(message)]
+        pub fn emergency_redeem(&mut self, amount: Balance) -> Result<Balance, EmergencyRedeemError> {
+            let rate = self.emergency_rate.ok_or(EmergencyRedeemError::NotActive)?;
+            let mut stable = self.stable.clone();
+            let mut collateral = self.collateral.clone();
+            let redeemed = compute_redemption(amount, rate, stable.decimal_count, collateral.decimal_count)?;
+            let caller = self.env().caller();
+
+            burn_from(&mut stable, caller, amount)?;
+            mint(&mut collateral, caller, redeemed)?;
+
+            self.env().emit_event(Conversion::from(ConversionOutput {
+                from: stable.ticker,
+                to: collateral.ticker,
+                caller,
+            }));
+            Ok(redeemed)
+        }
+
+        /// Computes the amount of Privi `convert_to_privi` would mint for `amount` pUSD at the current prices.
+        #[ink(message)]
+        pub fn quote_convert_to_privi(&self, amount: Balance) -> Result<Balance, ConvertError> {
+            self.quote(amount, &self.stable, &self.collateral)
+        }
+
+        /// Computes the amount of pUSD `convert_to_pusd` would mint for `amount` of the collateral at the current
+        /// prices.
+        #[ink(message)]
+        pub fn quote_convert_to_pusd(
+            &self,
+            amount: Balance,
+            collateral_ticker: Ticker,
+        ) -> Result<Balance, ConvertError> {
+            let collateral = self.ensure_collateral(collateral_ticker)?;
+            self.quote(amount, &collateral, &self.stable)
+        }
+
+        fn quote(&self, amount: Balance, from: &TokenSpec, to: &TokenSpec) -> Result<Balance, ConvertError> {
+            let from_price = Decimal::from_i128_with_scale(self.get_price(from.ticker.clone())?, PRICE_DECIMALS);
+            let to_price = Decimal::from_i128_with_scale(self.get_price(to.ticker.clone())?, PRICE_DECIMALS);
 
             if from_price.is_zero() || to_price.is_zero() {
                 return Err(ConvertError::TokenValueIsZero);
             }
 
-            let amount = compute_conversion(from_price, to_price, request.amount)?;
+            Ok(compute_conversion(from_price, to_price, amount, from.decimal_count, to.decimal_count)?)
+        }
 
-            from.erc20.burn_from(request.address, request.amount)?;
-            to.erc20.mint(request.address, amount)?;
+        fn convert(
+            &mut self,
+            request: ConvertRequest,
+            mut from: TokenSpec,
+            mut to: TokenSpec,
+        ) -> Result<(), ConvertError> {
+            let amount = self.quote(request.amount, &from, &to)?;
+            let stable_amount = if from.ticker == self.stable.ticker { request.amount } else { amount };
+            let (window_start, window_total) = self.next_window(stable_amount, self.env().block_timestamp())?;
+
+            burn_from(&mut from, request.address, request.amount)?;
+            mint(&mut to, request.address, amount)?;
+            *self.window_start = window_start;
+            *self.window_total = window_total;
             self.env().emit_event(Conversion::from(ConversionOutput {
                 from: from.ticker,
                 to: to.ticker,
@@ -328,27 +637,493 @@ mod stablecoin {
             }));
             Ok(())
         }
+
+        /// Returns the start and total of the rate limit window after converting `stable_amount` at `now`. A new
+        /// window starts once `window_duration` has passed since the current one started.
+        fn next_window(&self, stable_amount: Balance, now: Timestamp) -> Result<(Timestamp, Balance), ConvertError> {
+            let (start, total) = match *self.rate_limit {
+                Some(limit) if now < self.window_start.saturating_add(limit.window_duration) => {
+                    (*self.window_start, *self.window_total)
+                }
+                _ => (now, 0),
+            };
+            let total = total.checked_add(stable_amount).ok_or(ConvertError::RateLimited)?;
+            match *self.rate_limit {
+                Some(limit) if total > limit.window_limit => Err(ConvertError::RateLimited),
+                _ => Ok((start, total)),
+            }
+        }
+    }
+
+    /// Burns `amount` of `token` from `account`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn burn_from(token: &mut TokenSpec, account: AccountId, amount: Balance) -> Result<(), erc20::Error> {
+        token.erc20.burn_from(account, amount)
+    }
+
+    /// Mints `amount` of `token` to `account`
+    #[cfg(not(any(test, feature = "ink-as-dependency")))]
+    fn mint(token: &mut TokenSpec, account: AccountId, amount: Balance) -> Result<(), erc20::Error> {
+        token.erc20.mint(account, amount)
+    }
+
+    #[cfg(test)]
+    use tests::mock_erc20::{burn_from, mint};
+
+    /// Looks up `ticker` in the token-accounts contract and queries the erc20 token for its metadata.
+    fn resolve_token(ta: &TokenAccounts, ticker: Ticker) -> Result<TokenSpec, ConstructorError> {
+        let account_id = check_token(&ticker, ta.get_token(ticker.clone()))?;
+        TokenSpec::new(FromAccountId::from_account_id(account_id))
+            .map_err(|reason| ConstructorError::MissingMetadata { ticker, reason: reason.into() })
+    }
+
+    /// Ensures the registered token exists and is an erc20 token, returning its account id.
+    fn check_token(ticker: &str, token: Option<Token>) -> Result<AccountId, ConstructorError> {
+        let token = token.ok_or_else(|| ConstructorError::TokenNotFound { ticker: ticker.into() })?;
+        match token.standard {
+            TokenStandard::Erc20 => Ok(token.account_id),
+            _ => Err(ConstructorError::InvalidTokenStandard { ticker: ticker.into() }),
+        }
+    }
+
+    /// Scales `price` from `decimals` to `PRICE_DECIMALS`.
+    fn normalize_price(price: u64, decimals: u8) -> Result<i128, GetPriceError> {
+        let price = i128::from(price);
+        let decimals = u32::from(decimals);
+        if decimals <= PRICE_DECIMALS {
+            10_i128.checked_pow(PRICE_DECIMALS - decimals).and_then(|factor| price.checked_mul(factor))
+        } else {
+            10_i128.checked_pow(decimals - PRICE_DECIMALS).map(|factor| price / factor)
+        }
+        .ok_or_else(|| GetPriceError::math_error("normalizing the price overflowed"))
     }
 
-    fn compute_conversion(from: Decimal, to: Decimal, amount: Balance) -> Result<Balance, GetPriceError> {
-        use rust_decimal::prelude::ToPrimitive;
+    /// Applies the emergency `rate` to `amount` of a token with `from_decimals`, returning an amount of a token with
+    /// `to_decimals`.
+    fn compute_redemption(
+        amount: Balance,
+        rate: Balance,
+        from_decimals: u8,
+        to_decimals: u8,
+    ) -> Result<Balance, EmergencyRedeemError> {
+        let overflow = || EmergencyRedeemError::math_error("computing the redeemed amount overflowed");
+        // `amount * rate` has the decimals of the stable coin and of the rate
+        let decimals = u32::from(from_decimals) + PRICE_DECIMALS;
+        let to_decimals = u32::from(to_decimals);
+        let value = amount.checked_mul(rate).ok_or_else(overflow)?;
+        if to_decimals >= decimals {
+            10_u128.checked_pow(to_decimals - decimals).and_then(|factor| value.checked_mul(factor))
+        } else {
+            10_u128.checked_pow(decimals - to_decimals).map(|factor| value / factor)
+        }
+        .ok_or_else(overflow)
+    }
 
+    /// Converts `amount` of a token with `from_decimals` priced at `from` into a token with `to_decimals` priced at
+    /// `to`.
+    fn compute_conversion(
+        from: Decimal,
+        to: Decimal,
+        amount: Balance,
+        from_decimals: u8,
+        to_decimals: u8,
+    ) -> Result<Balance, GetPriceError> {
+        use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+
+        let overflow = || GetPriceError::math_error("computing the converted amount overflowed");
         let ratio = from.checked_div(to).ok_or_else(|| GetPriceError::math_error("computing the ratio errored"))?;
 
-        let amount = ratio * Decimal::from(amount);
-        Ok(amount.to_u128().unwrap())
+        let amount = Decimal::from_u128(amount).and_then(|amount| amount.checked_mul(ratio)).ok_or_else(overflow)?;
+        // the prices are per whole token, so the amount is rescaled to the smallest unit of the target token
+        let (from_decimals, to_decimals) = (u32::from(from_decimals), u32::from(to_decimals));
+        let amount = if to_decimals >= from_decimals {
+            10_u64.checked_pow(to_decimals - from_decimals).and_then(|factor| amount.checked_mul(factor.into()))
+        } else {
+            10_u64.checked_pow(from_decimals - to_decimals).and_then(|factor| amount.checked_div(factor.into()))
+        };
+        amount.and_then(|amount| amount.to_u128()).ok_or_else(overflow)
     }
 
     #[cfg(test)]
     mod tests {
         use super::*;
+        use contract_utils::test_utils;
         use ink_lang as ink;
         use rust_decimal_macros::dec;
 
+        /// In-memory balances of the stable coin and the collaterals, as token contracts can't be called off-chain
+        pub(super) mod mock_erc20 {
+            use super::*;
+            use std::{cell::RefCell, collections::BTreeMap};
+
+            thread_local! {
+                static BALANCES: RefCell<BTreeMap<(AccountId, AccountId), Balance>> = RefCell::new(BTreeMap::new());
+            }
+
+            pub fn balance_of(token: &TokenSpec, account: AccountId) -> Balance {
+                BALANCES.with(|x| x.borrow().get(&(token.erc20.to_account_id(), account)).copied().unwrap_or_default())
+            }
+
+            pub fn set_balance(token: &TokenSpec, account: AccountId, balance: Balance) {
+                BALANCES.with(|x| x.borrow_mut().insert((token.erc20.to_account_id(), account), balance));
+            }
+
+            pub fn burn_from(token: &mut TokenSpec, account: AccountId, amount: Balance) -> Result<(), erc20::Error> {
+                let balance =
+                    balance_of(token, account).checked_sub(amount).ok_or(erc20::Error::InsufficientBalance)?;
+                set_balance(token, account, balance);
+                Ok(())
+            }
+
+            pub fn mint(token: &mut TokenSpec, account: AccountId, amount: Balance) -> Result<(), erc20::Error> {
+                set_balance(token, account, balance_of(token, account) + amount);
+                Ok(())
+            }
+        }
+
+        fn new_stablecoin() -> Stablecoin {
+            let accounts = test_utils::default_accounts();
+            Stablecoin::new_raw(
+                TokenData { decimal_count: 12, ticker: "pUSD".into(), account_id: accounts.django },
+                TokenData { decimal_count: 12, ticker: "PRIVI".into(), account_id: accounts.eve },
+            )
+        }
+
+        fn register(contract: &mut Stablecoin, address: AccountId, name: &str) {
+            register_with_decimals(contract, address, name, None);
+        }
+
+        fn register_with_decimals(contract: &mut Stablecoin, address: AccountId, name: &str, decimals: Option<u8>) {
+            contract
+                .register_oracle(RegisterOracleRequest { address, name: name.into(), price_decimals: decimals })
+                .unwrap();
+        }
+
+        fn submit(contract: &mut Stablecoin, oracle: AccountId, price: u64, volume: u64) {
+            submit_for(contract, oracle, "PRIVI", price, volume);
+        }
+
+        fn submit_for(contract: &mut Stablecoin, oracle: AccountId, token: &str, price: u64, volume: u64) {
+            test_utils::set_caller(oracle);
+            contract.submit_price(SubmitPriceRequest { token: token.into(), price, volume }).unwrap();
+            ink_env::test::pop_execution_context();
+        }
+
         #[ink::test]
         fn test_compute_conversion() {
-            assert_eq!(compute_conversion(10.into(), 1.into(), 10).unwrap(), 100);
-            assert_eq!(compute_conversion(10.into(), 1.into(), 1).unwrap(), 10);
+            assert_eq!(compute_conversion(10.into(), 1.into(), 10, 12, 12).unwrap(), 100);
+            assert_eq!(compute_conversion(10.into(), 1.into(), 1, 12, 12).unwrap(), 10);
+        }
+
+        #[ink::test]
+        fn test_compute_conversion_rescales_decimals() {
+            // 1 token with 6 decimals worth 10 is 10 tokens with 12 decimals worth 1, and back
+            assert_eq!(compute_conversion(10.into(), 1.into(), 1_000_000, 6, 12).unwrap(), 10_000_000_000_000);
+            assert_eq!(compute_conversion(1.into(), 10.into(), 10_000_000_000_000, 12, 6).unwrap(), 1_000_000);
+            // amounts below the smallest unit of the target token are rounded down
+            assert_eq!(compute_conversion(1.into(), 1.into(), 999_999, 12, 6).unwrap(), 0);
+        }
+
+        #[ink::test]
+        fn test_compute_conversion_overflow() {
+            assert!(matches!(
+                compute_conversion(Decimal::MAX, 1.into(), Balance::MAX, 12, 12),
+                Err(GetPriceError::MathError(_))
+            ));
+        }
+
+        #[ink::test]
+        fn test_check_token_missing_ticker() {
+            assert_eq!(check_token("pUSD", None).unwrap_err(), ConstructorError::TokenNotFound {
+                ticker: "pUSD".into()
+            });
+        }
+
+        #[ink::test]
+        fn test_check_token_wrong_standard() {
+            let account_id = AccountId::from([0x01; 32]);
+            assert_eq!(
+                check_token("PRIVI", Some(Token::new(account_id, TokenStandard::Erc721))).unwrap_err(),
+                ConstructorError::InvalidTokenStandard { ticker: "PRIVI".into() }
+            );
+            assert_eq!(check_token("PRIVI", Some(Token::new(account_id, TokenStandard::Erc20))).unwrap(), account_id);
+        }
+
+        #[ink::test]
+        fn test_list_oracles() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = new_stablecoin();
+            assert!(contract.list_oracles().is_empty());
+
+            register(&mut contract, accounts.bob, "bob");
+            register(&mut contract, accounts.charlie, "charlie");
+            register(&mut contract, accounts.frank, "frank");
+
+            let oracles = contract.list_oracles();
+            assert_eq!(oracles.len(), 3);
+            for address in [accounts.bob, accounts.charlie, accounts.frank].iter() {
+                assert!(oracles.iter().any(|oracle| oracle.address == *address && oracle.state.is_allowed()));
+            }
+        }
+
+        #[ink::test]
+        fn test_last_submission() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = new_stablecoin();
+            register(&mut contract, accounts.bob, "bob");
+
+            assert_eq!(contract.last_submission(accounts.bob, "pUSD".into()), None);
+
+            test_utils::set_caller(accounts.bob);
+            contract.submit_price(SubmitPriceRequest { token: "pUSD".into(), price: 42, volume: 1 }).unwrap();
+
+            let now = ink_env::block_timestamp::<ink_env::DefaultEnvironment>().unwrap();
+            assert_eq!(contract.last_submission(accounts.bob, "pUSD".into()), Some((42, now)));
+            assert_eq!(contract.last_submission(accounts.charlie, "pUSD".into()), None);
+            assert_eq!(contract.last_submission(accounts.bob, "PRIVI".into()), None);
+        }
+
+        #[ink::test]
+        fn test_emergency_mode() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = new_stablecoin();
+
+            // redeeming is blocked while inactive
+            assert!(!contract.is_emergency());
+            assert_eq!(contract.emergency_redeem(100), Err(EmergencyRedeemError::NotActive));
+
+            // only the owner may activate
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(contract.activate_emergency(2_000_000_000_000), Err(OwnerError));
+            ink_env::test::pop_execution_context();
+
+            contract.activate_emergency(2_000_000_000_000).unwrap();
+            assert!(contract.is_emergency());
+
+            contract.deactivate_emergency().unwrap();
+            assert!(!contract.is_emergency());
+            assert_eq!(contract.emergency_redeem(100), Err(EmergencyRedeemError::NotActive));
+        }
+
+        #[ink::test]
+        fn test_compute_redemption() {
+            assert_eq!(compute_redemption(100, 2_000_000_000_000, 12, 12).unwrap(), 200);
+            assert_eq!(compute_redemption(100, 500_000_000_000, 12, 12).unwrap(), 50);
+            assert!(compute_redemption(Balance::MAX, 2_000_000_000_000, 12, 12).is_err());
+
+            // 1 pUSD at a rate of 2.0 is 2 of a collateral with 6 or 18 decimals
+            assert_eq!(compute_redemption(1_000_000_000_000, 2_000_000_000_000, 12, 6).unwrap(), 2_000_000);
+            assert_eq!(
+                compute_redemption(1_000_000_000_000, 2_000_000_000_000, 12, 18).unwrap(),
+                2_000_000_000_000_000_000
+            );
+            // and a 6 decimal stable coin is scaled up to 12 decimals
+            assert_eq!(compute_redemption(1_000_000, 2_000_000_000_000, 6, 12).unwrap(), 2_000_000_000_000);
+            // amounts below the smallest unit of the collateral are rounded down
+            assert_eq!(compute_redemption(999_999, 1_000_000_000_000, 12, 6).unwrap(), 0);
+        }
+
+        #[ink::test]
+        fn test_emergency_redeem_normalizes_decimals() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = Stablecoin::new_raw(
+                TokenData { decimal_count: 12, ticker: "pUSD".into(), account_id: accounts.django },
+                TokenData { decimal_count: 6, ticker: "PRIVI".into(), account_id: accounts.eve },
+            );
+            mock_erc20::set_balance(&contract.stable, accounts.alice, 5_000_000_000_000);
+            contract.activate_emergency(2_000_000_000_000).unwrap();
+
+            // 1 pUSD redeems 2 PRIVI
+            assert_eq!(contract.emergency_redeem(1_000_000_000_000), Ok(2_000_000));
+            assert_eq!(mock_erc20::balance_of(&contract.stable, accounts.alice), 4_000_000_000_000);
+            assert_eq!(mock_erc20::balance_of(&contract.collateral, accounts.alice), 2_000_000);
+
+            // the pUSD is burned before anything is minted
+            assert_eq!(
+                contract.emergency_redeem(5_000_000_000_000),
+                Err(EmergencyRedeemError::Erc20(erc20::Error::InsufficientBalance))
+            );
+            assert_eq!(mock_erc20::balance_of(&contract.collateral, accounts.alice), 2_000_000);
+        }
+
+        #[ink::test]
+        fn test_quote_convert() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = new_stablecoin();
+            register(&mut contract, accounts.bob, "bob");
+
+            // no prices yet
+            assert!(contract.quote_convert_to_privi(100).is_err());
+
+            // 1 pUSD = 1.0, 1 PRIVI = 0.5
+            submit_for(&mut contract, accounts.bob, "pUSD", 1_000_000_000_000, 1);
+            submit_for(&mut contract, accounts.bob, "PRIVI", 500_000_000_000, 1);
+
+            // the quote uses the same computation as the conversion
+            let from = Decimal::from_i128_with_scale(1_000_000_000_000, PRICE_DECIMALS);
+            let to = Decimal::from_i128_with_scale(500_000_000_000, PRICE_DECIMALS);
+            assert_eq!(
+                contract.quote_convert_to_privi(100).unwrap(),
+                compute_conversion(from, to, 100, 12, 12).unwrap()
+            );
+            assert_eq!(contract.quote_convert_to_privi(100).unwrap(), 200);
+            assert_eq!(contract.quote_convert_to_pusd(100, "PRIVI".into()).unwrap(), 50);
+        }
+
+        #[ink::test]
+        fn test_quote_convert_rescales_decimals() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = Stablecoin::new_raw(
+                TokenData { decimal_count: 12, ticker: "pUSD".into(), account_id: accounts.django },
+                TokenData { decimal_count: 6, ticker: "PRIVI".into(), account_id: accounts.eve },
+            );
+            register(&mut contract, accounts.bob, "bob");
+
+            // 1 pUSD = 1.0, 1 PRIVI = 0.5
+            submit_for(&mut contract, accounts.bob, "pUSD", 1_000_000_000_000, 1);
+            submit_for(&mut contract, accounts.bob, "PRIVI", 500_000_000_000, 1);
+
+            // 1 pUSD is 2 PRIVI with 6 decimals, and 1 PRIVI is 0.5 pUSD with 12 decimals
+            assert_eq!(contract.quote_convert_to_privi(1_000_000_000_000).unwrap(), 2_000_000);
+            assert_eq!(contract.quote_convert_to_pusd(1_000_000, "PRIVI".into()).unwrap(), 500_000_000_000);
+        }
+
+        #[ink::test]
+        fn test_multiple_collaterals() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = new_stablecoin();
+            register(&mut contract, accounts.bob, "bob");
+            let dot = TokenData { decimal_count: 12, ticker: "DOT".into(), account_id: accounts.frank };
+
+            // only the owner may add collaterals, and each ticker only once
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(contract.add_collateral(dot.clone()), Err(UpdateCollateralError::AuthzError(OwnerError)));
+            ink_env::test::pop_execution_context();
+            contract.add_collateral(dot.clone()).unwrap();
+            assert_eq!(contract.add_collateral(dot), Err(UpdateCollateralError::AlreadyExists));
+            assert_eq!(contract.collateral_tickers(), vec![String::from("PRIVI"), String::from("DOT")]);
+
+            // 1 pUSD = 1.0, 1 PRIVI = 0.5, 1 DOT = 20.0
+            submit_for(&mut contract, accounts.bob, "pUSD", 1_000_000_000_000, 1);
+            submit_for(&mut contract, accounts.bob, "PRIVI", 500_000_000_000, 1);
+            submit_for(&mut contract, accounts.bob, "DOT", 20_000_000_000_000, 1);
+
+            // pUSD can be minted from either collateral
+            assert_eq!(contract.quote_convert_to_pusd(100, "PRIVI".into()).unwrap(), 50);
+            assert_eq!(contract.quote_convert_to_pusd(100, "DOT".into()).unwrap(), 2_000);
+            assert_eq!(contract.quote_convert_to_pusd(100, "BTC".into()), Err(ConvertError::token_not_found("BTC")));
+
+            // the additional collateral can be removed, the primary one cannot
+            assert_eq!(contract.remove_collateral("PRIVI".into()), Err(UpdateCollateralError::PrimaryCollateral));
+            contract.remove_collateral("DOT".into()).unwrap();
+            assert_eq!(contract.remove_collateral("DOT".into()), Err(UpdateCollateralError::DoesNotExist));
+            assert_eq!(contract.quote_convert_to_pusd(100, "DOT".into()), Err(ConvertError::token_not_found("DOT")));
+        }
+
+        #[ink::test]
+        fn test_rate_limit() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = new_stablecoin();
+            register(&mut contract, accounts.bob, "bob");
+            let limit = RateLimit { window_duration: 1_000, window_limit: 100 };
+
+            // 1 pUSD = 1 PRIVI
+            submit_for(&mut contract, accounts.bob, "pUSD", 1_000_000_000_000, 1);
+            submit_for(&mut contract, accounts.bob, "PRIVI", 1_000_000_000_000, 1);
+            mock_erc20::set_balance(&contract.stable, accounts.alice, 1_000);
+            let to_privi = |contract: &mut Stablecoin, amount: Balance| {
+                contract.convert_to_privi(ConvertRequest { address: accounts.alice, amount })
+            };
+
+            // only the owner may set the limit
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(contract.set_rate_limit(Some(limit)), Err(OwnerError));
+            ink_env::test::pop_execution_context();
+            contract.set_rate_limit(Some(limit)).unwrap();
+            assert_eq!(contract.rate_limit(), Some(limit));
+
+            // convert up to the limit
+            to_privi(&mut contract, 60).unwrap();
+            test_utils::advance_time(500);
+            to_privi(&mut contract, 40).unwrap();
+            assert_eq!(to_privi(&mut contract, 1), Err(ConvertError::RateLimited));
+            assert_eq!(mock_erc20::balance_of(&contract.stable, accounts.alice), 900);
+            assert_eq!(mock_erc20::balance_of(&contract.collateral, accounts.alice), 100);
+
+            // the window is fixed, the whole limit is available once it ended
+            test_utils::advance_time(500);
+            to_privi(&mut contract, 100).unwrap();
+            assert_eq!(to_privi(&mut contract, 1), Err(ConvertError::RateLimited));
+
+            // the pUSD minted from a collateral counts as well
+            let request = ConvertRequest { address: accounts.alice, amount: 1 };
+            assert_eq!(contract.convert_to_pusd(request, "PRIVI".into()), Err(ConvertError::RateLimited));
+            assert_eq!(mock_erc20::balance_of(&contract.stable, accounts.alice), 800);
+            assert_eq!(mock_erc20::balance_of(&contract.collateral, accounts.alice), 200);
+
+            // without a limit everything passes
+            contract.set_rate_limit(None).unwrap();
+            to_privi(&mut contract, 800).unwrap();
+            assert_eq!(mock_erc20::balance_of(&contract.stable, accounts.alice), 0);
+            assert_eq!(mock_erc20::balance_of(&contract.collateral, accounts.alice), 1_000);
+        }
+
+        #[ink::test]
+        fn test_normalize_price() {
+            assert_eq!(normalize_price(1_500_000, 6).unwrap(), 1_500_000_000_000);
+            assert_eq!(normalize_price(1_500_000_000_000, 12).unwrap(), 1_500_000_000_000);
+            assert_eq!(normalize_price(1_500_000_000_000_000, 15).unwrap(), 1_500_000_000_000);
+        }
+
+        #[ink::test]
+        fn test_get_price_mixed_decimals() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = new_stablecoin();
+            register_with_decimals(&mut contract, accounts.bob, "bob", Some(6));
+            register_with_decimals(&mut contract, accounts.charlie, "charlie", Some(12));
+
+            // both report 1.5
+            submit(&mut contract, accounts.bob, 1_500_000, 1);
+            submit(&mut contract, accounts.charlie, 1_500_000_000_000, 1);
+            assert_eq!(contract.get_price("PRIVI".into()).unwrap(), 1_500_000_000_000);
+
+            // 2.0 with volume 1 and 1.0 with volume 3 average to 1.25
+            submit(&mut contract, accounts.bob, 2_000_000, 1);
+            submit(&mut contract, accounts.charlie, 1_000_000_000_000, 3);
+            assert_eq!(contract.get_price("PRIVI".into()).unwrap(), 1_250_000_000_000);
+        }
+
+        #[ink::test]
+        fn test_single_oracle_fallback() {
+            let accounts = test_utils::default_accounts();
+            let mut contract = new_stablecoin();
+            register_with_decimals(&mut contract, accounts.bob, "bob", Some(6));
+            register(&mut contract, accounts.charlie, "charlie");
+
+            // without volume there is no weighted average
+            submit(&mut contract, accounts.bob, 1_500_000, 0);
+            test_utils::advance_time(1_000);
+            submit(&mut contract, accounts.charlie, 2_000_000_000_000, 0);
+            assert!(matches!(contract.get_price("PRIVI".into()), Err(GetPriceError::MathError(_))));
+
+            // only the owner can allow the fallback
+            test_utils::set_caller(accounts.bob);
+            assert_eq!(contract.set_single_oracle_fallback(true), Err(OwnerError));
+            ink_env::test::pop_execution_context();
+
+            // the fallback uses the price charlie submitted last
+            contract.set_single_oracle_fallback(true).unwrap();
+            assert!(contract.allows_single_oracle_fallback());
+            assert_eq!(contract.get_price("PRIVI".into()).unwrap(), 2_000_000_000_000);
+
+            // and normalizes it to the price decimals
+            test_utils::advance_time(1_000);
+            submit(&mut contract, accounts.bob, 1_000_000, 0);
+            assert_eq!(contract.get_price("PRIVI".into()).unwrap(), 1_000_000_000_000);
+
+            // the weighted average is used whenever it can be computed
+            submit(&mut contract, accounts.charlie, 3_000_000_000_000, 1);
+            assert_eq!(contract.get_price("PRIVI".into()).unwrap(), 3_000_000_000_000);
         }
     }
 }
@@ -39,6 +39,8 @@ impl Default for OracleState {
 pub struct RegisterOracleRequest {
     pub address: AccountId,
     pub name: String,
+    /// The number of decimals of the prices the oracle submits. Defaults to the canonical price scale.
+    pub price_decimals: Option<u8>,
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -63,6 +65,8 @@ pub struct PriceBucket {
     pub token: Ticker,
     pub prices: BTreeMap<AccountId, u64>,
     pub volumes: BTreeMap<AccountId, u64>,
+    /// Block timestamp of the latest submission of each oracle.
+    pub timestamps: BTreeMap<AccountId, u64>,
 }
 
 #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
@@ -80,6 +84,16 @@ pub struct ConvertRequest {
     pub amount: Balance,
 }
 
+/// Limits the total amount of stable coins converted from or to within a fixed window of time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub struct RateLimit {
+    /// The length of a window in milliseconds.
+    pub window_duration: u64,
+    /// The maximum amount of stable coins converted within a window.
+    pub window_limit: Balance,
+}
+
 #[derive(Debug, Clone, scale::Encode, scale::Decode, PackedLayout, SpreadLayout)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
 pub struct TokenSpec {
@@ -3,7 +3,7 @@
 
 pub use contract::TokenAccounts;
 
-use contract_utils::TokenStandard;
+use contract_utils::{OwnableError, TokenStandard};
 use ink_env::AccountId;
 use ink_lang as ink;
 use ink_prelude::{string::String, vec::Vec};
@@ -17,6 +17,21 @@ pub enum Error {
     /// Only the owner may perform this operation
     #[error(display = "Only the owner may perform this operation")]
     OnlyOwnerAllowed,
+    /// Only the pending owner may accept ownership
+    #[error(display = "Only the pending owner may accept ownership")]
+    OnlyPendingOwnerAllowed,
+    /// A token symbol is empty
+    #[error(display = "A token symbol is empty")]
+    EmptySymbol,
+}
+
+impl From<OwnableError> for Error {
+    fn from(error: OwnableError) -> Self {
+        match error {
+            OwnableError::NotOwner => Error::OnlyOwnerAllowed,
+            OwnableError::NotPendingOwner => Error::OnlyPendingOwnerAllowed,
+        }
+    }
 }
 
 /// The result type for this contract
@@ -37,6 +52,9 @@ impl Token {
     pub fn new(account_id: AccountId, standard: TokenStandard) -> Self { Self { account_id, standard } }
 }
 
+/// The maximum number of tokens returned by `tokens_paged`
+pub const MAX_TOKENS_PAGE_SIZE: u32 = 50;
+
 /// Information about a token
 #[derive(Debug, Encode, Decode, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -54,7 +72,9 @@ mod contract {
     use super::*;
 
     #[cfg(not(feature = "ink-as-dependency"))]
-    use ink_storage::{collections::HashMap, lazy::Lazy};
+    use contract_utils::Ownable2Step;
+    #[cfg(not(feature = "ink-as-dependency"))]
+    use ink_storage::collections::HashMap;
 
     /// Event emitted when `set_account` is successful
     #[ink(event)]
@@ -74,54 +94,116 @@ mod contract {
         symbol: String,
     }
 
+    /// Event emitted when the pending owner accepts ownership
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
     /// Contains info for tokens
     #[ink(storage)]
     pub struct TokenAccounts {
         /// `AccountId` by token symbol
         tokens_by_symbol: HashMap<String, Token>,
         /// The owner of the contract
-        owner: Lazy<AccountId>,
+        ownership: Ownable2Step,
     }
 
     impl TokenAccounts {
         /// Creates a new instance
         #[ink(constructor)]
         #[allow(clippy::new_without_default)]
-        pub fn new() -> Self { Self { tokens_by_symbol: Default::default(), owner: Lazy::new(Self::env().caller()) } }
+        pub fn new() -> Self {
+            Self { tokens_by_symbol: Default::default(), ownership: Ownable2Step::new(Self::env().caller()) }
+        }
 
         /// Insert a token
         #[ink(message)]
         pub fn set_token(&mut self, symbol: String, account_id: AccountId, standard: TokenStandard) -> Result<()> {
-            if self.env().caller() != *self.owner {
-                return Err(Error::OnlyOwnerAllowed);
-            }
+            self.ownership.ensure_owner(self.env().caller())?;
             self.tokens_by_symbol.insert(symbol.clone(), Token { account_id, standard });
             self.env().emit_event(SetToken { symbol, account_id, standard });
             Ok(())
         }
 
+        /// Insert multiple tokens at once. No token is inserted if any symbol is empty.
+        #[ink(message)]
+        pub fn set_tokens(&mut self, tokens: Vec<(String, AccountId, TokenStandard)>) -> Result<()> {
+            self.ownership.ensure_owner(self.env().caller())?;
+            if tokens.iter().any(|(symbol, _, _)| symbol.is_empty()) {
+                return Err(Error::EmptySymbol);
+            }
+            for (symbol, account_id, standard) in tokens {
+                self.tokens_by_symbol.insert(symbol.clone(), Token { account_id, standard });
+                self.env().emit_event(SetToken { symbol, account_id, standard });
+            }
+            Ok(())
+        }
+
         /// Remove a token
         #[ink(message)]
         pub fn remove_token(&mut self, symbol: String) -> Result<()> {
-            if self.env().caller() != *self.owner {
-                return Err(Error::OnlyOwnerAllowed);
-            }
+            self.ownership.ensure_owner(self.env().caller())?;
             self.tokens_by_symbol.take(&symbol);
             self.env().emit_event(RemovedToken { symbol });
             Ok(())
         }
 
+        /// Proposes `new_owner` as the owner of the contract. The ownership only changes once `new_owner` calls
+        /// `accept_ownership`.
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            self.ownership.transfer_ownership(self.env().caller(), new_owner)?;
+            Ok(())
+        }
+
+        /// Accepts a pending ownership transfer. Must be called by the proposed owner.
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let previous_owner = self.ownership.owner();
+            let new_owner = self.env().caller();
+            self.ownership.accept_ownership(new_owner)?;
+            self.env().emit_event(OwnershipTransferred { previous_owner, new_owner });
+            Ok(())
+        }
+
+        /// Returns the owner of the contract
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId { self.ownership.owner() }
+
+        /// Returns the proposed owner, if an ownership transfer is in progress
+        #[ink(message)]
+        pub fn pending_owner(&self) -> Option<AccountId> { self.ownership.pending_owner() }
+
         /// Returns the `Token` for the given `symbol`
         #[ink(message)]
         pub fn get_token(&self, symbol: String) -> Option<Token> { self.tokens_by_symbol.get(&symbol).copied() }
 
-        /// Returns all of the tokens
+        /// Returns all of the tokens. The result grows with the registry and can exceed the return size limit of a
+        /// call, so prefer `tokens_paged`.
+        #[ink(message)]
+        pub fn get_all_tokens(&self) -> Vec<TokenInfo> { self.token_infos().collect() }
+
+        /// Returns up to `limit` tokens, skipping the first `start`. `limit` is capped at `MAX_TOKENS_PAGE_SIZE`.
         #[ink(message)]
-        pub fn get_all_tokens(&self) -> Vec<TokenInfo> {
-            self.tokens_by_symbol
-                .iter()
-                .map(|(symbol, x)| TokenInfo { symbol: symbol.clone(), account_id: x.account_id, standard: x.standard })
-                .collect()
+        pub fn tokens_paged(&self, start: u32, limit: u32) -> Vec<TokenInfo> {
+            self.token_infos().skip(start as usize).take(limit.min(MAX_TOKENS_PAGE_SIZE) as usize).collect()
+        }
+
+        /// Returns the number of registered tokens
+        #[ink(message)]
+        pub fn token_count(&self) -> u32 { self.tokens_by_symbol.len() }
+
+        /// Iterates over the registered tokens
+        fn token_infos(&self) -> impl Iterator<Item = TokenInfo> + '_ {
+            self.tokens_by_symbol.iter().map(|(symbol, x)| TokenInfo {
+                symbol: symbol.clone(),
+                account_id: x.account_id,
+                standard: x.standard,
+            })
         }
     }
 
@@ -159,5 +241,89 @@ mod contract {
             tokens.set_token(symbol.clone(), accounts.bob, TokenStandard::Erc20).unwrap_err();
             tokens.remove_token(symbol).unwrap_err();
         }
+
+        #[ink::test]
+        fn test_set_tokens() {
+            let mut tokens = TokenAccounts::new();
+            let accounts = test_utils::default_accounts();
+
+            // a batch with an empty symbol is rejected as a whole
+            assert_eq!(
+                tokens.set_tokens(vec![
+                    ("DOT".into(), accounts.bob, TokenStandard::Erc20),
+                    ("".into(), accounts.charlie, TokenStandard::Erc20),
+                ]),
+                Err(Error::EmptySymbol)
+            );
+            assert_eq!(tokens.token_count(), 0);
+
+            tokens
+                .set_tokens(vec![
+                    ("DOT".into(), accounts.bob, TokenStandard::Erc20),
+                    ("PUNK".into(), accounts.charlie, TokenStandard::Erc721),
+                    ("ITEM".into(), accounts.django, TokenStandard::Erc1155),
+                ])
+                .unwrap();
+            assert_eq!(test_utils::recorded_event_count(), 3);
+            assert_eq!(tokens.get_token("DOT".into()), Some(Token::new(accounts.bob, TokenStandard::Erc20)));
+            assert_eq!(tokens.get_token("PUNK".into()), Some(Token::new(accounts.charlie, TokenStandard::Erc721)));
+            assert_eq!(tokens.get_token("ITEM".into()), Some(Token::new(accounts.django, TokenStandard::Erc1155)));
+
+            // only the owner may insert tokens
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(tokens.set_tokens(Vec::new()), Err(Error::OnlyOwnerAllowed));
+        }
+
+        #[ink::test]
+        fn test_tokens_paged() {
+            let mut tokens = TokenAccounts::new();
+            let accounts = test_utils::default_accounts();
+            let symbols = ["DOT", "ETH", "USDT", "DAI", "BTC"];
+            for symbol in symbols.iter() {
+                tokens.set_token((*symbol).into(), accounts.bob, TokenStandard::Erc20).unwrap();
+            }
+            assert_eq!(tokens.token_count(), 5);
+
+            // page through the tokens two at a time
+            let mut paged = Vec::new();
+            for start in (0..6).step_by(2) {
+                let page = tokens.tokens_paged(start, 2);
+                assert!(page.len() <= 2);
+                paged.extend(page);
+            }
+            assert_eq!(paged, tokens.get_all_tokens());
+            assert_eq!(paged.iter().map(|x| x.symbol.as_str()).collect::<Vec<_>>(), symbols);
+
+            // pages past the end are empty and the limit is capped
+            assert!(tokens.tokens_paged(5, 2).is_empty());
+            assert_eq!(tokens.tokens_paged(0, u32::MAX).len(), 5);
+        }
+
+        #[ink::test]
+        fn test_transfer_ownership() {
+            let mut tokens = TokenAccounts::new();
+            let accounts = test_utils::default_accounts();
+
+            // propose bob as the new owner
+            tokens.transfer_ownership(accounts.bob).unwrap();
+            assert_eq!(tokens.owner(), accounts.alice);
+            assert_eq!(tokens.pending_owner(), Some(accounts.bob));
+
+            // charlie cannot accept
+            test_utils::set_caller(accounts.charlie);
+            assert_eq!(tokens.accept_ownership(), Err(Error::OnlyPendingOwnerAllowed));
+            ink_env::test::pop_execution_context();
+
+            // bob accepts and becomes the owner
+            test_utils::set_caller(accounts.bob);
+            tokens.accept_ownership().unwrap();
+            assert_eq!(tokens.owner(), accounts.bob);
+            assert_eq!(tokens.pending_owner(), None);
+            tokens.set_token("DOT".into(), accounts.django, TokenStandard::Erc20).unwrap();
+            ink_env::test::pop_execution_context();
+
+            // alice is no longer the owner
+            assert_eq!(tokens.transfer_ownership(accounts.alice), Err(Error::OnlyOwnerAllowed));
+        }
     }
 }
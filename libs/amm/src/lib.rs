@@ -21,22 +21,22 @@ pub enum Curve {
 }
 
 /// Automated market maker functionality. The Amm does not store the liquidity pool state, it just
-/// governs the pricing.
+/// governs the pricing. `precision` is the number of decimals of the funding token.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Amm {
-    Quadratic { scale: Balance, shift: Balance },
-    Linear { scale: Balance, shift: Balance },
+    Quadratic { scale: Balance, shift: Balance, precision: u32 },
+    Linear { scale: Balance, shift: Balance, precision: u32 },
 }
 
 /// The parameters describing an Amm. Useful to avoid incorrect usage by destructuring when doing
 /// manual calculations outside of the Amm library.
 ///
 /// ```
-/// use amm::{Parameters, Amm, Curve};
+/// use amm::{Parameters, Amm, Curve, DEFAULT_PRECISION};
 /// use rust_decimal_macros::dec;
 /// use contract_utils::env_exports::Balance;
 ///
-/// let amm = Amm::new(Curve::Linear, 1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000).expect("with correct parameters, Amm::new cannot fail");
+/// let amm = Amm::new(Curve::Linear, 1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000, DEFAULT_PRECISION).expect("with correct parameters, Amm::new cannot fail");
 /// // now use scale and shift without being able to confuse them.
 /// let Parameters { scale, shift } = amm.parameters();
 /// ```
@@ -46,47 +46,85 @@ pub struct Parameters {
     pub shift: Balance,
 }
 
+/// Which party benefits when a price calculation has to round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round up when buying and down when selling, so the pool never loses value to rounding.
+    TowardPool,
+    /// Truncate intermediate results, which can round in the user's favor.
+    TowardUser,
+}
+
+/// How the divisions of an integral are rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RoundingMode {
+    Truncate,
+    Ceil,
+    Floor,
+}
+
 pub(crate) const THREE: u32 = 3;
 pub(crate) const TWO: u32 = 2;
 const BASE: u128 = 10;
-const MAX_PRECISION: u32 = 12;
-// Positions to truncate during integral calculation to avoid overflow
-const TRUNCATE_POSITION: u32 = 6;
+/// The precision of 12-decimal funding tokens, which most pods use
+pub const DEFAULT_PRECISION: u32 = 12;
 
 impl Amm {
-    /// Create a new Amm. Will return None if over- or underflows occurred.
-    pub fn new(curve: Curve, initial_price: Balance, max_price: Balance, max_supply: Balance) -> Option<Amm> {
+    /// Create a new Amm for a funding token with `precision` decimals. Will return None if over- or underflows
+    /// occurred.
+    pub fn new(
+        curve: Curve,
+        initial_price: Balance,
+        max_price: Balance,
+        max_supply: Balance,
+        precision: u32,
+    ) -> Option<Amm> {
         let shift = initial_price;
+        let unit = BASE.checked_pow(precision)?;
 
         let amm = match curve {
             Curve::Linear => {
-                let quot = ((max_price.checked_sub(initial_price)?).checked_div_euclid(max_supply)?)
-                    .checked_mul(BASE.checked_pow(MAX_PRECISION)?)?;
+                let quot =
+                    ((max_price.checked_sub(initial_price)?).checked_div_euclid(max_supply)?).checked_mul(unit)?;
                 let rem = ((max_price.checked_sub(initial_price)?).checked_rem_euclid(max_supply)?)
-                    .checked_div(max_supply.checked_div(BASE.checked_pow(MAX_PRECISION)?)?)?;
-                Amm::Linear { scale: quot.checked_add(rem)?, shift }
+                    .checked_div(max_supply.checked_div(unit)?)?;
+                Amm::Linear { scale: quot.checked_add(rem)?, shift, precision }
             }
             Curve::Quadratic => {
                 let quot = (max_price.checked_sub(initial_price)?)
                     .checked_div_euclid(max_supply.checked_pow(2)?)?
-                    .checked_mul(BASE.checked_pow(MAX_PRECISION)?)?;
+                    .checked_mul(unit)?;
                 let rem = (max_price.checked_sub(initial_price)?)
                     .checked_rem_euclid(max_supply.checked_pow(2)?)?
-                    .checked_div((max_supply.checked_div(BASE.checked_pow(MAX_PRECISION)?)?).checked_pow(2)?)?;
-                Amm::Quadratic { scale: quot.checked_add(rem)?, shift }
+                    .checked_div((max_supply.checked_div(unit)?).checked_pow(2)?)?;
+                Amm::Quadratic { scale: quot.checked_add(rem)?, shift, precision }
             }
         };
         Some(amm)
     }
 
+    /// Whether the price of the Amm rises with the supply. A zero scale means a flat price, either because the
+    /// maximum price equals the initial price or because the slope was lost to the precision of the curve.
+    pub fn is_well_formed(&self) -> bool { self.parameters().scale > 0 }
+
     /// The parameters of the Amm.
     pub fn parameters(&self) -> Parameters {
         match self {
-            Amm::Quadratic { shift, scale } => Parameters { shift: *shift, scale: *scale },
-            Amm::Linear { shift, scale } => Parameters { shift: *shift, scale: *scale },
+            Amm::Quadratic { shift, scale, .. } => Parameters { shift: *shift, scale: *scale },
+            Amm::Linear { shift, scale, .. } => Parameters { shift: *shift, scale: *scale },
+        }
+    }
+
+    /// The number of decimals of the funding token.
+    pub fn precision(&self) -> u32 {
+        match self {
+            Amm::Quadratic { precision, .. } | Amm::Linear { precision, .. } => *precision,
         }
     }
 
+    /// Positions to truncate during integral calculation to avoid overflow.
+    fn truncate_position(&self) -> u32 { self.precision() / 2 }
+
     fn exponent(&self) -> u32 {
         match self {
             Amm::Quadratic { .. } => THREE,
@@ -96,56 +134,89 @@ impl Amm {
 
     /// Computes the integral of the Amm curve
     pub fn integral(&self, lower: Balance, upper: Balance) -> Option<Balance> {
+        self.integral_rounded(lower, upper, RoundingMode::Truncate)
+    }
+
+    /// Computes the integral of the Amm curve. `Ceil` never returns less than `Truncate`, and `Floor` never more.
+    fn integral_rounded(&self, lower: Balance, upper: Balance, mode: RoundingMode) -> Option<Balance> {
         let Parameters { shift, scale } = self.parameters();
+        let precision = self.precision();
+        let truncate_position = self.truncate_position();
+        let up = mode == RoundingMode::Ceil;
+        let term2 = upper.checked_sub(lower)?;
 
-        // truncate lower digits to avoid overflow
-        let _upper = upper.checked_div(BASE.checked_pow(TRUNCATE_POSITION)?)?;
-        let _lower = lower.checked_div(BASE.checked_pow(TRUNCATE_POSITION)?)?;
-        let rem_pos = MAX_PRECISION.checked_sub(TRUNCATE_POSITION)?;
+        // truncate lower digits to avoid overflow. Rounding the bounds apart or together widens or narrows the range.
+        let _upper = div(upper, BASE.checked_pow(truncate_position)?, up)?;
+        let _lower = div(lower, BASE.checked_pow(truncate_position)?, mode == RoundingMode::Floor)?;
+        let rem_pos = precision.checked_sub(truncate_position)?;
 
         let exp = self.exponent();
-        let mut term1 = _upper.checked_pow(exp)?.checked_sub(_lower.checked_pow(exp)?)?;
+        let mut term1 = _upper.checked_pow(exp)?.saturating_sub(_lower.checked_pow(exp)?);
 
         let mut rem_pos = rem_pos.checked_mul(exp)?;
 
-        if rem_pos > MAX_PRECISION {
-            rem_pos = rem_pos.checked_sub(MAX_PRECISION)?;
-            term1 = term1.checked_div_euclid(BASE.checked_pow(rem_pos)?)?;
+        if rem_pos > precision {
+            rem_pos = rem_pos.checked_sub(precision)?;
+            term1 = div(term1, BASE.checked_pow(rem_pos)?, up)?;
         } else {
-            rem_pos = MAX_PRECISION.checked_sub(rem_pos)?;
+            rem_pos = precision.checked_sub(rem_pos)?;
             term1 = term1.checked_mul(BASE.checked_pow(rem_pos)?)?;
         }
 
-        let term2 = upper.checked_sub(lower)?;
-        let integral = term1.checked_div(exp as u128)?.checked_add(term2)?;
-        scale
-            .checked_mul(integral.checked_div(exp as u128)?)?
-            .checked_div_euclid(BASE.checked_pow(MAX_PRECISION)?)?
-            .checked_add(shift)
+        let integral = div(term1, exp as u128, up)?.checked_add(term2)?;
+        div(scale.checked_mul(div(integral, exp as u128, up)?)?, BASE.checked_pow(precision)?, up)?.checked_add(shift)
     }
 
     /// Computes the market price of the token.
     pub fn market_price(&self, supply_released: Balance) -> Option<Balance> {
         match self {
-            Amm::Linear { scale, shift } => {
-                scale.checked_mul(supply_released)?.checked_div(BASE.checked_pow(MAX_PRECISION)?)?.checked_add(*shift)
+            Amm::Linear { scale, shift, precision } => {
+                scale.checked_mul(supply_released)?.checked_div(BASE.checked_pow(*precision)?)?.checked_add(*shift)
             }
-            Amm::Quadratic { scale, shift } => scale
+            Amm::Quadratic { scale, shift, precision } => scale
                 .checked_mul(supply_released.checked_pow(TWO)?)?
-                .checked_div(BASE.checked_pow(MAX_PRECISION)?.checked_pow(TWO)?)?
+                .checked_div(BASE.checked_pow(*precision)?.checked_pow(TWO)?)?
                 .checked_add(*shift),
         }
     }
 
     /// Determines the amount of funding tokens to pay for purchasing `amount` pod tokens.
     pub fn buy(&self, supply_released: Balance, amount: Balance) -> Option<Balance> {
-        self.integral(supply_released, supply_released.checked_add(amount)?)
+        self.buy_rounded(supply_released, amount, Rounding::TowardUser)
     }
 
     /// Determines the amount of Y of funding tokens to receive after selling X pod tokens.
     pub fn sell(&self, supply_released: Balance, amount: Balance) -> Option<Balance> {
+        self.sell_rounded(supply_released, amount, Rounding::TowardUser)
+    }
+
+    /// Like [`Amm::buy`], with `Rounding::TowardPool` rounding the price up.
+    pub fn buy_rounded(&self, supply_released: Balance, amount: Balance, rounding: Rounding) -> Option<Balance> {
+        let mode = match rounding {
+            Rounding::TowardPool => RoundingMode::Ceil,
+            Rounding::TowardUser => RoundingMode::Truncate,
+        };
+        self.integral_rounded(supply_released, supply_released.checked_add(amount)?, mode)
+    }
+
+    /// Like [`Amm::sell`], with `Rounding::TowardPool` rounding the reward down.
+    pub fn sell_rounded(&self, supply_released: Balance, amount: Balance, rounding: Rounding) -> Option<Balance> {
+        let mode = match rounding {
+            Rounding::TowardPool => RoundingMode::Floor,
+            Rounding::TowardUser => RoundingMode::Truncate,
+        };
         let left = supply_released.checked_sub(amount)?;
-        self.integral(left, supply_released)
+        self.integral_rounded(left, supply_released, mode)
+    }
+}
+
+/// Divides `n` by `d`, rounding up if `up` is set and down otherwise.
+fn div(n: Balance, d: Balance, up: bool) -> Option<Balance> {
+    let quot = n.checked_div(d)?;
+    if up && n % d != 0 {
+        quot.checked_add(1)
+    } else {
+        Some(quot)
     }
 }
 
@@ -164,59 +235,90 @@ mod tests {
     #[test]
     fn test_new() {
         assert_eq!(
-            Amm::Linear { scale: 0, shift: 1_000_000_000_000 },
-            Amm::new(Curve::Linear, 1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000).unwrap()
+            Amm::Linear { scale: 0, shift: 1_000_000_000_000, precision: 12 },
+            Amm::new(Curve::Linear, 1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000, 12).unwrap()
         );
         assert_eq!(
-            Amm::Linear { scale: 9_000_000_000_000 as u128, shift: 1_000_000_000_000 as u128 },
-            Amm::new(Curve::Linear, 1_000_000_000_000 as u128, 10_000_000_000_000 as u128, 1_000_000_000_000 as u128)
-                .unwrap()
+            Amm::Linear { scale: 9_000_000_000_000 as u128, shift: 1_000_000_000_000 as u128, precision: 12 },
+            Amm::new(
+                Curve::Linear,
+                1_000_000_000_000 as u128,
+                10_000_000_000_000 as u128,
+                1_000_000_000_000 as u128,
+                12
+            )
+            .unwrap()
         );
         assert_eq!(
-            Amm::Linear { scale: 90_000_000_000, shift: 1_000_000_000_000 },
-            Amm::new(Curve::Linear, 1_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000).unwrap()
+            Amm::Linear { scale: 90_000_000_000, shift: 1_000_000_000_000, precision: 12 },
+            Amm::new(Curve::Linear, 1_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap()
         );
         assert_eq!(
-            Amm::Linear { scale: 9_000_000_000_000 as u128, shift: 1_000_000_000_000 as u128 },
-            Amm::new(Curve::Linear, 1_000_000_000_000 as u128, 10_000_000_000_000 as u128, 1_000_000_000_000 as u128)
-                .unwrap()
+            Amm::Linear { scale: 9_000_000_000_000 as u128, shift: 1_000_000_000_000 as u128, precision: 12 },
+            Amm::new(
+                Curve::Linear,
+                1_000_000_000_000 as u128,
+                10_000_000_000_000 as u128,
+                1_000_000_000_000 as u128,
+                12
+            )
+            .unwrap()
         );
         assert_eq!(
-            Amm::Linear { scale: 50_000_000_000, shift: 5_000_000_000_000 },
-            Amm::new(Curve::Linear, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000).unwrap()
+            Amm::Linear { scale: 50_000_000_000, shift: 5_000_000_000_000, precision: 12 },
+            Amm::new(Curve::Linear, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap()
         );
         assert_eq!(
-            Amm::Linear { scale: 990_000_000_000, shift: 1_000_000_000_000 },
-            Amm::new(Curve::Linear, 1_000_000_000_000, 100_000_000_000_000, 100_000_000_000_000).unwrap()
+            Amm::Linear { scale: 990_000_000_000, shift: 1_000_000_000_000, precision: 12 },
+            Amm::new(Curve::Linear, 1_000_000_000_000, 100_000_000_000_000, 100_000_000_000_000, 12).unwrap()
         );
         assert_eq!(
-            Amm::Linear { scale: 999_000_000_000, shift: 1_000_000_000_000 },
-            Amm::new(Curve::Linear, 1_000_000_000_000, 1000_000_000_000_000, 1000_000_000_000_000).unwrap()
+            Amm::Linear { scale: 999_000_000_000, shift: 1_000_000_000_000, precision: 12 },
+            Amm::new(Curve::Linear, 1_000_000_000_000, 1000_000_000_000_000, 1000_000_000_000_000, 12).unwrap()
         );
 
         assert_eq!(
-            Amm::Quadratic { scale: 0, shift: 1_000_000_000_000 },
-            Amm::new(Curve::Quadratic, 1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000).unwrap()
+            Amm::Quadratic { scale: 0, shift: 1_000_000_000_000, precision: 12 },
+            Amm::new(Curve::Quadratic, 1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000, 12).unwrap()
         );
         assert_eq!(
-            Amm::Quadratic { scale: 9_000_000_000_000, shift: 1_000_000_000_000 },
-            Amm::new(Curve::Quadratic, 1_000_000_000_000, 10_000_000_000_000, 1_000_000_000_000).unwrap()
+            Amm::Quadratic { scale: 9_000_000_000_000, shift: 1_000_000_000_000, precision: 12 },
+            Amm::new(Curve::Quadratic, 1_000_000_000_000, 10_000_000_000_000, 1_000_000_000_000, 12).unwrap()
         );
 
         assert_eq!(
-            Amm::Quadratic { scale: 900_000_000, shift: 1_000_000_000_000 },
-            Amm::new(Curve::Quadratic, 1_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000).unwrap()
+            Amm::Quadratic { scale: 900_000_000, shift: 1_000_000_000_000, precision: 12 },
+            Amm::new(Curve::Quadratic, 1_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap()
         );
 
         assert_eq!(
-            Amm::Quadratic { scale: 500_000_000, shift: 5_000_000_000_000 },
-            Amm::new(Curve::Quadratic, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000).unwrap()
+            Amm::Quadratic { scale: 500_000_000, shift: 5_000_000_000_000, precision: 12 },
+            Amm::new(Curve::Quadratic, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap()
         );
     }
 
+    #[test]
+    fn test_is_well_formed() {
+        // flat curves, because the price does not rise or the slope is below the precision
+        assert!(!Amm::new(Curve::Linear, 1_000_000_000_000, 1_000_000_000_000, 1_000_000_000_000, 12)
+            .unwrap()
+            .is_well_formed());
+        assert!(!Amm::new(Curve::Quadratic, 1_000_000_000_000, 1_000_000_000_001, 100_000_000_000_000, 12)
+            .unwrap()
+            .is_well_formed());
+
+        // sloped curves
+        assert!(Amm::new(Curve::Linear, 1_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12)
+            .unwrap()
+            .is_well_formed());
+        assert!(Amm::new(Curve::Quadratic, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12)
+            .unwrap()
+            .is_well_formed());
+    }
+
     #[test]
     fn test_sell() {
-        let amm = Amm::new(Curve::Quadratic, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000).unwrap();
+        let amm = Amm::new(Curve::Quadratic, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap();
         assert_eq!(5_057_222_222_222, amm.sell(10_000_000_000_000, 10_000_000_000_000).unwrap());
         assert_eq!(18_692_000_000_000, amm.sell(100_000_000_000_000, 9_000_000_000_000).unwrap());
         assert_eq!(16_301_328_722_222, amm.sell(100_000_000_000_000, 7_300_000_000_000).unwrap());
@@ -229,13 +331,13 @@ mod tests {
             amm.sell(1_000_000_000_000_000_000, 7_000_300_000_000_000).unwrap()
         );
 
-        let amm = Amm::new(Curve::Linear, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000).unwrap();
+        let amm = Amm::new(Curve::Linear, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap();
         assert_eq!(6_500_000_000_000, amm.sell(10_000_000_000_000, 10_000_000_000_000).unwrap());
         assert_eq!(26_712_500_000_000, amm.sell(100_000_000_000_000, 9_000_000_000_000).unwrap());
         assert_eq!(22_766_375_000_000, amm.sell(100_000_000_000_000, 7_300_000_000_000).unwrap());
 
         // Adding more range check
-        let amm = Amm::new(Curve::Linear, 5_000_000_000_000, 10_000_000_000_000, 1000_000_000_000_000).unwrap();
+        let amm = Amm::new(Curve::Linear, 5_000_000_000_000, 10_000_000_000_000, 1000_000_000_000_000, 12).unwrap();
         assert_eq!(23_201_637_500_000, amm.sell(1_000_000_000_000_000, 7_300_000_000_000).unwrap());
         assert_eq!(187_451_637_500_000, amm.sell(10_000_000_000_000_000, 7_300_000_000_000).unwrap());
         assert_eq!(47_561_137_500_000, amm.sell(200_000_000_000_000, 121_300_000_000_000).unwrap());
@@ -246,21 +348,75 @@ mod tests {
 
     #[test]
     fn test_buy() {
-        let amm = Amm::new(Curve::Quadratic, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000).unwrap();
+        let amm = Amm::new(Curve::Quadratic, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap();
         assert_eq!(5_390_555_555_555, amm.buy(10_000_000_000_000, 10_000_000_000_000).unwrap());
         assert_eq!(21_392_000_000_000, amm.buy(100_000_000_000_000, 9_000_000_000_000).unwrap());
         assert_eq!(18_077_662_055_555, amm.buy(100_000_000_000_000, 7_300_000_000_000).unwrap());
 
-        let amm = Amm::new(Curve::Linear, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000).unwrap();
+        let amm = Amm::new(Curve::Linear, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap();
         assert_eq!(9_000_000_000_000, amm.buy(10_000_000_000_000, 10_000_000_000_000).unwrap());
         assert_eq!(28_737_500_000_000, amm.buy(100_000_000_000_000, 9_000_000_000_000).unwrap());
         assert_eq!(8_638_625_000_000, amm.buy(10_000_000_000_000, 9_300_000_000_000).unwrap());
         assert_eq!(248_578_625_000_000, amm.buy(100_000_000_000_000, 71_300_000_000_000).unwrap());
     }
 
+    #[test]
+    fn test_six_decimals() {
+        // the 12-decimal vectors scaled down to a 6-decimal funding token
+        let amm = Amm::new(Curve::Quadratic, 5_000_000, 10_000_000, 100_000_000, 6).unwrap();
+        assert_eq!(Amm::Quadratic { scale: 500, shift: 5_000_000, precision: 6 }, amm);
+        assert_eq!(5_057_222, amm.sell(10_000_000, 10_000_000).unwrap());
+        assert_eq!(18_692_000, amm.sell(100_000_000, 9_000_000).unwrap());
+        assert_eq!(5_390_555, amm.buy(10_000_000, 10_000_000).unwrap());
+        assert_eq!(21_392_000, amm.buy(100_000_000, 9_000_000).unwrap());
+        assert_eq!(5_050_000, amm.market_price(10_000_000).unwrap());
+
+        let amm = Amm::new(Curve::Linear, 5_000_000, 10_000_000, 100_000_000, 6).unwrap();
+        assert_eq!(Amm::Linear { scale: 50_000, shift: 5_000_000, precision: 6 }, amm);
+        assert_eq!(6_500_000, amm.sell(10_000_000, 10_000_000).unwrap());
+        assert_eq!(26_712_500, amm.sell(100_000_000, 9_000_000).unwrap());
+        assert_eq!(9_000_000, amm.buy(10_000_000, 10_000_000).unwrap());
+        assert_eq!(28_737_500, amm.buy(100_000_000, 9_000_000).unwrap());
+        assert_eq!(5_500_000, amm.market_price(10_000_000).unwrap());
+
+        // a supply below one unit of the default precision can't be priced
+        assert_eq!(None, Amm::new(Curve::Linear, 1_000_000, 100_000_000, 100_000_000, DEFAULT_PRECISION));
+        assert!(Amm::new(Curve::Linear, 1_000_000, 100_000_000, 100_000_000, 6).unwrap().is_well_formed());
+    }
+
+    #[test]
+    fn test_rounding_toward_pool() {
+        let quadratic =
+            Amm::new(Curve::Quadratic, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap();
+        let linear = Amm::new(Curve::Linear, 5_000_000_000_000, 10_000_000_000_000, 100_000_000_000_000, 12).unwrap();
+        let cases: [(Balance, Balance); 6] = [
+            (10_000_000_000_000, 10_000_000_000_000),
+            (100_000_000_000_000, 9_000_000_000_000),
+            (100_000_000_000_000, 7_300_000_000_000),
+            (110_000_000_000_000, 11_000_000_000_000),
+            (10_000_000_000_000, 9_300_000_000_000),
+            (100_000_000_000_123, 7_300_000_000_456),
+        ];
+
+        for amm in [quadratic, linear].iter() {
+            for (supply, amount) in cases.iter().copied() {
+                let buy = amm.buy(supply, amount).unwrap();
+                let sell = amm.sell(supply, amount).unwrap();
+                assert!(amm.buy_rounded(supply, amount, Rounding::TowardPool).unwrap() >= buy, "{:?}", amm);
+                assert!(amm.sell_rounded(supply, amount, Rounding::TowardPool).unwrap() <= sell, "{:?}", amm);
+                assert_eq!(amm.buy_rounded(supply, amount, Rounding::TowardUser), Some(buy));
+                assert_eq!(amm.sell_rounded(supply, amount, Rounding::TowardUser), Some(sell));
+            }
+        }
+
+        // quadratic prices are not exact, so rounding toward the pool charges more
+        let price = quadratic.buy_rounded(10_000_000_000_000, 10_000_000_000_000, Rounding::TowardPool);
+        assert_eq!(Some(5_390_555_555_556), price);
+    }
+
     #[test]
     fn test_integral_linear() {
-        let amm = Amm::Linear { scale: 10_000_000_000_000, shift: 1_000_000_000_000 };
+        let amm = Amm::Linear { scale: 10_000_000_000_000, shift: 1_000_000_000_000, precision: 12 };
         assert_eq!(1_000_000_000_000, amm.integral(0, 0).unwrap());
         assert_eq!(8_500_000_000_000, amm.integral(0, 1_000_000_000_000).unwrap());
         assert_eq!(140_437_201_000_000_000, amm.integral(1_000_000_000_000, 236_020_000_000_000).unwrap());
@@ -269,7 +425,7 @@ mod tests {
 
     #[test]
     fn test_integral_quadratic() {
-        let amm = Amm::Quadratic { scale: 10_000_000_000_000, shift: 1_000_000_000_000 };
+        let amm = Amm::Quadratic { scale: 10_000_000_000_000, shift: 1_000_000_000_000, precision: 12 };
         assert_eq!(1_000_000_000_000, amm.integral(0, 0).unwrap());
         // assert_eq!(5_444_444_444_444, amm.integral(0, 1_000_000_000_000).unwrap()); // original test case
         assert_eq!(5_444_444_444_440, amm.integral(0, 1_000_000_000_000).unwrap()); // slight change in satoshi
@@ -319,10 +475,10 @@ mod tests {
         ];
 
         for test in test_cases {
-            let amm = Amm::new(Curve::Linear, test.initial_price, test.max_price, test.max_supply).unwrap();
+            let amm = Amm::new(Curve::Linear, test.initial_price, test.max_price, test.max_supply, 12).unwrap();
             assert_eq!(test.linear_result, amm.market_price(test.supply_released), "linear testcase: {:?}", test);
 
-            let amm = Amm::new(Curve::Quadratic, test.initial_price, test.max_price, test.max_supply).unwrap();
+            let amm = Amm::new(Curve::Quadratic, test.initial_price, test.max_price, test.max_supply, 12).unwrap();
             assert_eq!(test.quadratic_result, amm.market_price(test.supply_released), "quadratic testcase: {:?}", test)
         }
     }
@@ -3,11 +3,13 @@
 pub mod constants;
 mod log;
 pub mod math;
+mod ownable;
 pub mod test_utils;
 pub mod time;
 mod token;
 
 pub use log::*;
+pub use ownable::*;
 pub use token::*;
 
 #[cfg(feature = "decimal")]
@@ -96,4 +98,11 @@ mod tests {
         let hash = Hash::from([7_u8; 32]);
         assert_eq!(Hash::decode(&mut &hash.encode()[..]).unwrap(), hash);
     }
+
+    /// The token contracts repeat the selectors as literals, which have to match the constants
+    #[test]
+    fn token_selectors_match_literals() {
+        assert_eq!(TOKEN_STANDARD_SELECTOR, [0x74, 0x6f, 0x6b, 0x73]);
+        assert_eq!(SUPPORTS_SELECTOR, [0x73, 0x75, 0x70, 0x70]);
+    }
 }
@@ -21,3 +21,35 @@ macro_rules! ink_log_err {
         }
     };
 }
+
+#[cfg(feature = "debug-logs")]
+#[macro_export]
+/// Prints a formatted message with `ink_env::debug_println`. Compiles to nothing unless the `debug-logs` feature of
+/// `contract-utils` is enabled.
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        ink_env::debug_println(&ink_prelude::format!($($arg)*))
+    };
+}
+
+#[cfg(not(feature = "debug-logs"))]
+#[macro_export]
+/// Prints a formatted message with `ink_env::debug_println`. Compiles to nothing unless the `debug-logs` feature of
+/// `contract-utils` is enabled.
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        // keep the arguments type checked and "used" without formatting anything
+        if false {
+            let _ = core::format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_debug_log() {
+        let amount = 100;
+        debug_log!("bid of {} from {:?}", amount, crate::ZERO_ACCOUNT);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::env_exports::AccountId;
+use ink_storage::traits::{PackedLayout, SpreadLayout};
+use scale::{Decode, Encode};
+
+/// Errors returned by `Ownable2Step`
+#[derive(Debug, Encode, Decode, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum OwnableError {
+    /// Only the owner may perform this operation
+    NotOwner,
+    /// Only the pending owner may accept ownership
+    NotPendingOwner,
+}
+
+/// Contract ownership that is transferred in two steps: the owner proposes a new owner, who then has to accept.
+/// This prevents handing the contract to an account that can never use it, e.g. a mistyped address.
+#[derive(Debug, Encode, Decode, SpreadLayout, PackedLayout, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
+pub struct Ownable2Step {
+    owner: AccountId,
+    pending_owner: Option<AccountId>,
+}
+
+impl Ownable2Step {
+    /// Create a new instance owned by `owner`
+    pub fn new(owner: AccountId) -> Self { Self { owner, pending_owner: None } }
+
+    /// The current owner
+    pub fn owner(&self) -> AccountId { self.owner }
+
+    /// The account that may accept ownership, if a transfer is in progress
+    pub fn pending_owner(&self) -> Option<AccountId> { self.pending_owner }
+
+    /// Returns an error if `caller` is not the owner
+    pub fn ensure_owner(&self, caller: AccountId) -> Result<(), OwnableError> {
+        if caller != self.owner {
+            return Err(OwnableError::NotOwner);
+        }
+        Ok(())
+    }
+
+    /// Proposes `new_owner` as the owner. Ownership only changes once `new_owner` calls `accept_ownership`. A
+    /// later call replaces the pending owner.
+    pub fn transfer_ownership(&mut self, caller: AccountId, new_owner: AccountId) -> Result<(), OwnableError> {
+        self.ensure_owner(caller)?;
+        self.pending_owner = Some(new_owner);
+        Ok(())
+    }
+
+    /// Makes the pending owner the owner. Must be called by the pending owner.
+    pub fn accept_ownership(&mut self, caller: AccountId) -> Result<(), OwnableError> {
+        if self.pending_owner != Some(caller) {
+            return Err(OwnableError::NotPendingOwner);
+        }
+        self.owner = caller;
+        self.pending_owner = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_step_transfer() {
+        let (alice, bob, eve) = (AccountId::from([1; 32]), AccountId::from([2; 32]), AccountId::from([3; 32]));
+        let mut ownable = Ownable2Step::new(alice);
+
+        // only the owner may propose
+        assert_eq!(ownable.transfer_ownership(bob, bob), Err(OwnableError::NotOwner));
+
+        // proposing does not change the owner
+        ownable.transfer_ownership(alice, bob).unwrap();
+        assert_eq!(ownable.owner(), alice);
+        assert_eq!(ownable.pending_owner(), Some(bob));
+
+        // another account cannot accept
+        assert_eq!(ownable.accept_ownership(eve), Err(OwnableError::NotPendingOwner));
+        assert_eq!(ownable.owner(), alice);
+
+        // the pending owner can
+        ownable.accept_ownership(bob).unwrap();
+        assert_eq!(ownable.owner(), bob);
+        assert_eq!(ownable.pending_owner(), None);
+        assert_eq!(ownable.ensure_owner(alice), Err(OwnableError::NotOwner));
+    }
+}
@@ -7,3 +7,24 @@ pub const DAY: Timestamp = 24 * HOUR;
 pub const WEEK: Timestamp = 7 * DAY;
 pub const MONTH: Timestamp = 30 * DAY;
 pub const YEAR: Timestamp = 365 * DAY;
+
+/// Returns the whole number of seconds between `start` and `stop`, or `None` if `stop` is before `start`
+pub fn duration_secs(start: Timestamp, stop: Timestamp) -> Option<u64> {
+    stop.checked_sub(start).map(|millis| core::time::Duration::from_millis(millis).as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_secs() {
+        assert_eq!(duration_secs(1000, 1000 + 5 * MINUTE), Some(300));
+        // partial seconds are truncated
+        assert_eq!(duration_secs(0, 1999), Some(1));
+        // zero-length range
+        assert_eq!(duration_secs(1000, 1000), Some(0));
+        // inverted range
+        assert_eq!(duration_secs(2000, 1000), None);
+    }
+}
@@ -1,6 +1,30 @@
 use ink_storage::traits::{PackedLayout, SpreadLayout};
 use scale::{Decode, Encode};
 
+/// The selector of the `token_standard` message every token contract implements. ink! only accepts a literal in
+/// `#[ink(selector = ...)]`, so the contracts repeat it as `"0x746f6b73"`.
+pub const TOKEN_STANDARD_SELECTOR: [u8; 4] = *b"toks";
+
+/// The selector of the `supports` message every token contract implements, repeated as `"0x73757070"`.
+pub const SUPPORTS_SELECTOR: [u8; 4] = *b"supp";
+
+/// Bits of the feature set passed to the `supports` message of the token contracts. A contract supports a set of
+/// features if it supports every feature in it.
+pub mod token_feature {
+    /// Minting new tokens
+    pub const MINT: u32 = 1;
+    /// Burning tokens
+    pub const BURN: u32 = 1 << 1;
+    /// Pausing transfers
+    pub const PAUSE: u32 = 1 << 2;
+    /// Reading token metadata, e.g. the name of an ERC-20 or the metadata of an NFT
+    pub const METADATA: u32 = 1 << 3;
+    /// Paying royalties on transfers
+    pub const ROYALTIES: u32 = 1 << 4;
+    /// Transferring several tokens in one call
+    pub const BATCH: u32 = 1 << 5;
+}
+
 /// The token standard of the contract
 #[derive(Debug, Encode, Decode, SpreadLayout, PackedLayout, Copy, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout))]
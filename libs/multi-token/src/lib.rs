@@ -46,6 +46,13 @@ pub enum Error {
     /// ERC-1155 error
     #[error(display = "ERC-1155 error: {}", _0)]
     Erc1155(#[source] erc1155::Error),
+    /// A token is already registered under the symbol
+    #[error(display = "a token is already registered for symbol: {}", _0)]
+    SymbolAlreadyRegistered(ink_prelude::string::String),
+    /// TokenAccounts error
+    #[cfg(feature = "token-accounts")]
+    #[error(display = "TokenAccounts error: {}", _0)]
+    TokenAccounts(#[source] token_accounts::Error),
 }
 
 /// The Result type for this crate
@@ -138,6 +145,15 @@ impl MultiToken {
         Ok(())
     }
 
+    /// Revokes every approval the caller has given to `operator`. For ERC-20 the allowance is set to zero, for
+    /// ERC-721 and ERC-1155 the operator approval is removed.
+    pub fn revoke_all(&mut self, operator: AccountId) -> Result<()> {
+        match self.standard {
+            TokenStandard::Erc20 => erc20_approve(*self, operator, 0),
+            TokenStandard::Erc721 | TokenStandard::Erc1155 => set_approval_for_all(*self, operator, false),
+        }
+    }
+
     /// Returns the amount which `spender` is allowed to withdraw from `owner`.
     pub fn allowance(&mut self, owner: AccountId, spender: AccountId) -> Option<Balance> {
         match self.standard {
@@ -211,19 +227,30 @@ impl MultiToken {
         Ok(())
     }
 
-    /// Returns the balance of `account`
+    /// Returns the balance of `account`. For ERC-721 this is the number of tokens `account` owns.
+    ///
+    /// # Panics
+    ///
+    /// Panics for ERC-1155 tokens, whose balances are per id. That arm is deprecated, use `balance_of_token` instead.
     pub fn balance_of(&mut self, account: AccountId) -> Balance {
         match self.standard {
-            TokenStandard::Erc20 => self.as_erc20_unchecked().balance_of(account),
-            TokenStandard::Erc721 => {
-                // TODO: implement allowance for erc721
-                unimplemented!("allowance is not implemented for erc721")
+            TokenStandard::Erc20 | TokenStandard::Erc721 => {
+                self.balance_of_token(account, None).expect("the token id is only required for erc1155")
             }
+            TokenStandard::Erc1155 => unimplemented!("balance_of is deprecated for erc1155, use balance_of_token"),
+        }
+    }
+
+    /// Returns the balance of `account`. `token_id` selects the token for ERC-1155 and is ignored otherwise. For
+    /// ERC-721 this is the number of tokens `account` owns.
+    pub fn balance_of_token(&mut self, account: AccountId, token_id: impl Into<Option<TokenId>>) -> Result<Balance> {
+        Ok(match self.standard {
+            TokenStandard::Erc20 => erc20_balance_of(*self, account),
+            TokenStandard::Erc721 => Balance::from(erc721_balance_of(*self, account)),
             TokenStandard::Erc1155 => {
-                // TODO: implement allowance for erc1155
-                unimplemented!("allowance is not implemented for erc1155")
+                erc1155_balance_of(*self, account, token_id.into().ok_or(Error::TokenIdRequired)?)
             }
-        }
+        })
     }
 
     /// Safe convert to Erc20
@@ -234,6 +261,16 @@ impl MultiToken {
         Ok(self.as_erc20_unchecked())
     }
 
+    /// Asks the token contract whether it supports every feature in `feature`, a set of
+    /// `contract_utils::token_feature` bits
+    pub fn supports(&self, feature: u32) -> bool {
+        match self.standard {
+            TokenStandard::Erc20 => self.as_erc20_unchecked().supports(feature),
+            TokenStandard::Erc721 => self.as_erc721_unchecked().supports(feature),
+            TokenStandard::Erc1155 => self.as_erc1155_unchecked().supports(feature),
+        }
+    }
+
     /// Convert to Erc20 without checking the token standard
     pub fn as_erc20_unchecked(&self) -> Erc20 { FromAccountId::from_account_id(self.account_id) }
 
@@ -264,3 +301,402 @@ impl MultiToken {
 impl From<token_accounts::Token> for MultiToken {
     fn from(token: token_accounts::Token) -> Self { Self { account_id: token.account_id, standard: token.standard } }
 }
+
+#[cfg(feature = "token-accounts")]
+impl MultiToken {
+    /// Like `From<Token>`, but first asks the token contract for its standard. Fails with `InvalidTokenStandard` if
+    /// the contract implements a different standard than the token was registered with, or can't be asked.
+    pub fn try_from_verified(token: token_accounts::Token) -> Result<Self> {
+        match query_token_standard(token.account_id) {
+            Ok(standard) if standard == token.standard => Ok(token.into()),
+            _ => Err(Error::InvalidTokenStandard),
+        }
+    }
+}
+
+/// Returns the balance of `account` for every ERC-20 registered in the token-accounts contract `token_accounts`,
+/// keyed by symbol. Tokens of other standards are skipped.
+#[cfg(feature = "token-accounts")]
+pub fn balance_of_all_tokens(
+    token_accounts: AccountId,
+    account: AccountId,
+) -> Vec<(ink_prelude::string::String, Balance)> {
+    get_all_tokens(token_accounts)
+        .into_iter()
+        .filter(|token| token.standard == TokenStandard::Erc20)
+        .map(|token| (token.symbol, erc20_balance_of(MultiToken::new(token.account_id, token.standard), account)))
+        .collect()
+}
+
+/// Calls `get_all_tokens` on the token-accounts contract `token_accounts`
+#[cfg(all(feature = "token-accounts", not(test)))]
+fn get_all_tokens(token_accounts: AccountId) -> Vec<token_accounts::TokenInfo> {
+    let registry: token_accounts::TokenAccounts = FromAccountId::from_account_id(token_accounts);
+    registry.get_all_tokens()
+}
+
+/// Calls `balance_of` on the ERC-20 `token`
+#[cfg(not(test))]
+fn erc20_balance_of(token: MultiToken, account: AccountId) -> Balance { token.as_erc20_unchecked().balance_of(account) }
+
+/// Calls `balance_of` on the ERC-721 `token`
+#[cfg(not(test))]
+fn erc721_balance_of(token: MultiToken, account: AccountId) -> u64 { token.as_erc721_unchecked().balance_of(account) }
+
+/// Calls `balance_of` on the ERC-1155 `token`
+#[cfg(not(test))]
+fn erc1155_balance_of(token: MultiToken, account: AccountId, token_id: TokenId) -> Balance {
+    token.as_erc1155_unchecked().balance_of(account, token_id)
+}
+
+#[cfg(all(test, feature = "token-accounts"))]
+use tests::mock_token_accounts::{get_all_tokens, query_token_standard};
+/// Calls `approve` on the ERC-20 `token`
+#[cfg(not(test))]
+fn erc20_approve(token: MultiToken, spender: AccountId, amount: Balance) -> Result<()> {
+    Ok(token.as_erc20_unchecked().approve(spender, amount)?)
+}
+
+/// Calls `set_approval_for_all` on the ERC-721 or ERC-1155 `token`
+#[cfg(not(test))]
+fn set_approval_for_all(token: MultiToken, operator: AccountId, approved: bool) -> Result<()> {
+    match token.standard {
+        TokenStandard::Erc20 => Err(Error::InvalidTokenStandard),
+        TokenStandard::Erc721 => Ok(token.as_erc721_unchecked().set_approval_for_all(operator, approved)?),
+        TokenStandard::Erc1155 => Ok(token.as_erc1155_unchecked().set_approval_for_all(operator, approved)?),
+    }
+}
+
+#[cfg(test)]
+use tests::mock_tokens::{
+    erc1155_balance_of, erc20_approve, erc20_balance_of, erc721_balance_of, set_approval_for_all,
+};
+
+/// Calls `token_standard` on the token contract `account_id`
+#[cfg(all(feature = "token-accounts", not(test)))]
+fn query_token_standard(account_id: AccountId) -> ink_env::Result<TokenStandard> {
+    use contract_utils::TOKEN_STANDARD_SELECTOR;
+    use ink_env::call::{build_call, utils::ReturnType, ExecutionInput, Selector};
+
+    build_call::<ink_env::DefaultEnvironment>()
+        .callee(account_id)
+        .gas_limit(0)
+        .exec_input(ExecutionInput::new(Selector::new(TOKEN_STANDARD_SELECTOR)))
+        .returns::<ReturnType<TokenStandard>>()
+        .fire()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "token-accounts")]
+    use ink_prelude::string::String;
+    #[cfg(feature = "token-accounts")]
+    use token_accounts::{Token, TokenAccounts, TokenInfo};
+
+    /// In-memory token state, as token contracts can't be called off-chain
+    pub mod mock_tokens {
+        use super::*;
+        use std::{cell::RefCell, collections::HashMap};
+
+        thread_local! {
+            pub static BALANCES: RefCell<HashMap<(AccountId, AccountId), Balance>> = RefCell::new(HashMap::new());
+            pub static ERC1155_BALANCES: RefCell<HashMap<(AccountId, AccountId, TokenId), Balance>> =
+                RefCell::new(HashMap::new());
+            /// ERC-20 allowances given by the caller, keyed by token and spender
+            pub static ALLOWANCES: RefCell<HashMap<(AccountId, AccountId), Balance>> = RefCell::new(HashMap::new());
+            /// operator approvals given by the caller, keyed by token and operator
+            pub static OPERATORS: RefCell<HashMap<(AccountId, AccountId), bool>> = RefCell::new(HashMap::new());
+        }
+
+        pub fn erc20_balance_of(token: MultiToken, account: AccountId) -> Balance {
+            assert_eq!(TokenStandard::Erc20, token.standard);
+            BALANCES.with(|x| x.borrow().get(&(token.account_id, account)).copied().unwrap_or_default())
+        }
+
+        pub fn erc721_balance_of(token: MultiToken, account: AccountId) -> u64 {
+            assert_eq!(TokenStandard::Erc721, token.standard);
+            BALANCES.with(|x| x.borrow().get(&(token.account_id, account)).map_or(0, |x| *x as u64))
+        }
+
+        pub fn erc1155_balance_of(token: MultiToken, account: AccountId, token_id: TokenId) -> Balance {
+            assert_eq!(TokenStandard::Erc1155, token.standard);
+            ERC1155_BALANCES
+                .with(|x| x.borrow().get(&(token.account_id, account, token_id)).copied().unwrap_or_default())
+        }
+
+        pub fn erc20_approve(token: MultiToken, spender: AccountId, amount: Balance) -> Result<()> {
+            assert_eq!(TokenStandard::Erc20, token.standard);
+            ALLOWANCES.with(|x| x.borrow_mut().insert((token.account_id, spender), amount));
+            Ok(())
+        }
+
+        pub fn set_approval_for_all(token: MultiToken, operator: AccountId, approved: bool) -> Result<()> {
+            assert_ne!(TokenStandard::Erc20, token.standard);
+            OPERATORS.with(|x| x.borrow_mut().insert((token.account_id, operator), approved));
+            Ok(())
+        }
+
+        pub fn allowance(token: AccountId, spender: AccountId) -> Balance {
+            ALLOWANCES.with(|x| x.borrow().get(&(token, spender)).copied().unwrap_or_default())
+        }
+
+        pub fn is_approved_for_all(token: AccountId, operator: AccountId) -> bool {
+            OPERATORS.with(|x| x.borrow().get(&(token, operator)).copied().unwrap_or_default())
+        }
+    }
+
+    #[cfg(feature = "token-accounts")]
+    pub mod mock_token_accounts {
+        use super::*;
+        use std::{cell::RefCell, collections::HashMap};
+
+        thread_local! {
+            pub static TOKENS: RefCell<Vec<TokenInfo>> = RefCell::new(Vec::new());
+            /// ERC-721 tokens minted through `mint_with_metadata`: token contract, token id, owner and metadata
+            pub static MINTED: RefCell<Vec<(AccountId, TokenId, AccountId, Vec<u8>)>> = RefCell::new(Vec::new());
+            /// what each token contract answers to `token_standard`, contracts without an entry trap
+            pub static STANDARDS: RefCell<HashMap<AccountId, TokenStandard>> = RefCell::new(HashMap::new());
+        }
+
+        pub fn query_token_standard(account_id: AccountId) -> ink_env::Result<TokenStandard> {
+            STANDARDS.with(|x| x.borrow().get(&account_id).copied().ok_or(ink_env::Error::CalleeTrapped))
+        }
+
+        pub fn get_all_tokens(_token_accounts: AccountId) -> Vec<TokenInfo> { TOKENS.with(|x| x.borrow().clone()) }
+
+        pub fn registered_token(_token_accounts: &TokenAccounts, symbol: String) -> Option<Token> {
+            TOKENS.with(|x| {
+                x.borrow()
+                    .iter()
+                    .find(|token| token.symbol == symbol)
+                    .map(|token| Token::new(token.account_id, token.standard))
+            })
+        }
+
+        pub fn register_token(_token_accounts: &mut TokenAccounts, symbol: String, token: MultiToken) -> Result<()> {
+            TOKENS.with(|x| {
+                x.borrow_mut().push(TokenInfo { symbol, account_id: token.account_id, standard: token.standard })
+            });
+            Ok(())
+        }
+
+        pub fn mint_with_metadata(token: MultiToken, recipient: AccountId, metadata: Vec<u8>) -> Result<TokenId> {
+            assert_eq!(TokenStandard::Erc721, token.standard);
+            MINTED.with(|x| {
+                let token_id = x.borrow().len() as TokenId + 1;
+                x.borrow_mut().push((token.account_id, token_id, recipient, metadata));
+                Ok(token_id)
+            })
+        }
+    }
+
+    #[cfg(feature = "token-accounts")]
+    fn register(symbol: &str, account_id: AccountId, standard: TokenStandard) {
+        mock_token_accounts::TOKENS
+            .with(|x| x.borrow_mut().push(TokenInfo { symbol: String::from(symbol), account_id, standard }));
+    }
+
+    /// Sets the ERC-20 balance, or the number of owned ERC-721 tokens, of `account`
+    fn set_balance(token: AccountId, account: AccountId, balance: Balance) {
+        mock_tokens::BALANCES.with(|x| x.borrow_mut().insert((token, account), balance));
+    }
+
+    fn set_erc1155_balance(token: AccountId, account: AccountId, token_id: TokenId, balance: Balance) {
+        mock_tokens::ERC1155_BALANCES.with(|x| x.borrow_mut().insert((token, account, token_id), balance));
+    }
+
+    #[test]
+    fn balance_of_token_works_for_erc20() {
+        let (token, alice) = (AccountId::from([10; 32]), AccountId::from([2; 32]));
+        let mut multi_token = MultiToken::new(token, TokenStandard::Erc20);
+        set_balance(token, alice, 100);
+
+        // the token id is ignored
+        assert_eq!(Ok(100), multi_token.balance_of_token(alice, None));
+        assert_eq!(Ok(100), multi_token.balance_of_token(alice, 7));
+        assert_eq!(Ok(0), multi_token.balance_of_token(AccountId::from([3; 32]), None));
+        assert_eq!(100, multi_token.balance_of(alice));
+    }
+
+    #[test]
+    fn balance_of_token_counts_erc721_tokens() {
+        let (token, alice) = (AccountId::from([10; 32]), AccountId::from([2; 32]));
+        let mut multi_token = MultiToken::new(token, TokenStandard::Erc721);
+        set_balance(token, alice, 3);
+
+        assert_eq!(Ok(3), multi_token.balance_of_token(alice, None));
+        assert_eq!(Ok(3), multi_token.balance_of_token(alice, 1));
+        assert_eq!(3, multi_token.balance_of(alice));
+    }
+
+    #[test]
+    fn balance_of_token_uses_erc1155_token_id() {
+        let (token, alice) = (AccountId::from([10; 32]), AccountId::from([2; 32]));
+        let mut multi_token = MultiToken::new(token, TokenStandard::Erc1155);
+        set_erc1155_balance(token, alice, 1, 50);
+        set_erc1155_balance(token, alice, 2, 5);
+
+        assert_eq!(Ok(50), multi_token.balance_of_token(alice, 1));
+        assert_eq!(Ok(5), multi_token.balance_of_token(alice, 2));
+        assert_eq!(Ok(0), multi_token.balance_of_token(alice, 3));
+    }
+
+    #[test]
+    fn balance_of_token_requires_erc1155_token_id() {
+        let mut multi_token = MultiToken::new(AccountId::from([10; 32]), TokenStandard::Erc1155);
+        assert_eq!(Err(Error::TokenIdRequired), multi_token.balance_of_token(AccountId::from([2; 32]), None));
+    }
+
+    #[test]
+    #[should_panic]
+    fn balance_of_panics_for_erc1155() {
+        MultiToken::new(AccountId::from([10; 32]), TokenStandard::Erc1155).balance_of(AccountId::from([2; 32]));
+    }
+
+    #[test]
+    fn revoke_all_clears_erc20_allowance() {
+        let (token, bob, charlie) = (AccountId::from([10; 32]), AccountId::from([3; 32]), AccountId::from([4; 32]));
+        let mut multi_token = MultiToken::new(token, TokenStandard::Erc20);
+        mock_tokens::erc20_approve(multi_token, bob, 100).unwrap();
+        mock_tokens::erc20_approve(multi_token, charlie, 50).unwrap();
+
+        assert_eq!(Ok(()), multi_token.revoke_all(bob));
+        assert_eq!(0, mock_tokens::allowance(token, bob));
+        assert_eq!(50, mock_tokens::allowance(token, charlie));
+    }
+
+    #[test]
+    fn revoke_all_removes_erc721_operator() {
+        let (token, bob, charlie) = (AccountId::from([10; 32]), AccountId::from([3; 32]), AccountId::from([4; 32]));
+        let mut multi_token = MultiToken::new(token, TokenStandard::Erc721);
+        mock_tokens::set_approval_for_all(multi_token, bob, true).unwrap();
+        mock_tokens::set_approval_for_all(multi_token, charlie, true).unwrap();
+
+        assert_eq!(Ok(()), multi_token.revoke_all(bob));
+        assert!(!mock_tokens::is_approved_for_all(token, bob));
+        assert!(mock_tokens::is_approved_for_all(token, charlie));
+    }
+
+    #[test]
+    fn revoke_all_removes_erc1155_operator() {
+        let (token, bob) = (AccountId::from([10; 32]), AccountId::from([3; 32]));
+        let mut multi_token = MultiToken::new(token, TokenStandard::Erc1155);
+        mock_tokens::set_approval_for_all(multi_token, bob, true).unwrap();
+
+        assert_eq!(Ok(()), multi_token.revoke_all(bob));
+        assert!(!mock_tokens::is_approved_for_all(token, bob));
+        // revoking twice is harmless
+        assert_eq!(Ok(()), multi_token.revoke_all(bob));
+        assert!(!mock_tokens::is_approved_for_all(token, bob));
+    }
+
+    #[cfg(feature = "token-accounts")]
+    fn unique_token(account_id: AccountId, standard: TokenStandard) -> UniqueMultiToken {
+        UniqueMultiToken { multi_token: MultiToken::new(account_id, standard), token_id: None }
+    }
+
+    #[test]
+    #[cfg(feature = "token-accounts")]
+    fn mint_and_register_works() {
+        let mut token_accounts: TokenAccounts = FromAccountId::from_account_id(AccountId::from([1; 32]));
+        let (nft, alice) = (AccountId::from([12; 32]), AccountId::from([2; 32]));
+        let mut token = unique_token(nft, TokenStandard::Erc721);
+
+        assert_eq!(Ok(1), token.mint_and_register(&mut token_accounts, String::from("NFT"), alice, vec![7]));
+        assert_eq!(Some(1), token.token_id);
+        mock_token_accounts::MINTED.with(|x| assert_eq!(vec![(nft, 1, alice, vec![7])], *x.borrow()));
+        assert_eq!(
+            vec![TokenInfo { symbol: String::from("NFT"), account_id: nft, standard: TokenStandard::Erc721 }],
+            mock_token_accounts::get_all_tokens(AccountId::from([1; 32]))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "token-accounts")]
+    fn mint_and_register_rejects_duplicate_symbol() {
+        let mut token_accounts: TokenAccounts = FromAccountId::from_account_id(AccountId::from([1; 32]));
+        let alice = AccountId::from([2; 32]);
+        register("NFT", AccountId::from([11; 32]), TokenStandard::Erc721);
+        let mut token = unique_token(AccountId::from([12; 32]), TokenStandard::Erc721);
+
+        assert_eq!(
+            Err(Error::SymbolAlreadyRegistered(String::from("NFT"))),
+            token.mint_and_register(&mut token_accounts, String::from("NFT"), alice, vec![7])
+        );
+        // nothing was minted or registered
+        assert_eq!(None, token.token_id);
+        mock_token_accounts::MINTED.with(|x| assert!(x.borrow().is_empty()));
+        assert_eq!(1, mock_token_accounts::get_all_tokens(AccountId::from([1; 32])).len());
+    }
+
+    #[test]
+    #[cfg(feature = "token-accounts")]
+    fn mint_and_register_requires_erc721() {
+        let mut token_accounts: TokenAccounts = FromAccountId::from_account_id(AccountId::from([1; 32]));
+        let mut token = unique_token(AccountId::from([10; 32]), TokenStandard::Erc20);
+        assert_eq!(
+            Err(Error::InvalidTokenStandard),
+            token.mint_and_register(&mut token_accounts, String::from("USDT"), AccountId::from([2; 32]), vec![])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "token-accounts")]
+    fn try_from_verified_accepts_matching_standard() {
+        let nft = AccountId::from([12; 32]);
+        mock_token_accounts::STANDARDS.with(|x| x.borrow_mut().insert(nft, TokenStandard::Erc721));
+
+        let multi_token = MultiToken::try_from_verified(Token::new(nft, TokenStandard::Erc721)).unwrap();
+        assert_eq!(nft, multi_token.account_id);
+        assert_eq!(TokenStandard::Erc721, multi_token.standard);
+    }
+
+    #[test]
+    #[cfg(feature = "token-accounts")]
+    fn try_from_verified_rejects_mislabeled_token() {
+        let nft = AccountId::from([12; 32]);
+        mock_token_accounts::STANDARDS.with(|x| x.borrow_mut().insert(nft, TokenStandard::Erc721));
+
+        assert_eq!(
+            Err(Error::InvalidTokenStandard),
+            MultiToken::try_from_verified(Token::new(nft, TokenStandard::Erc20)).map(|_| ())
+        );
+        assert_eq!(
+            Err(Error::InvalidTokenStandard),
+            MultiToken::try_from_verified(Token::new(nft, TokenStandard::Erc1155)).map(|_| ())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "token-accounts")]
+    fn try_from_verified_rejects_unknown_contract() {
+        assert_eq!(
+            Err(Error::InvalidTokenStandard),
+            MultiToken::try_from_verified(Token::new(AccountId::from([13; 32]), TokenStandard::Erc20)).map(|_| ())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "token-accounts")]
+    fn balance_of_all_tokens_works() {
+        let token_accounts = AccountId::from([1; 32]);
+        let (alice, bob) = (AccountId::from([2; 32]), AccountId::from([3; 32]));
+        let (usdt, dai, nft) = (AccountId::from([10; 32]), AccountId::from([11; 32]), AccountId::from([12; 32]));
+        register("USDT", usdt, TokenStandard::Erc20);
+        register("NFT", nft, TokenStandard::Erc721);
+        register("DAI", dai, TokenStandard::Erc20);
+        set_balance(usdt, alice, 100);
+        set_balance(dai, alice, 25);
+        set_balance(dai, bob, 7);
+
+        assert_eq!(
+            vec![(String::from("USDT"), 100), (String::from("DAI"), 25)],
+            balance_of_all_tokens(token_accounts, alice)
+        );
+        assert_eq!(
+            vec![(String::from("USDT"), 0), (String::from("DAI"), 7)],
+            balance_of_all_tokens(token_accounts, bob)
+        );
+    }
+}
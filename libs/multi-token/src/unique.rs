@@ -35,8 +35,57 @@ impl UniqueMultiToken {
     pub fn burn_from(&mut self, account: AccountId, amount: impl Into<Option<Balance>>) -> Result<()> {
         self.multi_token.burn_from(account, self.token_id, amount)
     }
+
+    /// Mints a new ERC-721 token with `metadata` to `recipient`, stores its id in `self.token_id` and registers the
+    /// token contract in `token_accounts` under `symbol`. The calling contract must own `token_accounts`. Fails with
+    /// `SymbolAlreadyRegistered` before minting if `symbol` is taken.
+    #[cfg(feature = "token-accounts")]
+    pub fn mint_and_register(
+        &mut self,
+        token_accounts: &mut token_accounts::TokenAccounts,
+        symbol: ink_prelude::string::String,
+        recipient: AccountId,
+        metadata: Vec<u8>,
+    ) -> Result<TokenId> {
+        self.multi_token.as_erc721()?;
+        if registered_token(token_accounts, symbol.clone()).is_some() {
+            return Err(Error::SymbolAlreadyRegistered(symbol));
+        }
+        let token_id = mint_with_metadata(self.multi_token, recipient, metadata)?;
+        self.token_id = Some(token_id);
+        register_token(token_accounts, symbol, self.multi_token)?;
+        Ok(token_id)
+    }
+}
+
+/// Calls `get_token` on `token_accounts`
+#[cfg(all(feature = "token-accounts", not(test)))]
+fn registered_token(
+    token_accounts: &token_accounts::TokenAccounts,
+    symbol: ink_prelude::string::String,
+) -> Option<token_accounts::Token> {
+    token_accounts.get_token(symbol)
 }
 
+/// Calls `set_token` on `token_accounts`
+#[cfg(all(feature = "token-accounts", not(test)))]
+fn register_token(
+    token_accounts: &mut token_accounts::TokenAccounts,
+    symbol: ink_prelude::string::String,
+    token: MultiToken,
+) -> Result<()> {
+    Ok(token_accounts.set_token(symbol, token.account_id, token.standard)?)
+}
+
+/// Calls `mint_with_metadata` on the ERC-721 `token`
+#[cfg(all(feature = "token-accounts", not(test)))]
+fn mint_with_metadata(token: MultiToken, recipient: AccountId, metadata: Vec<u8>) -> Result<TokenId> {
+    Ok(token.as_erc721_unchecked().mint_with_metadata(recipient, metadata)?)
+}
+
+#[cfg(all(test, feature = "token-accounts"))]
+use crate::tests::mock_token_accounts::{mint_with_metadata, register_token, registered_token};
+
 impl AsRef<MultiToken> for UniqueMultiToken {
     fn as_ref(&self) -> &MultiToken { &self.multi_token }
 }
@@ -36,6 +36,9 @@ describe('claimable media', () => {
       erc721Contract.address
     );
 
+    // the media contract mints an NFT for every media
+    await erc721Contract.tx.addMinter(mediaContract.address);
+
     const claimableMediaFactory = await getContractFactory(
       'claimable_media',
       Alice
@@ -46,6 +49,7 @@ describe('claimable media', () => {
         name: 'media',
         artists: [Alice.address],
         media: mediaContract.address,
+        media_type: 'Audio',
         erc1620: erc1620Contract,
         erc20: daiContract,
         view_conditions: {
@@ -57,12 +61,15 @@ describe('claimable media', () => {
           streaming_proportions: [],
           token_reward: [],
           token_entry: [],
-          duration: 10000000000
+          duration: 10000000000,
+          sharing_depth: 3,
+          entry_mode: 'HoldToWaive'
         },
         nft_conditions: {
           funding_token: daiContract.address,
           price: 5000
-        }
+        },
+        voting_window: null
       });
     };
 
@@ -84,6 +91,38 @@ describe('claimable media', () => {
     let media = await createMedia();
   });
 
+  it('Mints the media NFT with its royalty', async () => {
+    let { createMedia, erc721Contract, Alice } = await setup();
+
+    let media = await createMedia();
+    let result = await media.query.info();
+    // @ts-ignore
+    let mediaId = result.output.media_id;
+
+    // the claimable media creates its media with a royalty of 1, paid to the creator
+    let royaltyInfo = await erc721Contract.query.royaltyInfo(mediaId);
+    // @ts-ignore
+    let [receiver, royalty] = royaltyInfo.output.unwrap();
+    expect(receiver.toString()).to.equal(Alice.address);
+    expect(royalty.toNumber()).to.equal(1);
+  });
+
+  it('Registers and uploads its media', async () => {
+    let { createMedia, mediaContract } = await setup();
+
+    let media = await createMedia();
+    let result = await media.query.info();
+    // @ts-ignore
+    let mediaId = result.output.media_id;
+
+    // the media can be opened once it is released
+    let mediaInfo = await mediaContract.query.getMedia(mediaId);
+    // @ts-ignore
+    let info = mediaInfo.output.unwrap();
+    expect(info.is_registered.isTrue).to.equal(true);
+    expect(info.is_uploaded.isTrue).to.equal(true);
+  });
+
   it('should update artist', async () => {
     let { createMedia, Alice } = await setup();
     const artist = await getRandomSigner(Alice, '1 UNIT');
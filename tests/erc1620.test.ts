@@ -60,7 +60,9 @@ describe('erc1620', () => {
           10,
           erc20Contract.address,
           now,
-          now + 1000
+          now + 1000,
+          true,
+          null
         )
     ).events;
 
@@ -232,4 +232,217 @@ describe('exchange', () => {
       (await usdtContract.query.balanceOf(account1.address)).output
     ).to.equal(1011);
   });
+
+  it('Cancels all offers of an account', async () => {
+    const { exchangeContract, priviContract, usdtContract, Alice } =
+      await setup();
+
+    const account1 = await getRandomSigner(Alice, '10 UNIT');
+    const account2 = await getRandomSigner(Alice, '10 UNIT');
+
+    await priviContract.tx.transfer(account1.address, 1000);
+    await priviContract.tx.transfer(account2.address, 1000);
+    await usdtContract.tx.transfer(account2.address, 1000);
+    await priviContract
+      .connect(account1)
+      .tx.approve(exchangeContract.address, 1000);
+    await priviContract
+      .connect(account2)
+      .tx.approve(exchangeContract.address, 1000);
+    await usdtContract
+      .connect(account2)
+      .tx.approve(exchangeContract.address, 1000);
+
+    const priviToken = { account_id: priviContract.address, standard: 'Erc20' };
+    const usdtToken = { account_id: usdtContract.address, standard: 'Erc20' };
+
+    const events = (
+      await exchangeContract.connect(account1).tx.createExchange({
+        exchange_token: priviToken,
+        initial_amount: '10',
+        offer_token: usdtToken,
+        price: '2'
+      })
+    ).events;
+    const exchangeId = events[1].args[0].exchange_id;
+    const firstOfferId = events[1].args[0].offer_id;
+
+    // account2 quotes both sides of the market
+    for (const price of [1, 2]) {
+      await exchangeContract.connect(account2).tx.placeBuyingOffer({
+        exchange_id: exchangeId,
+        address: account2.address,
+        offer_token: usdtToken,
+        amount: 5,
+        price
+      });
+    }
+    await exchangeContract.connect(account2).tx.placeSellingOffer({
+      exchange_id: exchangeId,
+      address: account2.address,
+      offer_token: usdtToken,
+      amount: 3,
+      price: 4
+    });
+    expect(
+      (await usdtContract.query.balanceOf(account2.address)).output
+    ).to.equal(985);
+    expect(
+      (await priviContract.query.balanceOf(account2.address)).output
+    ).to.equal(997);
+
+    // all of account2's offers are cancelled and refunded at once
+    expect(
+      (await exchangeContract.connect(account2).query.cancelAllOffers()).output
+    ).to.eq({ Ok: 3 });
+    // a refund transfer and a CanceledOffer event per offer
+    const cancelEvents = (
+      await exchangeContract.connect(account2).tx.cancelAllOffers()
+    ).events;
+    expect(cancelEvents.length).to.equal(6);
+    expect(
+      (await usdtContract.query.balanceOf(account2.address)).output
+    ).to.equal(1000);
+    expect(
+      (await priviContract.query.balanceOf(account2.address)).output
+    ).to.equal(1000);
+
+    // account1's offer is untouched
+    const offers = (
+      await exchangeContract.query.getExchangeOffers(exchangeId)
+    ).output.unwrap();
+    expect(offers.length).to.equal(1);
+    expect(offers[0].id).to.equal(firstOfferId);
+  });
+
+  it('Generates unique offer ids', async () => {
+    const { exchangeContract, priviContract, usdtContract, Alice } =
+      await setup();
+
+    const account1 = await getRandomSigner(Alice, '10 UNIT');
+    await priviContract.tx.transfer(account1.address, 1000);
+    await priviContract
+      .connect(account1)
+      .tx.approve(exchangeContract.address, 1000);
+
+    const priviToken = { account_id: priviContract.address, standard: 'Erc20' };
+    const usdtToken = { account_id: usdtContract.address, standard: 'Erc20' };
+
+    const events = (
+      await exchangeContract.connect(account1).tx.createExchange({
+        exchange_token: priviToken,
+        initial_amount: '10',
+        offer_token: usdtToken,
+        price: '2'
+      })
+    ).events;
+    const exchangeId = events[1].args[0].exchange_id;
+    const ids = [
+      events[1].args[0].exchange_id.toString(),
+      events[1].args[0].offer_id.toString()
+    ];
+
+    // account1 places offers in quick succession
+    for (let i = 0; i < 10; i++) {
+      const offerEvents = (
+        await exchangeContract.connect(account1).tx.placeSellingOffer({
+          exchange_id: exchangeId,
+          address: account1.address,
+          offer_token: usdtToken,
+          amount: 1,
+          price: 2
+        })
+      ).events;
+      ids.push(offerEvents[1].args[0].offer_id.toString());
+    }
+
+    expect(new Set(ids).size).to.equal(ids.length);
+    const offers = (
+      await exchangeContract.query.getExchangeOffers(exchangeId)
+    ).output.unwrap();
+    expect(offers.length).to.equal(11);
+  });
+
+  it('Charges lower fees to high-volume traders', async () => {
+    const { exchangeContract, priviContract, usdtContract, Alice } =
+      await setup();
+
+    const account1 = await getRandomSigner(Alice, '10 UNIT');
+    const account2 = await getRandomSigner(Alice, '10 UNIT');
+
+    await priviContract.tx.transfer(account1.address, 1000);
+    await priviContract
+      .connect(account1)
+      .tx.approve(exchangeContract.address, 1000);
+    await usdtContract.tx.transfer(account2.address, 1000);
+    await usdtContract
+      .connect(account2)
+      .tx.approve(exchangeContract.address, 1000);
+
+    const priviToken = { account_id: priviContract.address, standard: 'Erc20' };
+    const usdtToken = { account_id: usdtContract.address, standard: 'Erc20' };
+
+    // 10% below a volume of 100, 5% from there on
+    expect(
+      (
+        await exchangeContract
+          .connect(account1)
+          .query.setFeeTiers([{ min_volume: 0, taker_fee_bps: 1000 }])
+      ).output
+    ).to.eq({ Err: 'Unauthorized' });
+    await exchangeContract.tx.setFeeTiers([
+      { min_volume: 0, taker_fee_bps: 1000 },
+      { min_volume: 100, taker_fee_bps: 500 }
+    ]);
+    expect(
+      (await exchangeContract.query.feeTierOf(account2.address)).output
+    ).to.equal(1000);
+
+    const events = (
+      await exchangeContract.connect(account1).tx.createExchange({
+        exchange_token: priviToken,
+        initial_amount: '100',
+        offer_token: usdtToken,
+        price: '2'
+      })
+    ).events;
+    const exchangeId = events[1].args[0].exchange_id;
+    const offerId = events[1].args[0].offer_id;
+    const ownerBalance = (await usdtContract.query.balanceOf(Alice.address))
+      .output;
+
+    // the first fill reaches the second tier
+    await exchangeContract.connect(account2).tx.buyFromOffer({
+      exchange_id: exchangeId,
+      offer_id: offerId,
+      address: account2.address,
+      amount: 50
+    });
+    expect(
+      (await usdtContract.query.balanceOf(account2.address)).output
+    ).to.equal(890);
+    expect(
+      (await exchangeContract.query.tradedVolume(account2.address)).output
+    ).to.equal(100);
+    expect(
+      (await exchangeContract.query.feeTierOf(account2.address)).output
+    ).to.equal(500);
+
+    // the next fill is charged the reduced fee
+    await exchangeContract.connect(account2).tx.buyFromOffer({
+      exchange_id: exchangeId,
+      offer_id: offerId,
+      address: account2.address,
+      amount: 20
+    });
+    expect(
+      (await usdtContract.query.balanceOf(account2.address)).output
+    ).to.equal(848);
+    expect(
+      (await usdtContract.query.balanceOf(Alice.address)).output
+    ).to.equal(ownerBalance.toNumber() + 12);
+    expect(
+      (await usdtContract.query.balanceOf(account1.address)).output
+    ).to.equal(140);
+  });
 });
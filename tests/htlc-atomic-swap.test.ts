@@ -74,6 +74,11 @@ describe('htlc-atomic-swap', () => {
     let output = result.output.unwrap();
     expect(output.secret_hash).to.equal(secretHash);
     expect(output.amount).to.equal(100);
+    expect(
+      (await htlcContract.query.getHtlcStatus(contractHash)).output
+        .unwrap()
+        .toString()
+    ).to.equal('Locked');
 
     // redspot is calling this twice for some reason, so I'm just checking the balance
     // claim the funds
@@ -88,6 +93,17 @@ describe('htlc-atomic-swap', () => {
     expect(
       (await usdtContract.query.balanceOf(charlie.address)).output
     ).to.equal(100);
+
+    // the contract is gone, but its status is kept
+    // @ts-ignore
+    expect(
+      (await htlcContract.query.getHtlcInfos([contractHash])).output[0].isNone
+    ).to.be.true;
+    expect(
+      (await htlcContract.query.getHtlcStatus(contractHash)).output
+        .unwrap()
+        .toString()
+    ).to.equal('Claimed');
   });
 
   it('Can refund funds', async () => {
@@ -136,6 +152,11 @@ describe('htlc-atomic-swap', () => {
     let output = result.output.unwrap();
     expect(output.secret_hash).to.equal(secretHash);
     expect(output.amount).to.equal(100);
+    expect(
+      (await htlcContract.query.getHtlcStatus(contractHash)).output
+        .unwrap()
+        .toString()
+    ).to.equal('Locked');
 
     // redspot is calling this twice for some reason, so I'm just checking the balance
     // refund the funds
@@ -149,5 +170,10 @@ describe('htlc-atomic-swap', () => {
     expect((await usdtContract.query.balanceOf(bob.address)).output).to.equal(
       100
     );
+    expect(
+      (await htlcContract.query.getHtlcStatus(contractHash)).output
+        .unwrap()
+        .toString()
+    ).to.equal('Refunded');
   });
 });
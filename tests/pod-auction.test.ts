@@ -24,6 +24,8 @@ describe('POD AUCTION', () => {
     // deploy erc721 contract
     const erc721contractFactory = await getContractFactory('erc721', Alice);
     const erc721contract = await erc721contractFactory.deployed('new');
+    // Bob mints the auctioned NFTs
+    await erc721contract.tx.addMinter(Bob.address);
 
     // deploy pod auction
     const podAuctionContractFactory = await getContractFactory(
@@ -46,6 +48,17 @@ describe('POD AUCTION', () => {
     return methods.includes('europa_backwardToHeight');
   }
 
+  // waits until the latest block is later than `timestamp`, submitting a transaction to produce blocks on demand
+  async function waitUntilAfter(podAuctionContract: any, timestamp: number) {
+    while (
+      Number((await podAuctionContract.query.getBlockTimeStamp()).output) <=
+      timestamp
+    ) {
+      await new Promise((resolve) => setTimeout(resolve, ONE_SECOND));
+      await podAuctionContract.tx.getBlockTimeStamp();
+    }
+  }
+
   it('Create, Bid and withdraw auction works', async () => {
     const { podAuctionContract, erc20contract, erc721contract, Alice, Bob } =
       await setup();
@@ -86,7 +99,11 @@ describe('POD AUCTION', () => {
         start_time: now + ONE_SECOND * startTimeSeconds,
         end_time: now + ONE_SECOND * 100,
         reserve_price: 1,
-        ipfs_hash: 'ipfs hash'
+        ipfs_hash: 'ipfs hash',
+        auction_kind: 'English',
+        start_price: 0,
+        end_price: 0,
+        opening_policy: 'AtLeastReserve'
       })
     ).to.emit(podAuctionContract, 'AuctionCreated');
 
@@ -104,7 +121,8 @@ describe('POD AUCTION', () => {
       podAuctionContract.tx.placeBid({
         token_address: erc20contract.address,
         owner: Bob.address,
-        amount: 1000
+        amount: 1000,
+        nonce: null
       })
     ).to.emit(podAuctionContract, 'BidPlaced');
 
@@ -125,13 +143,13 @@ describe('POD AUCTION', () => {
     const balanceOfBob = await erc20contract.query.balanceOf(Bob.address);
     expect(balanceOfBob.output).to.equal(0);
 
-    //check that auction is not withdrawn
+    //check that auction is still open
     const auction1 = await podAuctionContract.query.getAuctionByPair(
       erc20contract.address,
       Bob.address
     );
     // @ts-ignore
-    expect(auction1.output.unwrap().withdrawn).to.equal(false);
+    expect(auction1.output.unwrap().status.toString()).to.equal('Open');
 
     // withdraw auction
     const withdrawAuction = podAuctionContract.connect(Bob).tx.withdrawAuction({
@@ -157,6 +175,145 @@ describe('POD AUCTION', () => {
     await expect(auctionsQuery2.output.length).to.equal(0);
   }).timeout(120000);
 
+  it('Anyone can settle an ended auction', async () => {
+    const { podAuctionContract, erc20contract, erc721contract, Alice, Bob } =
+      await setup();
+    const Charlie = (await getSigners())[2];
+
+    await expect(erc721contract.connect(Bob).tx.mint(Bob.address)).to.emit(
+      erc721contract,
+      'Transfer'
+    );
+    await expect(
+      erc721contract.connect(Bob).tx.approve(podAuctionContract.address, 1)
+    ).to.emit(erc721contract, 'Approval');
+    await podAuctionContract.tx.approveUser(Bob.address);
+    await podAuctionContract.tx.approveUser(Alice.address);
+
+    const time = await podAuctionContract.query.getBlockTimeStamp();
+    let now = Number(time.output);
+    const startTime = now + ONE_SECOND * 3;
+    const endTime = now + ONE_SECOND * 30;
+
+    await expect(
+      podAuctionContract.connect(Bob).tx.createAuction({
+        media_address: erc721contract.address,
+        media_token_id: 1,
+        token_address: erc20contract.address,
+        owner: Bob.address,
+        bid_increment: 10,
+        start_time: startTime,
+        end_time: endTime,
+        reserve_price: 1,
+        ipfs_hash: 'ipfs hash',
+        auction_kind: 'English',
+        start_price: 0,
+        end_price: 0,
+        opening_policy: 'AtLeastReserve'
+      })
+    ).to.emit(podAuctionContract, 'AuctionCreated');
+    await expect(
+      erc20contract.tx.approve(podAuctionContract.address, 10000)
+    ).to.emit(erc20contract, 'Approval');
+
+    // Alice places the only bid
+    await waitUntilAfter(podAuctionContract, startTime);
+    await expect(
+      podAuctionContract.tx.placeBid({
+        token_address: erc20contract.address,
+        owner: Bob.address,
+        amount: 1000,
+        nonce: null
+      })
+    ).to.emit(podAuctionContract, 'BidPlaced');
+
+    // the auction can't be settled before it ended
+    const settle = () =>
+      podAuctionContract
+        .connect(Charlie)
+        .tx.settle(erc20contract.address, Bob.address);
+    await expect(settle()).to.not.emit(podAuctionContract, 'AuctionSettled');
+
+    // Charlie settles the auction once it ended
+    await waitUntilAfter(podAuctionContract, endTime);
+    await expect(settle()).to.emit(podAuctionContract, 'AuctionSettled');
+
+    // Alice owns the nft and Bob got the bid
+    const ownerOfNft = await erc721contract.query.ownerOf(1);
+    expect(ownerOfNft.output).to.equal(Alice.address);
+    const balanceOfBob = await erc20contract.query.balanceOf(Bob.address);
+    expect(balanceOfBob.output).to.equal(1000);
+
+    const auction = await podAuctionContract.query.getAuctionByPair(
+      erc20contract.address,
+      Bob.address
+    );
+    // @ts-ignore
+    expect(auction.output.unwrap().status.toString()).to.equal('Settled');
+
+    // a settled auction can't be settled again
+    await expect(settle()).to.not.emit(podAuctionContract, 'AuctionSettled');
+  }).timeout(120000);
+
+  it('Cancelling is limited to the grace period', async () => {
+    const { podAuctionContract, erc20contract, erc721contract, Bob } =
+      await setup();
+    const ONE_DAY = ONE_SECOND * 60 * 60 * 24;
+
+    await expect(erc721contract.connect(Bob).tx.mint(Bob.address)).to.emit(
+      erc721contract,
+      'Transfer'
+    );
+    await expect(
+      erc721contract.connect(Bob).tx.approve(podAuctionContract.address, 1)
+    ).to.emit(erc721contract, 'Approval');
+    await podAuctionContract.tx.approveUser(Bob.address);
+
+    // the grace period defaults to one day
+    const cancelGrace = await podAuctionContract.query.cancelGrace();
+    expect(Number(cancelGrace.output)).to.equal(ONE_DAY);
+
+    const time = await podAuctionContract.query.getBlockTimeStamp();
+    const endTime = Number(time.output) + ONE_SECOND * 10;
+    await expect(
+      podAuctionContract.connect(Bob).tx.createAuction({
+        media_address: erc721contract.address,
+        media_token_id: 1,
+        token_address: erc20contract.address,
+        owner: Bob.address,
+        bid_increment: 10,
+        start_time: endTime - ONE_SECOND * 5,
+        end_time: endTime,
+        reserve_price: 1,
+        ipfs_hash: 'ipfs hash',
+        auction_kind: 'English',
+        start_price: 0,
+        end_price: 0,
+        opening_policy: 'AtLeastReserve'
+      })
+    ).to.emit(podAuctionContract, 'AuctionCreated');
+    await waitUntilAfter(podAuctionContract, endTime);
+
+    const cancel = () =>
+      podAuctionContract.connect(Bob).tx.cancelAuction({
+        token_address: erc20contract.address,
+        owner: Bob.address
+      });
+
+    // without a grace period, the auction can't be cancelled after its end time
+    await podAuctionContract.tx.setCancelGrace(0);
+    await expect(cancel()).to.not.emit(podAuctionContract, 'AuctionCanceled');
+
+    // within the grace period it can
+    await podAuctionContract.tx.setCancelGrace(ONE_DAY);
+    await expect(cancel()).to.emit(podAuctionContract, 'AuctionCanceled');
+
+    // only the contract owner may change the grace period
+    await podAuctionContract.connect(Bob).tx.setCancelGrace(0);
+    const cancelGraceAfter = await podAuctionContract.query.cancelGrace();
+    expect(Number(cancelGraceAfter.output)).to.equal(ONE_DAY);
+  }).timeout(120000);
+
   it('Reset & Cancel auction works', async () => {
     const { podAuctionContract, erc20contract, erc721contract, Alice, Bob } =
       await setup();
@@ -197,7 +354,11 @@ describe('POD AUCTION', () => {
         start_time: now + ONE_SECOND * startTimeSeconds,
         end_time: now + ONE_SECOND * 100,
         reserve_price: 1,
-        ipfs_hash: 'ipfs hash'
+        ipfs_hash: 'ipfs hash',
+        auction_kind: 'English',
+        start_price: 0,
+        end_price: 0,
+        opening_policy: 'AtLeastReserve'
       })
     ).to.emit(podAuctionContract, 'AuctionCreated');
 
@@ -211,7 +372,8 @@ describe('POD AUCTION', () => {
       podAuctionContract.tx.placeBid({
         token_address: erc20contract.address,
         owner: Bob.address,
-        amount: 1000
+        amount: 1000,
+        nonce: null
       })
     ).to.emit(podAuctionContract, 'BidPlaced');
 
@@ -259,7 +421,8 @@ describe('POD AUCTION', () => {
       podAuctionContract.tx.placeBid({
         token_address: erc20contract.address,
         owner: Bob.address,
-        amount: 1000
+        amount: 1000,
+        nonce: null
       })
     ).to.emit(podAuctionContract, 'BidPlaced');
 
@@ -283,4 +446,93 @@ describe('POD AUCTION', () => {
     const balanceOfAlice3 = await erc20contract.query.balanceOf(Alice.address);
     expect(balanceOfAlice3.output).to.equal(10000);
   }).timeout(120000);
+
+  it('Applies the opening policy to the first bid', async () => {
+    const { podAuctionContract, erc20contract, erc721contract, Alice, Bob } =
+      await setup();
+
+    // a second token, as an owner can only run one auction per token
+    const erc20contractFactory = await getContractFactory('erc20', Alice);
+    const otherErc20contract = await erc20contractFactory.deployed(
+      'new',
+      '10000'
+    );
+
+    // Bob creates two erc 721 and approves pod auction to spend them
+    for (const tokenId of [1, 2]) {
+      await expect(erc721contract.connect(Bob).tx.mint(Bob.address)).to.emit(
+        erc721contract,
+        'Transfer'
+      );
+      await expect(
+        erc721contract
+          .connect(Bob)
+          .tx.approve(podAuctionContract.address, tokenId)
+      ).to.emit(erc721contract, 'Approval');
+    }
+
+    await podAuctionContract.tx.approveUser(Bob.address);
+    await podAuctionContract.tx.approveUser(Alice.address);
+
+    const time = await podAuctionContract.query.getBlockTimeStamp();
+    let now = Number(time.output);
+
+    let startTimeSeconds = 12;
+    if (await isEuropa()) {
+      startTimeSeconds = 3;
+    }
+
+    // both auctions have a reserve of 500, but only the first one requires the opening bid to meet it
+    const policies: [any, number, string][] = [
+      [erc20contract, 1, 'AtLeastReserve'],
+      [otherErc20contract, 2, 'AnyAboveZero']
+    ];
+    for (const [token, tokenId, policy] of policies) {
+      await expect(
+        podAuctionContract.connect(Bob).tx.createAuction({
+          media_address: erc721contract.address,
+          media_token_id: tokenId,
+          token_address: token.address,
+          owner: Bob.address,
+          bid_increment: 10,
+          start_time: now + ONE_SECOND * startTimeSeconds,
+          end_time: now + ONE_SECOND * 100,
+          reserve_price: 500,
+          ipfs_hash: 'ipfs hash',
+          auction_kind: 'English',
+          start_price: 0,
+          end_price: 0,
+          opening_policy: policy
+        })
+      ).to.emit(podAuctionContract, 'AuctionCreated');
+
+      await expect(
+        token.tx.approve(podAuctionContract.address, 10000)
+      ).to.emit(token, 'Approval');
+    }
+
+    const bid = (token: any, amount: number) =>
+      podAuctionContract.tx.placeBid({
+        token_address: token.address,
+        owner: Bob.address,
+        amount,
+        nonce: null
+      });
+
+    // an opening bid below the reserve is only accepted by AnyAboveZero
+    await expect(bid(erc20contract, 100)).to.not.emit(
+      podAuctionContract,
+      'BidPlaced'
+    );
+    await expect(bid(otherErc20contract, 100)).to.emit(
+      podAuctionContract,
+      'BidPlaced'
+    );
+
+    // AtLeastReserve accepts an opening bid at the reserve
+    await expect(bid(erc20contract, 500)).to.emit(
+      podAuctionContract,
+      'BidPlaced'
+    );
+  }).timeout(120000);
 });
@@ -1,4 +1,5 @@
 import BN from 'bn.js';
+import { expect } from 'chai';
 import { patract, network, artifacts } from 'redspot';
 
 const { getContractFactory, getRandomSigner } = patract;
@@ -42,6 +43,9 @@ describe('pod-media-investing', () => {
       erc721Contract.address
     );
 
+    // the media contract mints an NFT for every media
+    await erc721Contract.tx.addMinter(mediaContract.address);
+
     const podMediaInvesting = await getContractFactory(
       'pod_media_investing',
       Alice
@@ -59,11 +63,15 @@ describe('pod-media-investing', () => {
     };
   }
 
-  it('Can create a pod', async () => {
-    let { podMediaInvesting, daiContract, mediaContract, Alice } =
-      await setup();
+  const day = 24 * 60 * 60 * 1000;
 
-    let inititiate_investing_pod_request = {
+  function createPodRequest(
+    daiContract: any,
+    mediaContract: any,
+    Alice: any,
+    overrides: any = {}
+  ) {
+    return {
       pod_token_symbol: 'PODDAI',
       pod_token_name: 'PODDAI',
       funding_token: daiContract.address,
@@ -72,8 +80,9 @@ describe('pod-media-investing', () => {
       amm: 'Quadratic',
       spread: 1,
       max_price: 300000,
-      max_supply: 150000000,
-      funding_date: 1629237600000,
+      max_supply: 1000000000000,
+      funding_date: Date.now() + day,
+      max_per_investor: null,
       erc20_code_hash: daiContract.abi.project.source.wasmHash,
       media_contract: mediaContract.address,
       medias: [
@@ -89,7 +98,9 @@ describe('pod-media-investing', () => {
             streaming_proportions: [],
             token_reward: [],
             token_entry: [],
-            duration: 10000000000
+            duration: 10000000000,
+            sharing_depth: 3,
+            entry_mode: 'HoldToWaive'
           },
           nft_conditions: {
             funding_token: daiContract.address,
@@ -98,8 +109,407 @@ describe('pod-media-investing', () => {
           royalty: 50,
           collabs: [[Alice.address, 1_000_000_000]]
         }
-      ]
+      ],
+      registry: null,
+      ...overrides
     };
+  }
+
+  // registers every media of the pod, which moves the pod to Investing
+  async function registerMedias(
+    pod: any,
+    daiContract: any,
+    mediaContract: any,
+    Alice: any
+  ) {
+    const mediaIds = (await mediaContract.query.mediaIdsPaged(0, 10)).output;
+    for (const mediaId of mediaIds) {
+      await pod.tx.registerMedia({
+        media_id: mediaId,
+        funding_token: daiContract.address,
+        price: 50,
+        release_date: Date.now() + day,
+        payment_type: 'Fixed',
+        royalty: 50,
+        collabs: [[Alice.address, 1_000_000_000]]
+      });
+    }
+  }
+
+  it('Can create a pod', async () => {
+    let { podMediaInvesting, daiContract, mediaContract, Alice } =
+      await setup();
+
+    let inititiate_investing_pod_request = createPodRequest(
+      daiContract,
+      mediaContract,
+      Alice
+    );
     await podMediaInvesting.deploy('new', inititiate_investing_pod_request);
   });
+
+  it('Creator can grant the minter role on the pod token', async () => {
+    let { podMediaInvesting, erc20Factory, daiContract, mediaContract, Alice } =
+      await setup();
+    const Bob = (await getSigners())[1];
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    const podToken = erc20Factory.attach(
+      (await pod.query.podToken()).output.toString()
+    );
+
+    // only the creator may grant the role
+    await pod.connect(Bob).tx.grantMinterTo(Bob.address);
+    expect((await podToken.query.rolesOf(Bob.address)).output).to.equal(0);
+
+    // Bob can mint pod tokens once Bob is a minter (role bit 2)
+    await expect(pod.tx.grantMinterTo(Bob.address)).to.emit(
+      podToken,
+      'AddedMinter'
+    );
+    expect((await podToken.query.rolesOf(Bob.address)).output).to.equal(2);
+    await expect(podToken.connect(Bob).tx.mint(Bob.address, 1000)).to.emit(
+      podToken,
+      'Transfer'
+    );
+    expect((await podToken.query.balanceOf(Bob.address)).output).to.equal(
+      1000
+    );
+  });
+
+  it('Caps the investment per investor', async () => {
+    let { podMediaInvesting, daiContract, mediaContract, Alice } =
+      await setup();
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice, {
+        max_per_investor: 560000
+      })
+    );
+    await registerMedias(pod, daiContract, mediaContract, Alice);
+    await daiContract.tx.approve(pod.address, 1000000);
+
+    await pod.tx.investPod(280000);
+    expect((await pod.query.invested(Alice.address)).output).to.equal(280000);
+
+    // the cap applies to the total invested by the account
+    expect((await pod.query.investPod(560000)).output).to.eq({
+      Err: 'InvestorCapExceeded'
+    });
+    await pod.tx.investPod(280000);
+    expect((await pod.query.invested(Alice.address)).output).to.equal(560000);
+  });
+
+  it('Does not cap investors without max_per_investor', async () => {
+    let { podMediaInvesting, daiContract, mediaContract, Alice } =
+      await setup();
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    await registerMedias(pod, daiContract, mediaContract, Alice);
+    await daiContract.tx.approve(pod.address, 100000000);
+
+    await pod.tx.investPod(28000000);
+    await pod.tx.investPod(28000000);
+    expect((await pod.query.invested(Alice.address)).output).to.equal(
+      56000000
+    );
+  });
+
+  it('Creator reclaims the unsold pod tokens once', async () => {
+    let { podMediaInvesting, erc20Factory, daiContract, mediaContract, Alice } =
+      await setup();
+    const Bob = (await getSigners())[1];
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    const podToken = erc20Factory.attach(
+      (await pod.query.podToken()).output.toString()
+    );
+    await registerMedias(pod, daiContract, mediaContract, Alice);
+    await daiContract.tx.approve(pod.address, 28000000000);
+
+    // 99999 of the 100000 pod tokens are sold, the last funding token buys none
+    await pod.tx.investPod(27999999999);
+    expect((await pod.query.reclaimUnsold()).output).to.eq({
+      Err: 'PodNotInTradingState'
+    });
+    await pod.tx.investPod(1);
+
+    // pod tokens sent to the pod are not part of the unsold supply
+    await podToken.tx.transfer(pod.address, 10);
+
+    expect((await pod.connect(Bob).query.reclaimUnsold()).output).to.eq({
+      Err: 'Unauthorized'
+    });
+    await expect(pod.tx.reclaimUnsold()).to.emit(pod, 'UnsoldReclaimed');
+    expect((await podToken.query.balanceOf(Alice.address)).output).to.equal(
+      99990
+    );
+    expect((await pod.query.reclaimUnsold()).output).to.eq({
+      Err: 'UnsoldAlreadyReclaimed'
+    });
+  });
+
+  it('Reports the funding progress', async () => {
+    let { podMediaInvesting, daiContract, mediaContract, Alice } =
+      await setup();
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    await registerMedias(pod, daiContract, mediaContract, Alice);
+    await daiContract.tx.approve(pod.address, 30000000000);
+
+    expect((await pod.query.fundingProgress()).output).to.eq([
+      0,
+      28000000000,
+      0
+    ]);
+
+    await pod.tx.investPod(7000000000);
+    expect((await pod.query.fundingProgress()).output).to.eq([
+      7000000000,
+      28000000000,
+      25
+    ]);
+    let state = (await pod.query.podState()).output;
+    expect(state.status).to.equal('Investing');
+    expect(state.raised_funds).to.equal(7000000000);
+
+    // investing more than the remaining target only raises the rest of it
+    await pod.tx.investPod(30000000000);
+    expect((await pod.query.fundingProgress()).output).to.eq([
+      28000000000,
+      28000000000,
+      100
+    ]);
+    state = (await pod.query.podState()).output;
+    expect(state.status).to.equal('Trading');
+    expect(state.raised_funds).to.equal(28000000000);
+  });
+
+  it('Restricts investing to the allowlist while it is enabled', async () => {
+    let { podMediaInvesting, daiContract, mediaContract, Alice } =
+      await setup();
+    const Bob = (await getSigners())[1];
+    const Charlie = (await getSigners())[2];
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    await registerMedias(pod, daiContract, mediaContract, Alice);
+    for (const investor of [Bob, Charlie]) {
+      await daiContract.tx.transfer(investor.address, 1000000);
+      await daiContract.connect(investor).tx.approve(pod.address, 1000000);
+    }
+
+    // disabled: anyone may invest
+    expect((await pod.query.isAllowlisted(Charlie.address)).output).to.be.true;
+    await pod.connect(Charlie).tx.investPod(280000);
+    expect((await pod.query.invested(Charlie.address)).output).to.equal(
+      280000
+    );
+
+    // enabled: only allowlisted accounts may invest
+    await pod.tx.addInvestor(Bob.address);
+    await pod.tx.setAllowlistEnabled(true);
+    expect((await pod.query.isAllowlisted(Bob.address)).output).to.be.true;
+    expect((await pod.query.isAllowlisted(Charlie.address)).output).to.be
+      .false;
+    await pod.connect(Bob).tx.investPod(280000);
+    expect((await pod.query.invested(Bob.address)).output).to.equal(280000);
+    expect(
+      (await pod.connect(Charlie).query.investPod(280000)).output
+    ).to.eq({ Err: 'NotAllowlisted' });
+
+    // removed investors can't invest anymore
+    await pod.tx.removeInvestor(Bob.address);
+    expect((await pod.connect(Bob).query.investPod(280000)).output).to.eq({
+      Err: 'NotAllowlisted'
+    });
+
+    // only the creator manages the allowlist
+    expect(
+      (await pod.connect(Bob).query.setAllowlistEnabled(false)).output
+    ).to.eq({ Err: 'Unauthorized' });
+  });
+
+  it('Creator sweeps foreign tokens but not the pod assets', async () => {
+    let { podMediaInvesting, erc20Factory, daiContract, mediaContract, Alice } =
+      await setup();
+    const Bob = (await getSigners())[1];
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    const podToken = (await pod.query.podToken()).output.toString();
+    const usdtContract = await erc20Factory.deployed(
+      'new_optional',
+      '1000000000',
+      'USDT',
+      'USDT',
+      12
+    );
+    await usdtContract.tx.transfer(pod.address, 1000);
+
+    expect(
+      (
+        await pod
+          .connect(Bob)
+          .query.sweepForeignToken(usdtContract.address, Bob.address)
+      ).output
+    ).to.eq({ Err: 'Unauthorized' });
+    await expect(
+      pod.tx.sweepForeignToken(usdtContract.address, Bob.address)
+    ).to.emit(pod, 'ForeignTokenSwept');
+    expect((await usdtContract.query.balanceOf(Bob.address)).output).to.equal(
+      1000
+    );
+    expect((await usdtContract.query.balanceOf(pod.address)).output).to.equal(
+      0
+    );
+
+    for (const token of [daiContract.address, podToken]) {
+      expect(
+        (await pod.query.sweepForeignToken(token, Alice.address)).output
+      ).to.eq({ Err: 'CannotSweepPodAsset' });
+    }
+  });
+
+  it('Creator updates the curve during formation', async () => {
+    let { podMediaInvesting, daiContract, mediaContract, Alice } =
+      await setup();
+    const Bob = (await getSigners())[1];
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    // the price of the whole supply depends on every curve parameter
+    const quote = async () =>
+      (await pod.query.quoteBuy(1000000000000)).output.toString();
+    const initialQuote = await quote();
+
+    expect(
+      (
+        await pod
+          .connect(Bob)
+          .query.updateCurve(400000, 1000000000000, 'Quadratic')
+      ).output
+    ).to.eq({ Err: 'Unauthorized' });
+
+    // the max price is below the funding token price
+    expect(
+      (await pod.query.updateCurve(1, 1000000000000, 'Quadratic')).output
+    ).to.eq({ Err: 'InvalidCurveParams' });
+    expect(await quote()).to.equal(initialQuote);
+
+    await pod.tx.updateCurve(400000, 1000000000000, 'Quadratic');
+    expect(await quote()).to.not.equal(initialQuote);
+
+    await registerMedias(pod, daiContract, mediaContract, Alice);
+    expect(
+      (await pod.query.updateCurve(400000, 1000000000000, 'Quadratic')).output
+    ).to.eq({ Err: 'PodNotInFormationState' });
+  });
+
+  it('Quotes match the executed trades', async () => {
+    let { podMediaInvesting, daiContract, mediaContract, Alice } =
+      await setup();
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    const daiBalance = async () =>
+      new BN(
+        (await daiContract.query.balanceOf(Alice.address)).output.toString()
+      );
+    await daiContract.tx.approve(pod.address, '1000000000000000000000');
+
+    const buyQuote = (await pod.query.quoteBuy(1000000000)).output.asOk;
+    let before = await daiBalance();
+    await pod.tx.buyPodTokens(1000000000);
+    expect(before.sub(await daiBalance()).toString()).to.equal(
+      buyQuote.toString()
+    );
+
+    const sellQuote = (await pod.query.quoteSell(1000000000)).output.asOk;
+    before = await daiBalance();
+    await pod.tx.sellPodTokens(1000000000);
+    expect((await daiBalance()).sub(before).toString()).to.equal(
+      sellQuote.toString()
+    );
+  });
+
+  it('Creator adds media during formation', async () => {
+    let { podMediaInvesting, daiContract, mediaContract, Alice } =
+      await setup();
+    const Bob = (await getSigners())[1];
+
+    const request = createPodRequest(daiContract, mediaContract, Alice);
+    const pod = await podMediaInvesting.deployed('new', request);
+
+    expect(
+      (await pod.connect(Bob).query.addMedia(request.medias)).output
+    ).to.eq({ Err: 'Unauthorized' });
+
+    await pod.tx.addMedia(request.medias);
+    expect((await mediaContract.query.mediaCount()).output).to.equal(2);
+    expect((await pod.query.podState()).output.total_media).to.equal(2);
+
+    // the pod only moves on to Investing once the added media is registered too
+    await registerMedias(pod, daiContract, mediaContract, Alice);
+    expect((await pod.query.podState()).output.status).to.equal('Investing');
+    expect((await pod.query.addMedia(request.medias)).output).to.eq({
+      Err: 'PodNotInFormationState'
+    });
+  });
+
+  it('Sells at most the released supply held by the caller', async () => {
+    let { podMediaInvesting, erc20Factory, daiContract, mediaContract, Alice } =
+      await setup();
+    const Bob = (await getSigners())[1];
+
+    const pod = await podMediaInvesting.deployed(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    const podToken = erc20Factory.attach(
+      (await pod.query.podToken()).output.toString()
+    );
+    await daiContract.tx.approve(pod.address, '1000000000000000000000');
+    await pod.tx.buyPodTokens(1000000000);
+
+    expect((await pod.query.sellPodTokens(1000000001)).output).to.eq({
+      Err: 'ExceedsReleasedSupply'
+    });
+
+    await podToken.tx.transfer(Bob.address, 500000000);
+    expect(
+      (await pod.connect(Bob).query.sellPodTokens(1000000000)).output
+    ).to.eq({ Err: 'InsufficientPodTokens' });
+    expect((await pod.query.sellPodTokens(1000000000)).output).to.eq({
+      Err: 'InsufficientPodTokens'
+    });
+
+    await podToken.connect(Bob).tx.transfer(Alice.address, 500000000);
+    await pod.tx.sellPodTokens(1000000000);
+    expect((await pod.query.podState()).output.supply_released).to.equal(0);
+    expect((await podToken.query.balanceOf(Alice.address)).output).to.equal(
+      0
+    );
+  });
 });
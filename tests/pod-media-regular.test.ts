@@ -40,6 +40,9 @@ describe('pod-media-regular', () => {
       erc721Contract.address
     );
 
+    // the media contract mints an NFT for every media
+    await erc721Contract.tx.addMinter(mediaContract.address);
+
     const podMediaRegularFactory = await getContractFactory(
       'pod_media_regular',
       Alice
@@ -56,39 +59,103 @@ describe('pod-media-regular', () => {
     };
   }
 
-  it('Can create a pod', async () => {
-    let { podMediaRegularFactory, daiContract, mediaContract, Alice } =
-      await setup();
-    let pod = await podMediaRegularFactory.deploy('new', {
+  function createPodRequest(daiContract: any, mediaContract: any, Alice: any) {
+    return {
       erc20_code_hash: daiContract.abi.project.source.wasmHash,
       endowment: 1000000000,
       media_contract: mediaContract.address,
-      medias: [
-        {
-          name: 'test media',
-          type: 'Blog',
-          view_conditions: {
-            viewing_type: 'Fixed',
-            viewing_token: daiContract.address,
-            price: 50,
-            sharing_percent: 10,
-            is_streaming_live: false,
-            streaming_proportions: [],
-            token_reward: [],
-            token_entry: [],
-            duration: 10000000000
-          },
-          nft_conditions: {
-            funding_token: daiContract.address,
-            price: 5000
-          },
-          royalty: 50,
-          collabs: [[Alice.address, 1_000_000_000]]
-        }
-      ]
-    });
+      medias: [mediaRequest(daiContract, Alice)],
+      registry: null
+    };
+  }
+
+  function mediaRequest(daiContract: any, Alice: any) {
+    return {
+      name: 'test media',
+      type: 'Blog',
+      view_conditions: {
+        viewing_type: 'Fixed',
+        viewing_token: daiContract.address,
+        price: 50,
+        sharing_percent: 10,
+        is_streaming_live: false,
+        streaming_proportions: [],
+        token_reward: [],
+        token_entry: [],
+        duration: 10000000000,
+        sharing_depth: 3,
+        entry_mode: 'HoldToWaive'
+      },
+      nft_conditions: {
+        funding_token: daiContract.address,
+        price: 5000
+      },
+      royalty: 50,
+      collabs: [[Alice.address, 1_000_000_000]]
+    };
+  }
+
+  // decodes the events `contract` emitted in the latest block
+  async function emittedEvents(contract: any) {
+    const records: any = await api.query.system.events();
+    return records
+      .filter(
+        ({ event }: any) =>
+          event.section === 'contracts' &&
+          event.method === 'ContractEmitted' &&
+          event.data[0].toString() === contract.address.toString()
+      )
+      .map(({ event }: any) => contract.abi.decodeEvent(event.data[1]));
+  }
+
+  it('Can create a pod', async () => {
+    let { podMediaRegularFactory, daiContract, mediaContract, Alice } =
+      await setup();
+    let pod = await podMediaRegularFactory.deploy(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
 
     let result = await pod.query.creator();
     expect(result.output).to.equal(Alice.address);
   });
+
+  it('Emits PodCreated with the created media', async () => {
+    let { podMediaRegularFactory, daiContract, mediaContract, Alice } =
+      await setup();
+    const pod = await podMediaRegularFactory.deploy(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+
+    const podCreated = (await emittedEvents(pod)).find(
+      (event: any) => event.event.identifier === 'PodCreated'
+    );
+    expect(podCreated).to.not.be.undefined;
+    expect(podCreated.args[0].toString()).to.equal(Alice.address.toString());
+    expect(podCreated.args[1].toString()).to.equal(
+      (await mediaContract.query.mediaIdsPaged(0, 10)).output.toString()
+    );
+  });
+
+  it('Creator adds media to the pod', async () => {
+    let { podMediaRegularFactory, daiContract, mediaContract, Alice } =
+      await setup();
+    const Bob = (await getSigners())[1];
+    const pod = await podMediaRegularFactory.deploy(
+      'new',
+      createPodRequest(daiContract, mediaContract, Alice)
+    );
+    const medias = [
+      mediaRequest(daiContract, Alice),
+      mediaRequest(daiContract, Alice)
+    ];
+
+    expect((await pod.connect(Bob).query.addMedia(medias)).output).to.eq({
+      Err: 'Unauthorized'
+    });
+
+    await pod.tx.addMedia(medias);
+    expect((await mediaContract.query.mediaCount()).output).to.equal(3);
+  });
 });